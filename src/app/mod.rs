@@ -5,10 +5,17 @@ use crate::{
         error::{AppError, AppResult},
     },
     database,
-    helpers::google_auth,
+    helpers::{eip1271, google_auth, mailer::{self, Mailer}},
     nostr,
     queue::msg_queue::{MessageQueue, RedisMessage, RedisStreamPool},
-    server::{http_server_start, middlewares::jwt::jwt_handler, events::events_message::Event},
+    server::{
+        auth::{auth_service::RedisClient, twitter_client::TwitterClient},
+        binding::provider::SocialProviders,
+        events::events_message::Event,
+        http_server_start,
+        middlewares::jwt::jwt_handler,
+        notification::{notification_service, Notification},
+    },
 };
 // use ::nostr::event::Kind;
 use oauth2::basic::BasicClient;
@@ -21,35 +28,45 @@ pub struct AppState {
     pub store: database::Storage,
     pub jwt_handler: jwt_handler::JwtHandler,
     pub oauth: BasicClient,
+    pub twitter: TwitterClient,
+    pub social: SocialProviders,
+    pub mailer: Arc<dyn Mailer>,
     pub redis: redis::Client,
     pub queue: RedisStreamPool,
     pub nclient: nostr::NostrClient,
+    pub relay: nostr::relay::EmbeddedRelay,
+    pub sig_checker: Option<Arc<dyn eip1271::SignatureChecker>>,
 }
 
 impl AppState {
-    pub async fn new(path: PathBuf) -> Self {
+    pub async fn new(path: PathBuf) -> AppResult<Self> {
         let config = Config::load_config(path).unwrap();
         let store = database::Storage::new(config.database.clone()).await;
 
-        let secret = consts::JWT_SECRET_KEY.to_string();
-        let jwt_handler = jwt_handler::JwtHandler { secret };
+        let jwt_handler = jwt_handler::JwtHandler::new(&config.jwt)?;
+        let twitter = TwitterClient::new(&config.auth, &config.twitter)?;
+        let oauth = google_auth::oauth_client(config.auth.clone(), &config.twitter);
+        let mailer = mailer::mailer(&config.mail)?;
+        let social = SocialProviders::new(&config, twitter.clone(), oauth.clone(), mailer.clone())?;
 
-        Self {
+        Ok(Self {
             config: config.clone(),
             store,
             jwt_handler,
-            oauth: google_auth::oauth_client(config.auth),
+            oauth,
+            twitter,
+            social,
+            mailer,
             redis: redis::Client::open(config.redis.redis_url.as_str()).unwrap(),
             queue: RedisStreamPool::new(config.redis.redis_url.as_str())
                 .await
                 .unwrap(),
-            nclient: nostr::NostrClient::new(
-                config.nostr.priv_key.as_str(),
-                Some(config.nostr.ws_url.as_str()),
-            )
+            nclient: nostr::NostrClient::new(config.nostr.priv_key.as_str(), &config.nostr.relays())
             .await
             .unwrap(),
-        }
+            relay: nostr::relay::EmbeddedRelay::new(&config.nostr.relay),
+            sig_checker: eip1271::signature_checker(&config.eip1271),
+        })
     }
 }
 
@@ -65,14 +82,17 @@ impl Deref for SharedState {
 }
 
 impl SharedState {
-    pub async fn new(path: PathBuf) -> Self {
-        let state = AppState::new(path).await;
-        SharedState(Arc::new(state))
+    pub async fn new(path: PathBuf) -> AppResult<Self> {
+        let state = AppState::new(path).await?;
+        Ok(SharedState(Arc::new(state)))
     }
 
     pub async fn run(&self) -> AppResult<()> {
         let nclient = self.nclient.clone();
         let queue = self.queue.clone();
+        let store = self.store.clone();
+        let delivery = self.config.nostr.delivery.clone();
+        let relay = self.relay.clone();
 
         //let queue_topic = self.config.redis.topic.clone();
         let queue_topic = consts::NOSTR_TOPIC;
@@ -80,9 +100,9 @@ impl SharedState {
             loop {
                 match queue.consume(queue_topic).await {
                     Ok(msgs) => {
-                        for (_k, m) in msgs.iter().enumerate() {
+                        for m in msgs.iter() {
                             //Deserialize data
-                            let msg: nostr::LamportBinding = match serde_json::from_str(m.data.as_str()) {
+                            let msg: nostr::delivery::NostrJob = match serde_json::from_str(m.data.as_str()) {
                                     Ok(parsed) => parsed,
                                     Err(e) => {
                                         tracing::error!(
@@ -103,7 +123,10 @@ impl SharedState {
                                 };
 
                             tracing::info!("Received message: {:?}", msg);
-                            nclient.sign_and_send(&msg).await.unwrap();
+                            match nostr::delivery::process(&store, &nclient, &delivery, msg).await {
+                                Ok(event) => relay.notify(&event),
+                                Err(e) => tracing::error!("Failed to send nostr event: {}, error: {:?}", m.data, e),
+                            }
 
                             // ack
                             if let Err(e) = queue.acknowledge(queue_topic, &m.id).await {
@@ -130,7 +153,7 @@ impl SharedState {
             loop {
                 match timeline_queue.consume(timeline_topic).await {
                     Ok(msgs) => {
-                        for (_k, m) in msgs.iter().enumerate() {
+                        for m in msgs.iter() {
                             //Deserialize data
                             let  msg: Event = match serde_json::from_str(m.data.as_str()) {
                                 Ok(parsed) => parsed,
@@ -163,6 +186,110 @@ impl SharedState {
             }
         });
 
+        //tokens stored before encryption are sealed before anything reads them
+        match self.twitter.seal_plaintext_tokens(&self.store).await? {
+            0 => {}
+            n => tracing::info!("encrypted the stored tokens of {} twitter bindings", n),
+        }
+
+        //keep stored twitter tokens usable, refresh tokens rotate on every refresh
+        let twitter = self.twitter.clone();
+        let store = self.store.clone();
+        let refresh_interval = self.config.twitter.refresh_interval.max(1);
+        tokio::spawn(async move {
+            loop {
+                match twitter.refresh_expiring_bindings(&store).await {
+                    Ok(0) => {}
+                    Ok(n) => tracing::info!("refreshed {} twitter tokens", n),
+                    Err(e) => tracing::error!("Failed to refresh twitter tokens: {:?}", e),
+                }
+                tokio::time::sleep(tokio::time::Duration::from_secs(refresh_interval)).await;
+            }
+        });
+
+        let notification_queue = self.queue.clone();
+        let notification_topic = consts::NOTIFICATION_TOPIC;
+        let store = self.store.clone();
+        let mailer = self.mailer.clone();
+        tokio::spawn(async move {
+            loop {
+                match notification_queue.consume(notification_topic).await {
+                    Ok(msgs) => {
+                        for m in msgs.iter() {
+                            match serde_json::from_str::<Notification>(m.data.as_str()) {
+                                Ok(n) => match notification_service::deliver(&store, mailer.as_ref(), &n).await {
+                                    Ok(sent) => tracing::info!("notification {:?} mailed to {} users", n, sent),
+                                    Err(e) => tracing::error!("Failed to deliver notification: {:?}, error: {:?}", n, e),
+                                },
+                                Err(e) => tracing::error!("Failed to parse message: {}, error: {:?}", m.data, e),
+                            }
+
+                            // ack
+                            if let Err(e) = notification_queue.acknowledge(notification_topic, &m.id).await {
+                                tracing::error!("Failed to acknowledge message: {}, error: {:?}", m.id, e);
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        tracing::error!("Failed to consume messages from queue: {:?}", e);
+                        tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+                    }
+                }
+            }
+        });
+
+        //closing soon notices are found by scanning, nothing happens when a proposal nears its end
+        let store = self.store.clone();
+        let redis = RedisClient::from(self.redis.clone());
+        let queue = self.queue.clone();
+        let closing_window = self.config.notification.closing_window_hours;
+        let scan_interval = self.config.notification.scan_interval.max(1);
+        tokio::spawn(async move {
+            loop {
+                match notification_service::queue_closing_proposals(&store, &redis, &queue, closing_window).await {
+                    Ok(0) => {}
+                    Ok(n) => tracing::info!("queued {} closing proposal notices", n),
+                    Err(e) => tracing::error!("Failed to scan closing proposals: {:?}", e),
+                }
+                tokio::time::sleep(tokio::time::Duration::from_secs(scan_interval)).await;
+            }
+        });
+
+        //events some write relay did not take are sent again with backoff
+        let nclient = self.nclient.clone();
+        let store = self.store.clone();
+        let delivery = self.config.nostr.delivery.clone();
+        tokio::spawn(async move {
+            loop {
+                match nostr::delivery::retry_failed(&store, &nclient, &delivery, consts::NOSTR_RETRY_BATCH).await {
+                    Ok(0) => {}
+                    Ok(n) => tracing::info!("redelivered {} nostr events", n),
+                    Err(e) => tracing::error!("Failed to retry nostr deliveries: {:?}", e),
+                }
+                tokio::time::sleep(tokio::time::Duration::from_secs(delivery.retry_interval.max(1))).await;
+            }
+        });
+
+        //lamport events published by others, such as votes signed by users' own nostr keys
+        let ingest = &self.config.nostr.ingest;
+        if ingest.enabled {
+            let ingestor = nostr::ingest::Ingestor::new(self.nclient.get_pub_key(), &ingest.trusted_servers)?;
+            let relays = if ingest.relays.is_empty() {
+                self.config.nostr.read_relays()
+            } else {
+                ingest.relays.clone()
+            };
+            let store = self.store.clone();
+            let scoring = self.config.scoring.clone();
+            tokio::spawn(async move {
+                loop {
+                    if let Err(e) = ingestor.run(&relays, &store, &scoring).await {
+                        tracing::error!("Nostr ingest stopped: {:?}", e);
+                    }
+                    tokio::time::sleep(tokio::time::Duration::from_secs(consts::NOSTR_INGEST_RETRY)).await;
+                }
+            });
+        }
 
         http_server_start(self.clone()).await?;
 
@@ -170,6 +297,13 @@ impl SharedState {
     }
 }
 
+impl AppState {
+    //signs a lamport event linked to object (type, id) and queues it for the relays, returns the event id
+    pub async fn publish_nostr(&self, msg: nostr::LamportBinding, object: Option<(&str, &str)>) -> AppResult<String> {
+        nostr::delivery::sign_and_queue(&self.store, &self.nclient, &self.queue, msg, object).await
+    }
+}
+
 impl RedisStreamPool {
     #[allow(dead_code)]
     pub async fn add_queue_req(&self, topic: &str, id: String, p: serde_json::Value) -> AppResult<()> {
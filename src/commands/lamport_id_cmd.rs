@@ -0,0 +1,112 @@
+use crate::{common::config::Config, database::Storage};
+use async_trait::async_trait;
+use clap::{Arg, ArgMatches, Command};
+use cli::CommandHandler;
+
+const CLI_OPERATOR: &str = "cli";
+
+pub struct LamportIdCommand;
+
+#[async_trait]
+impl CommandHandler for LamportIdCommand {
+    fn name(&self) -> String {
+        "lamport_id".to_string()
+    }
+
+    fn define(&self) -> Command {
+        Command::new("lamport_id")
+            .about("reserve, assign and release lamport ids")
+            .arg(
+                Arg::new("config")
+                    .short('c')
+                    .long("config")
+                    .required(true)
+                    .value_parser(clap::value_parser!(String))
+                    .help("config file path"),
+            )
+            .subcommand_required(true)
+            .subcommand(
+                Command::new("reserve")
+                    .about("reserve a range of lamport ids")
+                    .arg(Arg::new("start").long("start").required(true).value_parser(clap::value_parser!(i64)))
+                    .arg(Arg::new("end").long("end").required(true).value_parser(clap::value_parser!(i64)))
+                    .arg(Arg::new("reason").long("reason").default_value("").value_parser(clap::value_parser!(String))),
+            )
+            .subcommand(
+                Command::new("assign")
+                    .about("assign a lamport id to an address")
+                    .arg(Arg::new("id").long("id").required(true).value_parser(clap::value_parser!(i64)))
+                    .arg(Arg::new("address").long("address").required(true).value_parser(clap::value_parser!(String)))
+                    .arg(Arg::new("reason").long("reason").default_value("").value_parser(clap::value_parser!(String))),
+            )
+            .subcommand(
+                Command::new("release")
+                    .about("release a reserved range or an unclaimed assignment")
+                    .arg(
+                        Arg::new("reservation")
+                            .long("reservation")
+                            .value_parser(clap::value_parser!(i32))
+                            .conflicts_with("id")
+                            .help("reservation id"),
+                    )
+                    .arg(
+                        Arg::new("id")
+                            .long("id")
+                            .value_parser(clap::value_parser!(i64))
+                            .help("assigned lamport id"),
+                    ),
+            )
+            .subcommand(Command::new("list").about("list reservations and assignments"))
+    }
+
+    async fn run(&self, matches: &ArgMatches) {
+        let config_file = matches.get_one::<String>("config").unwrap();
+        let config = Config::load_config(config_file.into()).unwrap();
+        let store = Storage::new(config.database).await;
+
+        match matches.subcommand() {
+            Some(("reserve", sub)) => {
+                let start = *sub.get_one::<i64>("start").unwrap();
+                let end = *sub.get_one::<i64>("end").unwrap();
+                let reason = sub.get_one::<String>("reason").unwrap();
+                match store.reserve_lamport_id_range(start, end, reason, CLI_OPERATOR).await {
+                    Ok(r) => println!("reserved {}-{} (reservation {})", r.start_value, r.end_value, r.id),
+                    Err(e) => eprintln!("reserve failed: {}", e),
+                }
+            }
+            Some(("assign", sub)) => {
+                let id = *sub.get_one::<i64>("id").unwrap();
+                let address = sub.get_one::<String>("address").unwrap();
+                let reason = sub.get_one::<String>("reason").unwrap();
+                match store.assign_lamport_id(id, address, reason, CLI_OPERATOR).await {
+                    Ok(a) => println!("assigned {} to {}", a.lamport_value, a.address),
+                    Err(e) => eprintln!("assign failed: {}", e),
+                }
+            }
+            Some(("release", sub)) => {
+                if let Some(reservation) = sub.get_one::<i32>("reservation") {
+                    match store.release_lamport_id_range(*reservation, CLI_OPERATOR).await {
+                        Ok(r) => println!("released {}-{}", r.start_value, r.end_value),
+                        Err(e) => eprintln!("release failed: {}", e),
+                    }
+                } else if let Some(id) = sub.get_one::<i64>("id") {
+                    match store.release_lamport_id_assignment(*id, CLI_OPERATOR).await {
+                        Ok(a) => println!("released {} from {}", a.lamport_value, a.address),
+                        Err(e) => eprintln!("release failed: {}", e),
+                    }
+                } else {
+                    eprintln!("either --reservation or --id is required");
+                }
+            }
+            Some(("list", _)) => {
+                for r in store.get_lamport_id_reservations().await.unwrap() {
+                    println!("reservation {}: {}-{} {}", r.id, r.start_value, r.end_value, r.reason);
+                }
+                for a in store.get_lamport_id_assignments().await.unwrap() {
+                    println!("assignment {}: {} claimed={} {}", a.lamport_value, a.address, a.claimed, a.reason);
+                }
+            }
+            _ => eprintln!("unknown lamport_id subcommand"),
+        }
+    }
+}
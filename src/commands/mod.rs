@@ -1,3 +1,4 @@
+pub mod lamport_id_cmd;
 pub mod migrate_cmd;
 pub mod run_cmd;
 
@@ -8,6 +9,7 @@ pub async fn run_command() {
         "server",
         "v1.0",
         migrate_cmd::MigrateCommand,
+        lamport_id_cmd::LamportIdCommand,
         run_cmd::RunCommand
     );
     cli.run().await;
@@ -23,7 +23,13 @@ impl CommandHandler for RunCommand {
 
     async fn run(&self, matches: &ArgMatches) {
         let config_file = matches.get_one::<String>("config").unwrap();
-        let share_state = app::SharedState::new(config_file.clone().into()).await;
+        let share_state = match app::SharedState::new(config_file.clone().into()).await {
+            Ok(state) => state,
+            Err(e) => {
+                tracing::error!("failed to start: {:?}", e);
+                std::process::exit(1);
+            }
+        };
 
         share_state.run().await.unwrap();
     }
@@ -1,3 +1,4 @@
+use crate::common::consts;
 use config_macros::LoadConfig;
 use serde::Deserialize;
 
@@ -5,6 +6,9 @@ use serde::Deserialize;
 pub struct ServerConfig {
     pub host: String,
     pub port: String,
+    //proxies whose X-Forwarded-For is believed, the socket peer is the client address otherwise
+    #[serde(default)]
+    pub trusted_proxies: Vec<std::net::IpAddr>,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -28,10 +32,433 @@ pub struct AuthConfig {
     pub redirect_url: String,
 }
 
+//endpoints of the X oauth2 flow, point them at a mock server in tests
+#[derive(Clone, Debug, Deserialize)]
+pub struct TwitterConfig {
+    pub authorize_url: String,
+    pub token_url: String,
+    pub userinfo_url: String,
+    pub revoke_url: String,
+    //space separated scopes asked for on login and binding
+    pub scope: String,
+    //stored access and refresh tokens are encrypted with a key derived from it, the app refuses to start without one
+    #[serde(default)]
+    pub token_secret: String,
+    //seconds between checks for tokens that are about to expire
+    #[serde(default = "default_refresh_interval")]
+    pub refresh_interval: u64,
+    //seconds before expiry a token is refreshed
+    #[serde(default = "default_refresh_margin")]
+    pub refresh_margin: i64,
+}
+
+fn default_refresh_interval() -> u64 {
+    300
+}
+
+fn default_refresh_margin() -> i64 {
+    900
+}
+
+impl Default for TwitterConfig {
+    fn default() -> Self {
+        Self {
+            authorize_url: consts::TWITTER_AUTHORIZE_URL.to_string(),
+            token_url: consts::TWITTER_TOKEN_URL.to_string(),
+            userinfo_url: consts::TWITTER_USERINFO_URL.to_string(),
+            revoke_url: consts::TWITTER_REVOKE_URL.to_string(),
+            scope: consts::TWITTER_SCOPE.to_string(),
+            token_secret: String::new(),
+            refresh_interval: default_refresh_interval(),
+            refresh_margin: default_refresh_margin(),
+        }
+    }
+}
+
+//an oauth2 provider accounts can be bound with
+#[derive(Clone, Debug, Deserialize)]
+pub struct OAuthProviderConfig {
+    pub client_id: String,
+    pub client_secret: String,
+    pub redirect_url: String,
+    pub authorize_url: String,
+    pub token_url: String,
+    pub userinfo_url: String,
+    //space separated
+    pub scope: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct EmailBindingConfig {
+    //page the magic link opens, it gets the token as ?token= and posts it back
+    pub link_url: String,
+    //seconds a link stays valid
+    pub expiration: u64,
+}
+
+//providers without a section are disabled, twitter is always available
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct SocialConfig {
+    pub github: Option<OAuthProviderConfig>,
+    pub discord: Option<OAuthProviderConfig>,
+    pub email: Option<EmailBindingConfig>,
+}
+
+//what happens to the binding points of a lamport id an account leaves
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RewardPolicy {
+    Keep,
+    #[default]
+    Clawback,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct BindingConfig {
+    //hours after an unbind or rebind before the account, or the lamport id it left, binds that provider again
+    pub cooldown_hours: i64,
+    pub reward_policy: RewardPolicy,
+}
+
+impl Default for BindingConfig {
+    fn default() -> Self {
+        Self {
+            cooldown_hours: 72,
+            reward_policy: RewardPolicy::Clawback,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ProfileConfig {
+    //hours between two username changes of a lamport id
+    pub username_cooldown_hours: i64,
+    pub name_max_len: usize,
+    pub bio_max_len: usize,
+}
+
+impl Default for ProfileConfig {
+    fn default() -> Self {
+        Self {
+            username_cooldown_hours: 720,
+            name_max_len: 50,
+            bio_max_len: 280,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MailTransport {
+    #[default]
+    Log,
+    File,
+    Smtp,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    pub password: String,
+    //upgrade a plain connection, otherwise connect with implicit tls
+    pub starttls: bool,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct MailConfig {
+    pub transport: MailTransport,
+    pub from: String,
+    //where the file transport writes mail
+    pub dir: String,
+    pub smtp: Option<SmtpConfig>,
+    //page an email verification link opens, it gets the token as ?token= and posts it back
+    pub verify_link_url: String,
+    //seconds a verification link stays valid
+    pub verify_expiration: u64,
+}
+
+impl Default for MailConfig {
+    fn default() -> Self {
+        Self {
+            transport: MailTransport::Log,
+            from: "Lamport ID <no-reply@localhost>".to_string(),
+            dir: "mail".to_string(),
+            smtp: None,
+            verify_link_url: "http://127.0.0.1:8080/verify-email".to_string(),
+            verify_expiration: 86400,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct NotificationConfig {
+    //proposals ending within this many hours get a closing soon notice, once
+    pub closing_window_hours: i64,
+    //seconds between scans for closing proposals
+    pub scan_interval: u64,
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        Self {
+            closing_window_hours: 24,
+            scan_interval: 300,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize)]
 pub struct NostrConfig {
     pub priv_key: String,
+    //the only relay, for reads and writes, when relays is empty
     pub ws_url: String,
+    #[serde(default)]
+    pub relays: Vec<RelayConfig>,
+    #[serde(default)]
+    pub ingest: NostrIngestConfig,
+    #[serde(default)]
+    pub delivery: NostrDeliveryConfig,
+    #[serde(default)]
+    pub relay: NostrRelayConfig,
+}
+
+impl NostrConfig {
+    pub fn relays(&self) -> Vec<RelayConfig> {
+        if self.relays.is_empty() {
+            vec![RelayConfig {
+                url: self.ws_url.clone(),
+                role: RelayRole::ReadWrite,
+            }]
+        } else {
+            self.relays.clone()
+        }
+    }
+
+    pub fn read_relays(&self) -> Vec<String> {
+        self.relays().into_iter().filter(|r| r.role.reads()).map(|r| r.url).collect()
+    }
+
+    pub fn write_relays(&self) -> Vec<String> {
+        self.relays().into_iter().filter(|r| r.role.writes()).map(|r| r.url).collect()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RelayRole {
+    Read,
+    Write,
+    #[default]
+    ReadWrite,
+}
+
+impl RelayRole {
+    pub fn reads(&self) -> bool {
+        matches!(self, RelayRole::Read | RelayRole::ReadWrite)
+    }
+
+    pub fn writes(&self) -> bool {
+        matches!(self, RelayRole::Write | RelayRole::ReadWrite)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct RelayConfig {
+    pub url: String,
+    #[serde(default)]
+    pub role: RelayRole,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct NostrDeliveryConfig {
+    //seconds between scans for failed deliveries
+    pub retry_interval: u64,
+    //seconds before the first retry, doubled after every failure
+    pub backoff_base: u64,
+    pub backoff_max: u64,
+    //a relay is given up on after this many failed attempts
+    pub max_attempts: i32,
+}
+
+impl Default for NostrDeliveryConfig {
+    fn default() -> Self {
+        Self {
+            retry_interval: 60,
+            backoff_base: 30,
+            backoff_max: 3600,
+            max_attempts: 10,
+        }
+    }
+}
+
+//relay served by this server at /relay
+#[derive(Clone, Debug, Deserialize)]
+pub struct NostrRelayConfig {
+    pub enabled: bool,
+    //open subscriptions per connection
+    pub max_subscriptions: usize,
+    //stored events sent per filter, also the default when a filter has no limit
+    pub max_limit: usize,
+    //bytes, longer messages are refused
+    #[serde(default = "default_relay_max_message_size")]
+    pub max_message_size: usize,
+    //per connection, further messages of that type are refused until the minute is over
+    #[serde(default = "default_relay_max_events_per_minute")]
+    pub max_events_per_minute: u32,
+    #[serde(default = "default_relay_max_reqs_per_minute")]
+    pub max_reqs_per_minute: u32,
+}
+
+fn default_relay_max_message_size() -> usize {
+    65536
+}
+
+fn default_relay_max_events_per_minute() -> u32 {
+    30
+}
+
+fn default_relay_max_reqs_per_minute() -> u32 {
+    60
+}
+
+impl Default for NostrRelayConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_subscriptions: 20,
+            max_limit: 500,
+            max_message_size: default_relay_max_message_size(),
+            max_events_per_minute: default_relay_max_events_per_minute(),
+            max_reqs_per_minute: default_relay_max_reqs_per_minute(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct NostrIngestConfig {
+    pub enabled: bool,
+    //relays to read lamport events from, the read relays when empty
+    pub relays: Vec<String>,
+    //hex or npub keys of other lamport id servers whose bindings are imported
+    pub trusted_servers: Vec<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Eip712Config {
+    pub name: String,
+    pub version: String,
+    pub chain_id: u64,
+    pub verifying_contract: Option<String>,
+}
+
+impl Default for Eip712Config {
+    fn default() -> Self {
+        Self {
+            name: "Lamport ID".to_string(),
+            version: "1".to_string(),
+            chain_id: 1,
+            verifying_contract: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct JwtKeyConfig {
+    pub kid: String,
+    //HS256, ES256 or EdDSA
+    pub algorithm: String,
+    //HS* keys
+    pub secret: Option<String>,
+    pub secret_file: Option<String>,
+    //ES256 / EdDSA keys in PEM, a key without private_key_file only validates tokens
+    pub private_key_file: Option<String>,
+    pub public_key_file: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct JwtConfig {
+    //kid used to sign new tokens, the other keys keep validating until removed
+    pub active_kid: String,
+    //minutes an access token is valid
+    pub expiration: i64,
+    //minutes an unused session and its refresh token are kept
+    #[serde(default = "default_refresh_expiration")]
+    pub refresh_expiration: i64,
+    pub keys: Vec<JwtKeyConfig>,
+}
+
+fn default_refresh_expiration() -> i64 {
+    consts::REFRESH_TOKEN_EXPIRATION
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Eip1271Config {
+    //node used to call isValidSignature on contract wallets, disabled when empty
+    pub rpc_url: Option<String>,
+    pub timeout: u64,
+}
+
+impl Default for Eip1271Config {
+    fn default() -> Self {
+        Self {
+            rpc_url: None,
+            timeout: 10,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct SiweConfig {
+    pub domain: String,
+    pub uri: String,
+    pub chain_ids: Vec<u64>,
+    //seconds a message stays acceptable after its issued-at
+    pub max_age: i64,
+}
+
+impl Default for SiweConfig {
+    fn default() -> Self {
+        Self {
+            domain: "127.0.0.1:8080".to_string(),
+            uri: "http://127.0.0.1:8080".to_string(),
+            chain_ids: vec![1],
+            max_age: 600,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct VerificationConfig {
+    //active attestations from verified users that verify an account
+    pub required_attestations: usize,
+    //lamport ids whose single attestation verifies, holders of the verifier role count as well
+    pub trusted_roots: Vec<String>,
+}
+
+impl Default for VerificationConfig {
+    fn default() -> Self {
+        Self {
+            required_attestations: 3,
+            trusted_roots: vec![],
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct ScoringConfig {
+    //humanity score needed to create a proposal, 0 disables the check
+    pub min_proposal_score: u32,
+    //humanity score needed to vote, 0 disables the check
+    pub min_vote_score: u32,
+    //weigh each vote by the voter's score instead of counting it as 100
+    pub weighted_votes: bool,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct AdminConfig {
+    pub lamport_ids: Vec<String>,
 }
 
 #[derive(Clone, Debug, Deserialize, LoadConfig)]
@@ -39,6 +466,32 @@ pub struct Config {
     pub server: ServerConfig,
     pub database: DatabaseConfig,
     pub auth: AuthConfig,
+    #[serde(default)]
+    pub twitter: TwitterConfig,
     pub redis: RedisConfig,
     pub nostr: NostrConfig,
+    //required, there is no built-in signing key to fall back to
+    pub jwt: JwtConfig,
+    #[serde(default)]
+    pub admin: AdminConfig,
+    #[serde(default)]
+    pub eip712: Eip712Config,
+    #[serde(default)]
+    pub eip1271: Eip1271Config,
+    #[serde(default)]
+    pub siwe: SiweConfig,
+    #[serde(default)]
+    pub verification: VerificationConfig,
+    #[serde(default)]
+    pub scoring: ScoringConfig,
+    #[serde(default)]
+    pub social: SocialConfig,
+    #[serde(default)]
+    pub binding: BindingConfig,
+    #[serde(default)]
+    pub profile: ProfileConfig,
+    #[serde(default)]
+    pub mail: MailConfig,
+    #[serde(default)]
+    pub notification: NotificationConfig,
 }
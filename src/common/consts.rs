@@ -1,14 +1,31 @@
-pub const TOKEN_ENDPOINT: &str = "https://api.twitter.com/oauth2/token";
-pub const USERINFO_ENDPOINT: &str = "https://api.twitter.com/2/users/me";
-pub const AUTH_ENDPOINT: &str = "https://api.x.com/2/users/me";
-pub const JWT_SECRET_KEY: &str = "my_secret_key";
-pub const JWT_EXPIRATION: i64 = 2880;
+//only used when config.yaml has no twitter section
+pub const TWITTER_AUTHORIZE_URL: &str = "https://x.com/i/oauth2/authorize";
+pub const TWITTER_TOKEN_URL: &str = "https://api.x.com/2/oauth2/token";
+pub const TWITTER_USERINFO_URL: &str = "https://api.x.com/2/users/me";
+pub const TWITTER_REVOKE_URL: &str = "https://api.x.com/2/oauth2/revoke";
+pub const TWITTER_SCOPE: &str = "tweet.read users.read offline.access";
+pub const PROVIDER_TWITTER: &str = "twitter";
+pub const PROVIDER_GITHUB: &str = "github";
+pub const PROVIDER_DISCORD: &str = "discord";
+pub const PROVIDER_EMAIL: &str = "email";
+//a linked nostr key, published like a binding but kept on the users row
+pub const PROVIDER_NOSTR: &str = "nostr";
+pub const BINDING_RELEASE_UNBIND: &str = "unbind";
+pub const BINDING_RELEASE_REBIND: &str = "rebind";
+//seconds a login may take between issuing the state and the callback
+pub const OAUTH_STATE_EXPIRATION: u64 = 600;
+//seconds before a lamport id can be mailed another link, and before an address can receive one
+pub const EMAIL_USER_COOLDOWN: u64 = 60;
+pub const EMAIL_ADDRESS_COOLDOWN: u64 = 300;
+//only used when the jwt section of config.yaml has no refresh_expiration
+pub const REFRESH_TOKEN_EXPIRATION: i64 = 43200;
 pub const REDIS_KEY: &str = "lamport_id";
 
 pub const POINTS_PROPOSAL: &str = "proposal";
 pub const POINTS_INVITE: &str = "invite";
 pub const POINTS_VOTE: &str = "vote";
 pub const POINTS_BINDING: &str = "binding";
+pub const POINTS_BINDING_CLAWBACK: &str = "binding_clawback";
 
 pub const POINTS_PROPOSAL_VALUE: i32 = 100;
 pub const POINTS_INVITE_VALUE: i32 = 100;
@@ -35,6 +52,40 @@ pub const PROPOSAL_DESCRIPTION_MAX_LENGTH: i32 = 4000;
 
 pub const EVENT_TOPIC: &str = "events";
 pub const NOSTR_TOPIC: &str = "nostr";
+pub const NOTIFICATION_TOPIC: &str = "notifications";
+
+//kinds we publish that carry a LamportID tag, 1 is for votes published before kind 2328
+pub const NOSTR_LAMPORT_KINDS: [u16; 9] = [1, 2321, 2322, 2323, 2324, 2325, 2326, 2327, 2328];
+//seconds to wait for relays when reading back our events
+pub const NOSTR_FETCH_TIMEOUT: u64 = 10;
+//seconds before the relay subscriber reconnects
+pub const NOSTR_INGEST_RETRY: u64 = 10;
+//seconds subtracted from the newest ingested event when resubscribing, relays deliver out of order
+pub const NOSTR_INGEST_SINCE_MARGIN: u64 = 600;
+pub const NOSTR_DELIVERY_OK: &str = "ok";
+pub const NOSTR_DELIVERY_ERROR: &str = "error";
+//failed deliveries retried per scan
+pub const NOSTR_RETRY_BATCH: u64 = 100;
+//seconds a stored event may wait in the queue before the retry loop sends it itself
+pub const NOSTR_UNQUEUED_GRACE: i64 = 300;
+//stored events read per query page by the embedded relay
+pub const NOSTR_RELAY_BATCH: u64 = 200;
+//stored rows a filter may read from each table before the embedded relay stops looking
+pub const NOSTR_RELAY_MAX_SCAN: u64 = 5000;
+//new events buffered for live relay subscriptions
+pub const NOSTR_RELAY_CHANNEL: usize = 1024;
+//domain objects signed nostr events are linked to
+pub const NOSTR_OBJECT_USER: &str = "user";
+pub const NOSTR_OBJECT_VOTE: &str = "vote";
+pub const NOSTR_OBJECT_PROPOSAL: &str = "proposal";
+pub const NOSTR_OBJECT_BINDING: &str = "binding";
+pub const NOSTR_OBJECT_ATTESTATION: &str = "attestation";
+pub const TOMBSTONE_PREFIX: &str = "deleted:";
+
+//NIP-42 client authentication kind, used to prove a pubkey belongs to a lamport id
+pub const NOSTR_CHALLENGE_KIND: u16 = 22242;
+//seconds a challenge, and the event answering it, stay valid
+pub const NOSTR_CHALLENGE_EXPIRATION: u64 = 600;
 
 pub const EVENT_TYPE_VOTE: &str = "vote";
 pub const EVENT_TYPE_PROPOSAL: &str = "proposal";
@@ -42,3 +93,28 @@ pub const EVENT_TYPE_INVITE: &str = "invite";
 pub const EVENT_TYPE_BINDING: &str = "binding";
 pub const EVENT_TYPE_REGISTER: &str = "register";
 pub const EVENT_TYPE_JOIN: &str = "join";
+pub const EVENT_TYPE_VERIFICATION: &str = "verification";
+pub const EVENT_TYPE_PROFILE: &str = "profile";
+
+pub const LAMPORT_ID_ACTION_RESERVE: &str = "reserve";
+pub const LAMPORT_ID_ACTION_ASSIGN: &str = "assign";
+pub const LAMPORT_ID_ACTION_RELEASE: &str = "release";
+
+pub const ADMIN_ACTION_GRANT_ROLE: &str = "grant_role";
+pub const ADMIN_ACTION_REVOKE_ROLE: &str = "revoke_role";
+pub const ADMIN_ACTION_BAN: &str = "ban";
+pub const ADMIN_ACTION_SUSPEND: &str = "suspend";
+pub const ADMIN_ACTION_LIFT_SANCTION: &str = "lift_sanction";
+pub const ADMIN_ACTION_HIDE_PROPOSAL: &str = "hide_proposal";
+pub const ADMIN_ACTION_UNHIDE_PROPOSAL: &str = "unhide_proposal";
+
+pub const LINKED_ADDRESS_MAX: u64 = 10;
+
+//recent timeline events shown on a public profile
+pub const PROFILE_EVENTS_LIMIT: u64 = 20;
+//signed events per page of a lamport id's nostr history
+pub const PROFILE_NOSTR_EVENTS_LIMIT: i64 = 100;
+
+pub const VOTE_FULL_WEIGHT: i32 = 100;
+//channel of votes imported from nostr relays
+pub const VOTE_CHANNEL_NOSTR: &str = "nostr";
@@ -76,6 +76,33 @@ pub enum AppError {
 
     #[error("invalid lamport type")]
     InvalidLamportType,
+
+    #[error(transparent)]
+    NostrTagError(#[from] nostr_sdk::event::tag::Error),
+
+    #[error(transparent)]
+    NostrEventError(#[from] nostr_sdk::event::Error),
+
+    #[error("invalid nostr event: {0}")]
+    InvalidNostrEvent(String),
+
+    #[error("siwe error: {0}")]
+    SiweError(String),
+
+    #[error(transparent)]
+    RedisError(#[from] redis::RedisError),
+
+    #[error("unauthorized: {0}")]
+    Unauthorized(String),
+
+    #[error("token expired")]
+    TokenExpired,
+
+    #[error("forbidden: {0}")]
+    Forbidden(String),
+
+    #[error("too many requests: {0}")]
+    TooManyRequests(String),
 }
 
 impl IntoResponse for AppError {
@@ -101,6 +128,15 @@ impl IntoResponse for AppError {
             Self::ConflictError(_) => StatusCode::CONFLICT,
             Self::InvalidSignature => StatusCode::UNPROCESSABLE_ENTITY,
             Self::InvalidLamportType => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::NostrTagError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::NostrEventError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::InvalidNostrEvent(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::SiweError(_) => StatusCode::UNAUTHORIZED,
+            Self::RedisError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            Self::TokenExpired => StatusCode::UNAUTHORIZED,
+            Self::Forbidden(_) => StatusCode::FORBIDDEN,
+            Self::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
         };
 
         (status, Json(serde_json::json!({"error":self.to_string()}))).into_response()
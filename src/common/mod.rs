@@ -1,3 +1,4 @@
 pub mod config;
 pub mod consts;
 pub mod error;
+pub mod rbac;
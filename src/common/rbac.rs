@@ -0,0 +1,98 @@
+use crate::common::error::AppError;
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+/// Role granted to a Lamport ID, stored by name in `user_roles`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Admin,
+    Moderator,
+    Verifier,
+    GroupCreator,
+}
+
+/// Action a route can be gated on, see `middlewares::PermissionGuard`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    ManageRoles,
+    ManageUsers,
+    ModerateProposals,
+    ViewAudit,
+    //a single attestation from the holder verifies its subject
+    VerifyUsers,
+    CreateGroup,
+    ManageLamportIds,
+}
+
+impl Role {
+    pub const ALL: [Role; 4] = [Role::Admin, Role::Moderator, Role::Verifier, Role::GroupCreator];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Admin => "admin",
+            Role::Moderator => "moderator",
+            Role::Verifier => "verifier",
+            Role::GroupCreator => "group_creator",
+        }
+    }
+
+    pub fn permissions(&self) -> &'static [Permission] {
+        match self {
+            Role::Admin => &[
+                Permission::ManageRoles,
+                Permission::ManageUsers,
+                Permission::ModerateProposals,
+                Permission::ViewAudit,
+                Permission::VerifyUsers,
+                Permission::CreateGroup,
+                Permission::ManageLamportIds,
+            ],
+            Role::Moderator => &[
+                Permission::ManageUsers,
+                Permission::ModerateProposals,
+                Permission::ViewAudit,
+            ],
+            Role::Verifier => &[Permission::VerifyUsers],
+            Role::GroupCreator => &[Permission::CreateGroup],
+        }
+    }
+
+    pub fn grants(&self, permission: Permission) -> bool {
+        self.permissions().contains(&permission)
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Role {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Role::ALL
+            .into_iter()
+            .find(|r| r.as_str() == s)
+            .ok_or_else(|| AppError::InputValidateError(format!("unknown role {}", s)))
+    }
+}
+
+/// Restriction placed on a Lamport ID, a suspension ends at its `expires_at`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SanctionKind {
+    Ban,
+    Suspend,
+}
+
+impl SanctionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SanctionKind::Ban => "ban",
+            SanctionKind::Suspend => "suspend",
+        }
+    }
+}
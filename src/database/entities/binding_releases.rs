@@ -3,21 +3,17 @@
 use sea_orm::entity::prelude::*;
 
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
-#[sea_orm(table_name = "twitter_binding")]
+#[sea_orm(table_name = "binding_releases")]
 pub struct Model {
     #[sea_orm(primary_key)]
     pub id: i32,
     pub user_id: String,
-    #[sea_orm(unique)]
-    pub x_id: String,
-    pub name: String,
-    #[sea_orm(unique)]
+    pub provider: String,
+    pub external_id: String,
     pub user_name: String,
-    pub image_url: String,
-    pub access_token: String,
-    pub refresh_token: String,
-    pub token_type: String,
-    pub scope: String,
+    pub reason: String,
+    pub bound_at: DateTimeWithTimeZone,
+    pub released_at: DateTimeWithTimeZone,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -2,12 +2,29 @@
 
 pub mod prelude;
 
+pub mod admin_audit;
+pub mod attestations;
+pub mod binding_releases;
 pub mod events;
 pub mod groups;
 pub mod lamport_id;
+pub mod lamport_id_assignments;
+pub mod lamport_id_audit;
+pub mod lamport_id_reservations;
+pub mod linked_addresses;
+pub mod nostr_deliveries;
+pub mod nostr_events;
+pub mod nostr_ingested_events;
+pub mod nostr_relay_events;
+pub mod notification_preferences;
 pub mod points;
 pub mod power;
+pub mod privacy_settings;
 pub mod proposals;
-pub mod twitter_binding;
+pub mod remote_bindings;
+pub mod social_bindings;
+pub mod user_roles;
+pub mod user_sanctions;
+pub mod username_history;
 pub mod users;
 pub mod vote;
@@ -1,11 +1,28 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.4
 
+pub use super::admin_audit::Entity as AdminAudit;
+pub use super::attestations::Entity as Attestations;
+pub use super::binding_releases::Entity as BindingReleases;
 pub use super::events::Entity as Events;
 pub use super::groups::Entity as Groups;
 pub use super::lamport_id::Entity as LamportId;
+pub use super::lamport_id_assignments::Entity as LamportIdAssignments;
+pub use super::lamport_id_audit::Entity as LamportIdAudit;
+pub use super::lamport_id_reservations::Entity as LamportIdReservations;
+pub use super::linked_addresses::Entity as LinkedAddresses;
+pub use super::nostr_deliveries::Entity as NostrDeliveries;
+pub use super::nostr_events::Entity as NostrEvents;
+pub use super::nostr_ingested_events::Entity as NostrIngestedEvents;
+pub use super::nostr_relay_events::Entity as NostrRelayEvents;
+pub use super::notification_preferences::Entity as NotificationPreferences;
 pub use super::points::Entity as Points;
 pub use super::power::Entity as Power;
+pub use super::privacy_settings::Entity as PrivacySettings;
 pub use super::proposals::Entity as Proposals;
-pub use super::twitter_binding::Entity as TwitterBinding;
+pub use super::remote_bindings::Entity as RemoteBindings;
+pub use super::social_bindings::Entity as SocialBindings;
+pub use super::user_roles::Entity as UserRoles;
+pub use super::user_sanctions::Entity as UserSanctions;
+pub use super::username_history::Entity as UsernameHistory;
 pub use super::users::Entity as Users;
 pub use super::vote::Entity as Vote;
@@ -0,0 +1,51 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(LamportIdReservations::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(LamportIdReservations::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(LamportIdReservations::StartValue).big_integer().not_null())
+                    .col(ColumnDef::new(LamportIdReservations::EndValue).big_integer().not_null())
+                    .col(ColumnDef::new(LamportIdReservations::Reason).string().not_null())
+                    .col(ColumnDef::new(LamportIdReservations::CreatedBy).string().not_null())
+                    .col(
+                        ColumnDef::new(LamportIdReservations::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(LamportIdReservations::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum LamportIdReservations {
+    Table,
+    Id,
+    StartValue,
+    EndValue,
+    Reason,
+    CreatedBy,
+    CreatedAt,
+}
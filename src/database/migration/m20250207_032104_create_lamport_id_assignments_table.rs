@@ -0,0 +1,53 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(LamportIdAssignments::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(LamportIdAssignments::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(LamportIdAssignments::LamportValue).big_integer().not_null().unique_key())
+                    .col(ColumnDef::new(LamportIdAssignments::Address).string().not_null().unique_key())
+                    .col(ColumnDef::new(LamportIdAssignments::Reason).string().not_null())
+                    .col(ColumnDef::new(LamportIdAssignments::AssignedBy).string().not_null())
+                    .col(ColumnDef::new(LamportIdAssignments::Claimed).boolean().not_null())
+                    .col(
+                        ColumnDef::new(LamportIdAssignments::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(LamportIdAssignments::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum LamportIdAssignments {
+    Table,
+    Id,
+    LamportValue,
+    Address,
+    Reason,
+    AssignedBy,
+    Claimed,
+    CreatedAt,
+}
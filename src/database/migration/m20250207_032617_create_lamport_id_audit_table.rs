@@ -0,0 +1,55 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(LamportIdAudit::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(LamportIdAudit::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(LamportIdAudit::Action).string().not_null())
+                    .col(ColumnDef::new(LamportIdAudit::StartValue).big_integer().not_null())
+                    .col(ColumnDef::new(LamportIdAudit::EndValue).big_integer().not_null())
+                    .col(ColumnDef::new(LamportIdAudit::Address).string().null())
+                    .col(ColumnDef::new(LamportIdAudit::Reason).string().null())
+                    .col(ColumnDef::new(LamportIdAudit::Operator).string().not_null())
+                    .col(
+                        ColumnDef::new(LamportIdAudit::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(LamportIdAudit::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum LamportIdAudit {
+    Table,
+    Id,
+    Action,
+    StartValue,
+    EndValue,
+    Address,
+    Reason,
+    Operator,
+    CreatedAt,
+}
@@ -0,0 +1,59 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(LinkedAddresses::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(LinkedAddresses::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(LinkedAddresses::LamportId).string().not_null())
+                    .col(ColumnDef::new(LinkedAddresses::Address).string().not_null().unique_key())
+                    .col(ColumnDef::new(LinkedAddresses::Sig).string().not_null())
+                    .col(
+                        ColumnDef::new(LinkedAddresses::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_linked_addresses_lamport_id")
+                    .table(LinkedAddresses::Table)
+                    .col(LinkedAddresses::LamportId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(LinkedAddresses::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum LinkedAddresses {
+    Table,
+    Id,
+    LamportId,
+    Address,
+    Sig,
+    CreatedAt,
+}
@@ -0,0 +1,61 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(UserRoles::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(UserRoles::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(UserRoles::LamportId).string().not_null())
+                    .col(ColumnDef::new(UserRoles::Role).string().not_null())
+                    .col(ColumnDef::new(UserRoles::GrantedBy).string().not_null())
+                    .col(
+                        ColumnDef::new(UserRoles::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_user_roles_lamport_id_role")
+                    .table(UserRoles::Table)
+                    .col(UserRoles::LamportId)
+                    .col(UserRoles::Role)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(UserRoles::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum UserRoles {
+    Table,
+    Id,
+    LamportId,
+    Role,
+    GrantedBy,
+    CreatedAt,
+}
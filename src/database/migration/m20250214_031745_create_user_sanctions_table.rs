@@ -0,0 +1,53 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(UserSanctions::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(UserSanctions::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(UserSanctions::LamportId).string().not_null().unique_key())
+                    .col(ColumnDef::new(UserSanctions::Kind).string().not_null())
+                    .col(ColumnDef::new(UserSanctions::Reason).string().not_null())
+                    .col(ColumnDef::new(UserSanctions::ExpiresAt).timestamp_with_time_zone().null())
+                    .col(ColumnDef::new(UserSanctions::CreatedBy).string().not_null())
+                    .col(
+                        ColumnDef::new(UserSanctions::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(UserSanctions::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum UserSanctions {
+    Table,
+    Id,
+    LamportId,
+    Kind,
+    Reason,
+    ExpiresAt,
+    CreatedBy,
+    CreatedAt,
+}
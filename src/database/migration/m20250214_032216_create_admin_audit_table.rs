@@ -0,0 +1,51 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AdminAudit::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AdminAudit::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(AdminAudit::Operator).string().not_null())
+                    .col(ColumnDef::new(AdminAudit::Action).string().not_null())
+                    .col(ColumnDef::new(AdminAudit::Target).string().not_null())
+                    .col(ColumnDef::new(AdminAudit::Detail).text().null())
+                    .col(
+                        ColumnDef::new(AdminAudit::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AdminAudit::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum AdminAudit {
+    Table,
+    Id,
+    Operator,
+    Action,
+    Target,
+    Detail,
+    CreatedAt,
+}
@@ -0,0 +1,92 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Attestations::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Attestations::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Attestations::Attester).string().not_null())
+                    .col(ColumnDef::new(Attestations::Subject).string().not_null())
+                    .col(ColumnDef::new(Attestations::SubjectAddress).string().not_null())
+                    .col(ColumnDef::new(Attestations::Sig).string().not_null())
+                    .col(ColumnDef::new(Attestations::Revoked).boolean().not_null().default(false))
+                    .col(ColumnDef::new(Attestations::RevokedBy).string().null())
+                    .col(ColumnDef::new(Attestations::RevokedAt).timestamp_with_time_zone().null())
+                    .col(
+                        ColumnDef::new(Attestations::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_attestations_attester_subject")
+                    .table(Attestations::Table)
+                    .col(Attestations::Attester)
+                    .col(Attestations::Subject)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_attestations_subject")
+                    .table(Attestations::Table)
+                    .col(Attestations::Subject)
+                    .to_owned(),
+            )
+            .await?;
+
+        //users verified through the old verify endpoint keep a single attestation from their verifier
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"INSERT INTO attestations (attester, subject, subject_address, sig, revoked, created_at)
+                SELECT verified_by, lamport_id, address, '', false, COALESCE(created_at, now())
+                FROM users WHERE verified AND verified_by IS NOT NULL AND verified_by <> lamport_id
+                ON CONFLICT DO NOTHING"#,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Attestations::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Attestations {
+    Table,
+    Id,
+    Attester,
+    Subject,
+    SubjectAddress,
+    Sig,
+    Revoked,
+    RevokedBy,
+    RevokedAt,
+    CreatedAt,
+}
@@ -0,0 +1,89 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .add_column(ColumnDef::new(Users::RegisterIp).string().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_users_register_ip")
+                    .table(Users::Table)
+                    .col(Users::RegisterIp)
+                    .to_owned(),
+            )
+            .await?;
+
+        //existing bindings count from the time of the migration
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TwitterBinding::Table)
+                    .add_column(
+                        ColumnDef::new(TwitterBinding::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Vote::Table)
+                    .add_column(ColumnDef::new(Vote::Weight).integer().not_null().default(100))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(Table::alter().table(Vote::Table).drop_column(Vote::Weight).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TwitterBinding::Table)
+                    .drop_column(TwitterBinding::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(Table::alter().table(Users::Table).drop_column(Users::RegisterIp).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    RegisterIp,
+}
+
+#[derive(DeriveIden)]
+enum TwitterBinding {
+    Table,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Vote {
+    Table,
+    Weight,
+}
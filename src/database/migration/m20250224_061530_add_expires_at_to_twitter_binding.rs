@@ -0,0 +1,40 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        //existing bindings have no known expiry and are refreshed on first use
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TwitterBinding::Table)
+                    .add_column(
+                        ColumnDef::new(TwitterBinding::ExpiresAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(TwitterBinding::Table)
+                    .drop_column(TwitterBinding::ExpiresAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum TwitterBinding {
+    Table,
+    ExpiresAt,
+}
@@ -0,0 +1,168 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(SocialBindings::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(SocialBindings::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(SocialBindings::UserId).string().not_null())
+                    .col(ColumnDef::new(SocialBindings::Provider).string().not_null())
+                    .col(ColumnDef::new(SocialBindings::ExternalId).string().not_null())
+                    .col(ColumnDef::new(SocialBindings::Name).string().not_null())
+                    .col(ColumnDef::new(SocialBindings::UserName).string().not_null())
+                    .col(ColumnDef::new(SocialBindings::ImageUrl).string().null())
+                    //only kept for providers whose api is called after binding
+                    .col(ColumnDef::new(SocialBindings::AccessToken).string().null())
+                    .col(ColumnDef::new(SocialBindings::RefreshToken).string().null())
+                    .col(ColumnDef::new(SocialBindings::TokenType).string().null())
+                    .col(ColumnDef::new(SocialBindings::Scope).string().null())
+                    .col(ColumnDef::new(SocialBindings::ExpiresAt).timestamp_with_time_zone().null())
+                    .col(
+                        ColumnDef::new(SocialBindings::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        //an account is bound once, a lamport id binds one account per provider
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_social_bindings_provider_external_id")
+                    .table(SocialBindings::Table)
+                    .col(SocialBindings::Provider)
+                    .col(SocialBindings::ExternalId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_social_bindings_user_id_provider")
+                    .table(SocialBindings::Table)
+                    .col(SocialBindings::UserId)
+                    .col(SocialBindings::Provider)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"INSERT INTO social_bindings (user_id, provider, external_id, name, user_name, image_url,
+                access_token, refresh_token, token_type, scope, expires_at, created_at)
+                SELECT user_id, 'twitter', x_id, name, user_name, image_url,
+                access_token, refresh_token, token_type, scope, expires_at, created_at
+                FROM twitter_binding ON CONFLICT DO NOTHING"#,
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(TwitterBinding::Table).to_owned())
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(TwitterBinding::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TwitterBinding::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(TwitterBinding::UserId).string().not_null())
+                    .col(ColumnDef::new(TwitterBinding::XId).string().not_null().unique_key())
+                    .col(ColumnDef::new(TwitterBinding::Name).string().not_null())
+                    .col(ColumnDef::new(TwitterBinding::UserName).string().not_null().unique_key())
+                    .col(ColumnDef::new(TwitterBinding::ImageUrl).string().not_null())
+                    .col(ColumnDef::new(TwitterBinding::AccessToken).string().not_null())
+                    .col(ColumnDef::new(TwitterBinding::RefreshToken).string().not_null())
+                    .col(ColumnDef::new(TwitterBinding::TokenType).string().not_null())
+                    .col(ColumnDef::new(TwitterBinding::Scope).string().not_null())
+                    .col(
+                        ColumnDef::new(TwitterBinding::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(ColumnDef::new(TwitterBinding::ExpiresAt).timestamp_with_time_zone().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        //bindings of the other providers are lost
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"INSERT INTO twitter_binding (user_id, x_id, name, user_name, image_url,
+                access_token, refresh_token, token_type, scope, created_at, expires_at)
+                SELECT user_id, external_id, name, user_name, COALESCE(image_url, ''),
+                COALESCE(access_token, ''), COALESCE(refresh_token, ''), COALESCE(token_type, ''),
+                COALESCE(scope, ''), created_at, expires_at
+                FROM social_bindings WHERE provider = 'twitter'"#,
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(SocialBindings::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum SocialBindings {
+    Table,
+    Id,
+    UserId, //lamport_id
+    Provider,
+    ExternalId,
+    Name,
+    UserName,
+    ImageUrl,
+    AccessToken,
+    RefreshToken,
+    TokenType,
+    Scope,
+    ExpiresAt,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum TwitterBinding {
+    Table,
+    Id,
+    UserId,
+    XId,
+    UserName,
+    Name,
+    ImageUrl,
+    AccessToken,
+    RefreshToken,
+    TokenType,
+    Scope,
+    CreatedAt,
+    ExpiresAt,
+}
@@ -0,0 +1,82 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(BindingReleases::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(BindingReleases::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(BindingReleases::UserId).string().not_null())
+                    .col(ColumnDef::new(BindingReleases::Provider).string().not_null())
+                    .col(ColumnDef::new(BindingReleases::ExternalId).string().not_null())
+                    .col(ColumnDef::new(BindingReleases::UserName).string().not_null())
+                    //unbind or rebind
+                    .col(ColumnDef::new(BindingReleases::Reason).string().not_null())
+                    .col(
+                        ColumnDef::new(BindingReleases::BoundAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(BindingReleases::ReleasedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_binding_releases_provider_external_id")
+                    .table(BindingReleases::Table)
+                    .col(BindingReleases::Provider)
+                    .col(BindingReleases::ExternalId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_binding_releases_user_id_provider")
+                    .table(BindingReleases::Table)
+                    .col(BindingReleases::UserId)
+                    .col(BindingReleases::Provider)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(BindingReleases::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum BindingReleases {
+    Table,
+    Id,
+    UserId, //lamport_id
+    Provider,
+    ExternalId,
+    UserName,
+    Reason,
+    BoundAt,
+    ReleasedAt,
+}
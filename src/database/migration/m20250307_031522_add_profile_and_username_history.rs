@@ -0,0 +1,97 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .add_column(ColumnDef::new(Users::Bio).string().not_null().default(""))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(UsernameHistory::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(UsernameHistory::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(UsernameHistory::UserId).string().not_null())
+                    .col(ColumnDef::new(UsernameHistory::OldUserName).string().not_null())
+                    .col(ColumnDef::new(UsernameHistory::NewUserName).string().not_null())
+                    .col(ColumnDef::new(UsernameHistory::Sig).string().not_null())
+                    .col(
+                        ColumnDef::new(UsernameHistory::ChangedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        //old handles keep redirecting, so each one belongs to a single lamport id
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_username_history_old_user_name")
+                    .table(UsernameHistory::Table)
+                    .col(UsernameHistory::OldUserName)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_username_history_user_id")
+                    .table(UsernameHistory::Table)
+                    .col(UsernameHistory::UserId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(UsernameHistory::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Users::Table)
+                    .drop_column(Users::Bio)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    Bio,
+}
+
+#[derive(DeriveIden)]
+enum UsernameHistory {
+    Table,
+    Id,
+    UserId, //lamport_id
+    OldUserName,
+    NewUserName,
+    Sig,
+    ChangedAt,
+}
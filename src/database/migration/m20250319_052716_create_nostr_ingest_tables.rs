@@ -0,0 +1,119 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(NostrIngestedEvents::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(NostrIngestedEvents::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(NostrIngestedEvents::EventId)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(NostrIngestedEvents::Pubkey).string().not_null())
+                    .col(ColumnDef::new(NostrIngestedEvents::Kind).integer().not_null())
+                    .col(ColumnDef::new(NostrIngestedEvents::LamportId).string().null())
+                    .col(ColumnDef::new(NostrIngestedEvents::Outcome).string().not_null())
+                    .col(
+                        ColumnDef::new(NostrIngestedEvents::EventCreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(NostrIngestedEvents::IngestedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(RemoteBindings::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(RemoteBindings::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(RemoteBindings::ServerPubkey).string().not_null())
+                    .col(ColumnDef::new(RemoteBindings::EventId).string().not_null())
+                    .col(ColumnDef::new(RemoteBindings::LamportId).string().not_null())
+                    .col(ColumnDef::new(RemoteBindings::Provider).string().not_null())
+                    .col(ColumnDef::new(RemoteBindings::Account).string().not_null())
+                    .col(
+                        ColumnDef::new(RemoteBindings::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        //an account is bound to one lamport id per server
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_remote_bindings_server_account")
+                    .table(RemoteBindings::Table)
+                    .col(RemoteBindings::ServerPubkey)
+                    .col(RemoteBindings::Provider)
+                    .col(RemoteBindings::Account)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RemoteBindings::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(NostrIngestedEvents::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum NostrIngestedEvents {
+    Table,
+    Id,
+    EventId, //hex
+    Pubkey,  //hex, author of the event
+    Kind,
+    LamportId,
+    Outcome,
+    EventCreatedAt,
+    IngestedAt,
+}
+
+#[derive(DeriveIden)]
+enum RemoteBindings {
+    Table,
+    Id,
+    ServerPubkey, //hex, the lamport id server that published the binding
+    EventId,
+    LamportId, //lamport id on that server
+    Provider,  //social provider, or "address" for wallet bindings
+    Account,
+    CreatedAt,
+}
@@ -0,0 +1,118 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(NostrEvents::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(NostrEvents::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(NostrEvents::EventId)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(NostrEvents::Kind).integer().not_null())
+                    .col(ColumnDef::new(NostrEvents::Raw).text().not_null())
+                    .col(
+                        ColumnDef::new(NostrEvents::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(NostrDeliveries::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(NostrDeliveries::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(NostrDeliveries::EventId).string().not_null())
+                    .col(ColumnDef::new(NostrDeliveries::Relay).string().not_null())
+                    .col(ColumnDef::new(NostrDeliveries::Status).string().not_null())
+                    .col(ColumnDef::new(NostrDeliveries::Error).text().null())
+                    .col(
+                        ColumnDef::new(NostrDeliveries::Attempts)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(NostrDeliveries::NextRetryAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(NostrDeliveries::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_nostr_deliveries_event_relay")
+                    .table(NostrDeliveries::Table)
+                    .col(NostrDeliveries::EventId)
+                    .col(NostrDeliveries::Relay)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(NostrDeliveries::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(NostrEvents::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum NostrEvents {
+    Table,
+    Id,
+    EventId, //hex
+    Kind,
+    Raw, //signed event json, republished on retries
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum NostrDeliveries {
+    Table,
+    Id,
+    EventId,
+    Relay,
+    Status, //ok or error
+    Error,
+    Attempts,
+    NextRetryAt, //null once delivered or given up
+    UpdatedAt,
+}
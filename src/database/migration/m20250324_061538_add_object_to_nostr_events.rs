@@ -0,0 +1,62 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(NostrEvents::Table)
+                    .add_column(ColumnDef::new(NostrEvents::LamportId).string().null())
+                    .add_column(ColumnDef::new(NostrEvents::ObjectType).string().null())
+                    .add_column(ColumnDef::new(NostrEvents::ObjectId).string().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_nostr_events_lamport_id")
+                    .table(NostrEvents::Table)
+                    .col(NostrEvents::LamportId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_nostr_events_object")
+                    .table(NostrEvents::Table)
+                    .col(NostrEvents::ObjectType)
+                    .col(NostrEvents::ObjectId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(NostrEvents::Table)
+                    .drop_column(NostrEvents::LamportId)
+                    .drop_column(NostrEvents::ObjectType)
+                    .drop_column(NostrEvents::ObjectId)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum NostrEvents {
+    Table,
+    LamportId, //the LamportID tag
+    ObjectType, //user, vote, proposal, binding or attestation
+    ObjectId,
+}
@@ -0,0 +1,177 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+//events written to the embedded relay by others get their own table, and our events get the
+//author and signing time so relay filters can be answered in sql
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(NostrEvents::Table)
+                    .add_column(ColumnDef::new(NostrEvents::Pubkey).string().null())
+                    .add_column(ColumnDef::new(NostrEvents::SignedAt).timestamp_with_time_zone().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"UPDATE nostr_events SET pubkey = raw::jsonb ->> 'pubkey',
+                signed_at = to_timestamp((raw::jsonb ->> 'created_at')::bigint)"#,
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(NostrEvents::Table)
+                    .modify_column(ColumnDef::new(NostrEvents::Pubkey).string().not_null())
+                    .modify_column(ColumnDef::new(NostrEvents::SignedAt).timestamp_with_time_zone().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_nostr_events_kind_signed_at")
+                    .table(NostrEvents::Table)
+                    .col(NostrEvents::Kind)
+                    .col(NostrEvents::SignedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(NostrRelayEvents::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(NostrRelayEvents::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(NostrRelayEvents::EventId)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(NostrRelayEvents::Pubkey).string().not_null())
+                    .col(ColumnDef::new(NostrRelayEvents::Kind).integer().not_null())
+                    .col(ColumnDef::new(NostrRelayEvents::Raw).text().not_null())
+                    .col(
+                        ColumnDef::new(NostrRelayEvents::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(NostrRelayEvents::ReceivedAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_nostr_relay_events_kind_created_at")
+                    .table(NostrRelayEvents::Table)
+                    .col(NostrRelayEvents::Kind)
+                    .col(NostrRelayEvents::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_nostr_relay_events_pubkey")
+                    .table(NostrRelayEvents::Table)
+                    .col(NostrRelayEvents::Pubkey)
+                    .to_owned(),
+            )
+            .await?;
+
+        //relay writes were kept in nostr_events without a lamport id or object, a key that signed
+        //any linked event is one of ours, so rows of other keys are moved once
+        let conn = manager.get_connection();
+        conn.execute_unprepared(
+            r#"INSERT INTO nostr_relay_events (event_id, pubkey, kind, raw, created_at, received_at)
+            SELECT event_id, pubkey, kind, raw, signed_at, created_at FROM nostr_events
+            WHERE lamport_id IS NULL AND object_type IS NULL AND pubkey NOT IN
+                (SELECT DISTINCT pubkey FROM nostr_events WHERE lamport_id IS NOT NULL OR object_type IS NOT NULL)
+            ON CONFLICT (event_id) DO NOTHING"#,
+        )
+        .await?;
+        conn.execute_unprepared(
+            r#"DELETE FROM nostr_events n WHERE n.lamport_id IS NULL AND n.object_type IS NULL
+            AND EXISTS (SELECT 1 FROM nostr_relay_events r WHERE r.event_id = n.event_id)"#,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        //relay writes go back to where older versions read them
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"INSERT INTO nostr_events (event_id, kind, raw, created_at, pubkey, signed_at)
+                SELECT event_id, kind, raw, received_at, pubkey, created_at FROM nostr_relay_events
+                ON CONFLICT (event_id) DO NOTHING"#,
+            )
+            .await?;
+        manager
+            .drop_table(Table::drop().table(NostrRelayEvents::Table).to_owned())
+            .await?;
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_nostr_events_kind_signed_at")
+                    .table(NostrEvents::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(NostrEvents::Table)
+                    .drop_column(NostrEvents::Pubkey)
+                    .drop_column(NostrEvents::SignedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum NostrEvents {
+    Table,
+    Kind,
+    Pubkey,   //hex, the server key
+    SignedAt, //created_at of the signed event
+}
+
+#[derive(DeriveIden)]
+enum NostrRelayEvents {
+    Table,
+    Id,
+    EventId, //hex
+    Pubkey,  //hex, author of the event
+    Kind,
+    Raw,       //signed json
+    CreatedAt, //created_at of the signed event
+    ReceivedAt,
+}
@@ -9,6 +9,29 @@ mod m20250116_063026_create_lamport_id_table;
 mod m20250116_131947_create_group_table;
 mod m20250125_140924_create_bindings_table;
 mod m20250128_081521_create_events_table;
+mod m20250207_031522_create_lamport_id_reservations_table;
+mod m20250207_032104_create_lamport_id_assignments_table;
+mod m20250207_032617_create_lamport_id_audit_table;
+mod m20250211_072340_create_linked_addresses_table;
+mod m20250214_031208_create_user_roles_table;
+mod m20250214_031745_create_user_sanctions_table;
+mod m20250214_032216_create_admin_audit_table;
+mod m20250214_032740_add_hidden_to_proposals_table;
+mod m20250218_094512_create_attestations_table;
+mod m20250221_083015_add_sybil_signal_columns;
+mod m20250224_061530_add_expires_at_to_twitter_binding;
+mod m20250227_024410_create_social_bindings_table;
+mod m20250303_051207_create_binding_releases_table;
+mod m20250307_031522_add_profile_and_username_history;
+mod m20250310_084417_add_email_verification_and_notifications;
+mod m20250312_062938_create_privacy_settings_table;
+mod m20250314_093105_add_deleted_at_to_users;
+mod m20250317_041253_add_nostr_pubkey_to_users;
+mod m20250319_052716_create_nostr_ingest_tables;
+mod m20250321_083044_create_nostr_delivery_tables;
+mod m20250324_061538_add_object_to_nostr_events;
+mod m20250328_052341_create_nostr_relay_events;
+mod m20250330_024518_drop_show_addresses_from_privacy_settings;
 
 pub struct Migrator;
 
@@ -25,6 +48,29 @@ impl MigratorTrait for Migrator {
             Box::new(m20250116_131947_create_group_table::Migration),
             Box::new(m20250125_140924_create_bindings_table::Migration),
             Box::new(m20250128_081521_create_events_table::Migration),
+            Box::new(m20250207_031522_create_lamport_id_reservations_table::Migration),
+            Box::new(m20250207_032104_create_lamport_id_assignments_table::Migration),
+            Box::new(m20250207_032617_create_lamport_id_audit_table::Migration),
+            Box::new(m20250211_072340_create_linked_addresses_table::Migration),
+            Box::new(m20250214_031208_create_user_roles_table::Migration),
+            Box::new(m20250214_031745_create_user_sanctions_table::Migration),
+            Box::new(m20250214_032216_create_admin_audit_table::Migration),
+            Box::new(m20250214_032740_add_hidden_to_proposals_table::Migration),
+            Box::new(m20250218_094512_create_attestations_table::Migration),
+            Box::new(m20250221_083015_add_sybil_signal_columns::Migration),
+            Box::new(m20250224_061530_add_expires_at_to_twitter_binding::Migration),
+            Box::new(m20250227_024410_create_social_bindings_table::Migration),
+            Box::new(m20250303_051207_create_binding_releases_table::Migration),
+            Box::new(m20250307_031522_add_profile_and_username_history::Migration),
+            Box::new(m20250310_084417_add_email_verification_and_notifications::Migration),
+            Box::new(m20250312_062938_create_privacy_settings_table::Migration),
+            Box::new(m20250314_093105_add_deleted_at_to_users::Migration),
+            Box::new(m20250317_041253_add_nostr_pubkey_to_users::Migration),
+            Box::new(m20250319_052716_create_nostr_ingest_tables::Migration),
+            Box::new(m20250321_083044_create_nostr_delivery_tables::Migration),
+            Box::new(m20250324_061538_add_object_to_nostr_events::Migration),
+            Box::new(m20250328_052341_create_nostr_relay_events::Migration),
+            Box::new(m20250330_024518_drop_show_addresses_from_privacy_settings::Migration),
         ]
     }
 }
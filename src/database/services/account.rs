@@ -0,0 +1,186 @@
+use crate::{
+    common::error::AppResult,
+    database::{
+        entities::{
+            attestations, events, linked_addresses, nostr_deliveries, nostr_events, notification_preferences, points,
+            power,
+            prelude::{
+                Events, LinkedAddresses, NostrDeliveries, NostrEvents, NotificationPreferences, Points, Power,
+                PrivacySettings, Proposals, SocialBindings, UsernameHistory, Vote,
+            },
+            privacy_settings, proposals, social_bindings, username_history, users, vote,
+        },
+        DbTxn, Storage,
+    },
+};
+use sea_orm::{prelude::Expr, sea_query::Query, *};
+use serde::Serialize;
+
+/// Everything stored about a lamport id, stored oauth tokens left out.
+#[derive(Debug, Serialize)]
+pub struct AccountArchive {
+    pub exported_at: chrono::DateTime<chrono::Utc>,
+    pub user: users::Model,
+    pub linked_addresses: Vec<linked_addresses::Model>,
+    pub bindings: Vec<social_bindings::Model>,
+    pub username_history: Vec<username_history::Model>,
+    pub proposals: Vec<proposals::Model>,
+    pub votes: Vec<vote::Model>,
+    pub points: Vec<points::Model>,
+    pub power: Vec<power::Model>,
+    pub events: Vec<events::Model>,
+    pub attestations_received: Vec<attestations::Model>,
+    pub attestations_given: Vec<attestations::Model>,
+    pub nostr_events: Vec<nostr_events::Model>,
+}
+
+impl Storage {
+    pub async fn export_account(&self, user: users::Model) -> AppResult<AccountArchive> {
+        let lamport_id = user.lamport_id.clone();
+        let conn = self.conn.as_ref();
+
+        let bindings = self
+            .get_social_bindings_by_user_id(lamport_id.as_str())
+            .await?
+            .into_iter()
+            .map(|mut b| {
+                b.access_token = None;
+                b.refresh_token = None;
+                b
+            })
+            .collect();
+
+        Ok(AccountArchive {
+            exported_at: chrono::Utc::now(),
+            linked_addresses: self.get_linked_addresses_by_uid(lamport_id.as_str()).await?,
+            bindings,
+            username_history: self.get_username_history_by_user_id(lamport_id.as_str()).await?,
+            proposals: Proposals::find()
+                .filter(proposals::Column::CreatedBy.eq(lamport_id.as_str()))
+                .order_by_asc(proposals::Column::CreatedAt)
+                .all(conn)
+                .await?,
+            votes: Vote::find()
+                .filter(vote::Column::VoterId.eq(lamport_id.as_str()))
+                .order_by_asc(vote::Column::CreatedAt)
+                .all(conn)
+                .await?,
+            points: Points::find()
+                .filter(points::Column::LamportId.eq(lamport_id.as_str()))
+                .order_by_asc(points::Column::CreatedAt)
+                .all(conn)
+                .await?,
+            power: Power::find()
+                .filter(power::Column::LamportId.eq(lamport_id.as_str()))
+                .order_by_asc(power::Column::CreatedAt)
+                .all(conn)
+                .await?,
+            events: self.get_all_events_by_lamport_id(lamport_id.as_str()).await?,
+            attestations_received: self.get_attestations_by_subject(lamport_id.as_str()).await?,
+            attestations_given: self.get_attestations_by_attester(lamport_id.as_str()).await?,
+            nostr_events: NostrEvents::find()
+                .filter(nostr_events::Column::LamportId.eq(lamport_id.as_str()))
+                .order_by_asc(nostr_events::Column::CreatedAt)
+                .all(conn)
+                .await?,
+            user,
+        })
+    }
+
+    //wipe the personal data of user, votes and proposals move to tombstone so tallies stay the same
+    pub async fn delete_account(&self, user: users::Model, tombstone: &str) -> AppResult<users::Model> {
+        let lamport_id = user.lamport_id.clone();
+        //all or nothing, a half deleted account would keep some personal data and lose the rest
+        let txn = DbTxn::new(self.conn.as_ref()).await?;
+        let conn = &txn.0;
+
+        Vote::update_many()
+            .col_expr(vote::Column::VoterId, Expr::value(tombstone))
+            .filter(vote::Column::VoterId.eq(lamport_id.as_str()))
+            .exec(conn)
+            .await?;
+
+        Proposals::update_many()
+            .col_expr(proposals::Column::CreatedBy, Expr::value(tombstone))
+            .filter(proposals::Column::CreatedBy.eq(lamport_id.as_str()))
+            .exec(conn)
+            .await?;
+
+        SocialBindings::delete_many()
+            .filter(social_bindings::Column::UserId.eq(lamport_id.as_str()))
+            .exec(conn)
+            .await?;
+        LinkedAddresses::delete_many()
+            .filter(linked_addresses::Column::LamportId.eq(lamport_id.as_str()))
+            .exec(conn)
+            .await?;
+        //old usernames stop redirecting and can be taken again
+        UsernameHistory::delete_many()
+            .filter(username_history::Column::UserId.eq(lamport_id.as_str()))
+            .exec(conn)
+            .await?;
+        Events::delete_many()
+            .filter(events::Column::LamportId.eq(lamport_id.as_str()))
+            .exec(conn)
+            .await?;
+        Points::delete_many()
+            .filter(points::Column::LamportId.eq(lamport_id.as_str()))
+            .exec(conn)
+            .await?;
+        Power::delete_many()
+            .filter(power::Column::LamportId.eq(lamport_id.as_str()))
+            .exec(conn)
+            .await?;
+        NotificationPreferences::delete_many()
+            .filter(notification_preferences::Column::UserId.eq(lamport_id.as_str()))
+            .exec(conn)
+            .await?;
+        PrivacySettings::delete_many()
+            .filter(privacy_settings::Column::UserId.eq(lamport_id.as_str()))
+            .exec(conn)
+            .await?;
+        //the events themselves are retracted on relays with a NIP-09 deletion, which stays with its
+        //deliveries so it keeps the retracted ids and is retried until every relay has it
+        let deletion = nostr_sdk::Kind::EventDeletion.as_u16() as i32;
+        NostrDeliveries::delete_many()
+            .filter(
+                nostr_deliveries::Column::EventId.in_subquery(
+                    Query::select()
+                        .column(nostr_events::Column::EventId)
+                        .from(NostrEvents)
+                        .and_where(nostr_events::Column::LamportId.eq(lamport_id.as_str()))
+                        .and_where(nostr_events::Column::Kind.ne(deletion))
+                        .to_owned(),
+                ),
+            )
+            .exec(conn)
+            .await?;
+        NostrEvents::delete_many()
+            .filter(nostr_events::Column::LamportId.eq(lamport_id.as_str()))
+            .filter(nostr_events::Column::Kind.ne(deletion))
+            .exec(conn)
+            .await?;
+
+        //the row stays so the lamport id is never handed out again, attestations keep pointing at it
+        let mut active = user.into_active_model();
+        active.name = Set("Deleted user".to_string());
+        active.user_name = Set(tombstone.to_string());
+        active.x_id = Set(String::new());
+        active.address = Set(tombstone.to_string());
+        active.image = Set(String::new());
+        active.email = Set(String::new());
+        active.bio = Set(String::new());
+        active.verified = Set(false);
+        active.verified_by = Set(None);
+        active.register_ip = Set(None);
+        active.email_verified_at = Set(None);
+        active.nostr_pubkey = Set(None);
+        active.deleted_at = Set(Some(chrono::Utc::now().into()));
+        active.updated_at = Set(Some(chrono::Utc::now().into()));
+
+        let deleted = active.update(conn).await?;
+        txn.commit_transaction().await?;
+
+        Ok(deleted)
+    }
+}
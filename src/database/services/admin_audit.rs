@@ -0,0 +1,39 @@
+use crate::{
+    common::error::AppResult,
+    database::{
+        entities::{admin_audit, prelude::AdminAudit},
+        Storage,
+    },
+};
+use sea_orm::*;
+
+impl Storage {
+    //every change made through the admin api is recorded here
+    pub async fn create_admin_audit(
+        &self,
+        operator: &str,
+        action: &str,
+        target: &str,
+        detail: Option<&str>,
+    ) -> AppResult<admin_audit::Model> {
+        let audit = admin_audit::ActiveModel {
+            operator: Set(operator.to_string()),
+            action: Set(action.to_string()),
+            target: Set(target.to_string()),
+            detail: Set(detail.map(|d| d.to_string())),
+            created_at: Set(chrono::Utc::now().into()),
+            ..Default::default()
+        };
+
+        Ok(audit.insert(self.conn.as_ref()).await?)
+    }
+
+    pub async fn get_admin_audit_logs(&self, offset: i64, limit: i64) -> AppResult<Vec<admin_audit::Model>> {
+        Ok(AdminAudit::find()
+            .order_by_desc(admin_audit::Column::CreatedAt)
+            .offset(offset as u64)
+            .limit(limit as u64)
+            .all(self.conn.as_ref())
+            .await?)
+    }
+}
@@ -0,0 +1,202 @@
+use crate::{
+    common::{
+        config::VerificationConfig,
+        error::{AppError, AppResult},
+        rbac::{Permission, Role},
+    },
+    database::{
+        entities::{
+            attestations,
+            prelude::{Attestations, Users},
+            users,
+        },
+        Storage,
+    },
+    helpers::web_of_trust::{resolve_verification, VerificationPolicy},
+};
+use sea_orm::*;
+use std::collections::HashSet;
+
+impl Storage {
+    //attest subject, an attestation revoked earlier by the same attester is renewed
+    pub async fn create_attestation(
+        &self,
+        attester: &str,
+        subject: &str,
+        subject_address: &str,
+        sig: &str,
+    ) -> AppResult<attestations::Model> {
+        let existing = Attestations::find()
+            .filter(attestations::Column::Attester.eq(attester))
+            .filter(attestations::Column::Subject.eq(subject))
+            .one(self.conn.as_ref())
+            .await?;
+
+        match existing {
+            Some(a) if !a.revoked => Err(AppError::ConflictError(format!(
+                "{} has already attested {}",
+                attester, subject
+            ))),
+            Some(a) => {
+                let mut active = a.into_active_model();
+                active.subject_address = Set(subject_address.to_string());
+                active.sig = Set(sig.to_string());
+                active.revoked = Set(false);
+                active.revoked_by = Set(None);
+                active.revoked_at = Set(None);
+                active.created_at = Set(chrono::Utc::now().into());
+                Ok(active.update(self.conn.as_ref()).await?)
+            }
+            None => Ok(attestations::ActiveModel {
+                attester: Set(attester.to_string()),
+                subject: Set(subject.to_string()),
+                subject_address: Set(subject_address.to_string()),
+                sig: Set(sig.to_string()),
+                revoked: Set(false),
+                created_at: Set(chrono::Utc::now().into()),
+                ..Default::default()
+            }
+            .insert(self.conn.as_ref())
+            .await?),
+        }
+    }
+
+    pub async fn revoke_attestation(&self, id: i32, operator: &str) -> AppResult<attestations::Model> {
+        let attestation = self.get_attestation(id).await?;
+        if attestation.revoked {
+            return Err(AppError::ConflictError(format!("Attestation {} has been revoked", id)));
+        }
+
+        let mut active = attestation.into_active_model();
+        active.revoked = Set(true);
+        active.revoked_by = Set(Some(operator.to_string()));
+        active.revoked_at = Set(Some(chrono::Utc::now().into()));
+
+        Ok(active.update(self.conn.as_ref()).await?)
+    }
+
+    pub async fn get_attestation(&self, id: i32) -> AppResult<attestations::Model> {
+        match Attestations::find_by_id(id).one(self.conn.as_ref()).await? {
+            Some(a) => Ok(a),
+            None => Err(AppError::CustomError(format!("Attestation {} has not existed", id))),
+        }
+    }
+
+    pub async fn get_attestations_by_subject(&self, subject: &str) -> AppResult<Vec<attestations::Model>> {
+        Ok(Attestations::find()
+            .filter(attestations::Column::Subject.eq(subject))
+            .order_by_desc(attestations::Column::CreatedAt)
+            .all(self.conn.as_ref())
+            .await?)
+    }
+
+    pub async fn get_attestations_by_attester(&self, attester: &str) -> AppResult<Vec<attestations::Model>> {
+        Ok(Attestations::find()
+            .filter(attestations::Column::Attester.eq(attester))
+            .order_by_desc(attestations::Column::CreatedAt)
+            .all(self.conn.as_ref())
+            .await?)
+    }
+
+    //trusted roots are the configured ones, the bootstrap admins and holders of a role that verifies users
+    pub async fn verification_policy(
+        &self,
+        config: &VerificationConfig,
+        bootstrap_admins: &[String],
+    ) -> AppResult<VerificationPolicy> {
+        let mut trusted: HashSet<String> = config.trusted_roots.iter().cloned().collect();
+        trusted.extend(bootstrap_admins.iter().cloned());
+
+        for role in Role::ALL.into_iter().filter(|r| r.grants(Permission::VerifyUsers)) {
+            trusted.extend(
+                self.get_role_assignments(Some(role))
+                    .await?
+                    .into_iter()
+                    .map(|r| r.lamport_id),
+            );
+        }
+
+        Ok(VerificationPolicy::new(config.required_attestations, trusted))
+    }
+
+    //recompute subject and everyone it attested down the chain, returns the users whose status flipped
+    pub async fn reevaluate_verification(
+        &self,
+        subject: &str,
+        policy: &VerificationPolicy,
+    ) -> AppResult<Vec<users::Model>> {
+        let mut region = vec![subject.to_string()];
+        let mut seen: HashSet<String> = HashSet::from([subject.to_string()]);
+        let mut frontier = region.clone();
+
+        while !frontier.is_empty() {
+            let reached = Attestations::find()
+                .filter(attestations::Column::Revoked.eq(false))
+                .filter(attestations::Column::Attester.is_in(frontier))
+                .all(self.conn.as_ref())
+                .await?;
+
+            frontier = reached
+                .into_iter()
+                .map(|a| a.subject)
+                .filter(|s| seen.insert(s.clone()))
+                .collect();
+            region.extend(frontier.iter().cloned());
+        }
+
+        let incoming: Vec<(String, String)> = Attestations::find()
+            .filter(attestations::Column::Revoked.eq(false))
+            .filter(attestations::Column::Subject.is_in(region.clone()))
+            .all(self.conn.as_ref())
+            .await?
+            .into_iter()
+            .map(|a| (a.attester, a.subject))
+            .collect();
+
+        let outside: Vec<String> = incoming
+            .iter()
+            .map(|(a, _)| a.clone())
+            .filter(|a| !seen.contains(a))
+            .collect();
+        let outside_verified: HashSet<String> = Users::find()
+            .filter(users::Column::LamportId.is_in(outside))
+            .filter(users::Column::Verified.eq(true))
+            .all(self.conn.as_ref())
+            .await?
+            .into_iter()
+            .map(|u| u.lamport_id)
+            .collect();
+
+        let resolved = resolve_verification(&region, &incoming, &outside_verified, policy);
+
+        let mut flipped = Vec::new();
+        for user in Users::find()
+            .filter(users::Column::LamportId.is_in(resolved.keys().cloned()))
+            .all(self.conn.as_ref())
+            .await?
+        {
+            let verified_by = resolved[&user.lamport_id].clone();
+            //a verified user whose credited attester withdrew is credited to another one
+            let credit_lost = user.verified
+                && !incoming
+                    .iter()
+                    .any(|(a, s)| s == &user.lamport_id && Some(a) == user.verified_by.as_ref());
+
+            if user.verified == verified_by.is_some() && !credit_lost {
+                continue;
+            }
+
+            let status_changed = user.verified != verified_by.is_some();
+            let mut active = user.into_active_model();
+            active.verified = Set(verified_by.is_some());
+            active.verified_by = Set(verified_by);
+            let updated = active.update(self.conn.as_ref()).await?;
+
+            if status_changed {
+                flipped.push(updated);
+            }
+        }
+
+        Ok(flipped)
+    }
+}
@@ -1,53 +1,259 @@
 use crate::{
     common::error::{AppError, AppResult},
     database::{
-        entities::{prelude::TwitterBinding, twitter_binding},
-        Storage,
+        entities::{
+            binding_releases,
+            prelude::{BindingReleases, SocialBindings},
+            social_bindings,
+        },
+        DbTxn, Storage,
     },
 };
 use sea_orm::*;
 
+/// Tokens kept for a binding, already encrypted.
+#[derive(Debug, Clone)]
+pub struct BindingTokens {
+    pub access_token: String,
+    pub refresh_token: Option<String>,
+    pub token_type: String,
+    pub scope: String,
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// An account of a provider proven by user_id, ready to be bound.
+#[derive(Debug, Clone)]
+pub struct NewSocialBinding {
+    pub user_id: String,
+    pub provider: String,
+    pub external_id: String,
+    pub name: String,
+    pub user_name: String,
+    pub image_url: Option<String>,
+    pub tokens: Option<BindingTokens>,
+}
+
+//bind an account of provider, db is the connection or the transaction of a rebind
+async fn insert_social_binding<C: ConnectionTrait>(db: &C, new: NewSocialBinding) -> AppResult<social_bindings::Model> {
+    let bound = SocialBindings::find()
+        .filter(social_bindings::Column::Provider.eq(new.provider.as_str()))
+        .filter(social_bindings::Column::ExternalId.eq(new.external_id.as_str()))
+        .one(db)
+        .await?;
+    if let Some(bound) = bound {
+        return Err(AppError::ConflictError(if bound.user_id == new.user_id {
+            format!("{} account has already been bound", new.provider)
+        } else {
+            format!("{} account is bound to another lamport id", new.provider)
+        }));
+    }
+
+    let existing = SocialBindings::find()
+        .filter(social_bindings::Column::UserId.eq(new.user_id.as_str()))
+        .filter(social_bindings::Column::Provider.eq(new.provider.as_str()))
+        .one(db)
+        .await?;
+    if existing.is_some() {
+        return Err(AppError::ConflictError(format!(
+            "{} has already bound a {} account",
+            new.user_id, new.provider
+        )));
+    }
+
+    let tokens = new.tokens;
+    let binding = social_bindings::ActiveModel {
+        user_id: Set(new.user_id),
+        provider: Set(new.provider),
+        external_id: Set(new.external_id),
+        name: Set(new.name),
+        user_name: Set(new.user_name),
+        image_url: Set(new.image_url),
+        access_token: Set(tokens.as_ref().map(|t| t.access_token.clone())),
+        refresh_token: Set(tokens.as_ref().and_then(|t| t.refresh_token.clone())),
+        token_type: Set(tokens.as_ref().map(|t| t.token_type.clone())),
+        scope: Set(tokens.as_ref().map(|t| t.scope.clone())),
+        expires_at: Set(tokens.as_ref().and_then(|t| t.expires_at).map(|t| t.into())),
+        created_at: Set(chrono::Utc::now().into()),
+        ..Default::default()
+    };
+
+    Ok(binding.insert(db).await?)
+}
+
+//delete a binding and remember it left its lamport id, cooldowns are counted from the release
+async fn insert_binding_release<C: ConnectionTrait>(
+    db: &C,
+    binding: social_bindings::Model,
+    reason: &str,
+) -> AppResult<binding_releases::Model> {
+    let release = binding_releases::ActiveModel {
+        user_id: Set(binding.user_id.clone()),
+        provider: Set(binding.provider.clone()),
+        external_id: Set(binding.external_id.clone()),
+        user_name: Set(binding.user_name.clone()),
+        reason: Set(reason.to_string()),
+        bound_at: Set(binding.created_at),
+        released_at: Set(chrono::Utc::now().into()),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?;
+
+    //released by someone else meanwhile, the transaction is rolled back
+    if binding.delete(db).await?.rows_affected == 0 {
+        return Err(AppError::ConflictError("binding has already been released".into()));
+    }
+
+    Ok(release)
+}
 
 impl Storage {
-    //create twitter binding
-    pub async fn binding_twitter(
+    //bind an account of provider to user_id
+    pub async fn create_social_binding(&self, new: NewSocialBinding) -> AppResult<social_bindings::Model> {
+        insert_social_binding(self.conn.as_ref(), new).await
+    }
+
+    pub async fn get_social_binding(&self, user_id: &str, provider: &str) -> AppResult<social_bindings::Model> {
+        match SocialBindings::find()
+            .filter(social_bindings::Column::UserId.eq(user_id))
+            .filter(social_bindings::Column::Provider.eq(provider))
+            .one(self.conn.as_ref())
+            .await?
+        {
+            Some(binding) => Ok(binding),
+            None => Err(AppError::CustomError(format!("{} binding has not existed", provider))),
+        }
+    }
+
+    pub async fn get_social_binding_by_external_id(
         &self,
-        user_id: String,
-        x_id: String,
-        name: String,
-        user_name: String,
-        image_url: String,
+        provider: &str,
+        external_id: &str,
+    ) -> AppResult<Option<social_bindings::Model>> {
+        Ok(SocialBindings::find()
+            .filter(social_bindings::Column::Provider.eq(provider))
+            .filter(social_bindings::Column::ExternalId.eq(external_id))
+            .one(self.conn.as_ref())
+            .await?)
+    }
+
+    pub async fn get_social_bindings_by_user_id(&self, user_id: &str) -> AppResult<Vec<social_bindings::Model>> {
+        Ok(SocialBindings::find()
+            .filter(social_bindings::Column::UserId.eq(user_id))
+            .order_by_asc(social_bindings::Column::CreatedAt)
+            .all(self.conn.as_ref())
+            .await?)
+    }
+
+    //lock a binding until txn ends, so the rotating refresh token is only used by one refresh at a time
+    pub async fn lock_social_binding(&self, txn: &DbTxn, id: i32) -> AppResult<Option<social_bindings::Model>> {
+        Ok(SocialBindings::find_by_id(id).lock_exclusive().one(&txn.0).await?)
+    }
+
+    //store refreshed tokens, refresh tokens rotate so both are replaced
+    pub async fn update_binding_tokens(
+        &self,
+        txn: &DbTxn,
+        binding: social_bindings::Model,
         access_token: String,
-        refresh_token: String,
-        token_type: String,
-        scope: String,
-    ) -> AppResult<twitter_binding::Model> {
-        let binding : twitter_binding::ActiveModel = twitter_binding::ActiveModel {
-            user_id : Set(user_id),
-            x_id : Set(x_id),
-            name : Set(name),
-            user_name : Set(user_name),
-            image_url : Set(image_url),
-            access_token : Set(access_token),
-            refresh_token : Set(refresh_token),
-            token_type : Set(token_type),
-            scope : Set(scope),
-            ..Default::default()
-        };
-
-        Ok(binding.insert(self.conn.as_ref()).await?)
-    }
-
-    //get twitter binding by user id
-    pub async fn get_twitter_binding_by_user_id(&self, user_id: &str) -> AppResult<twitter_binding::Model> {
-        match TwitterBinding::find()
-            .filter(twitter_binding::Column::UserId.eq(user_id))
+        refresh_token: Option<String>,
+        expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> AppResult<social_bindings::Model> {
+        let mut active = binding.into_active_model();
+        active.access_token = Set(Some(access_token));
+        active.refresh_token = Set(refresh_token);
+        active.expires_at = Set(expires_at.map(|t| t.into()));
+
+        Ok(active.update(&txn.0).await?)
+    }
+
+    //refreshable bindings of provider whose token expires before the given time
+    pub async fn get_expiring_bindings(
+        &self,
+        provider: &str,
+        before: chrono::DateTime<chrono::Utc>,
+    ) -> AppResult<Vec<social_bindings::Model>> {
+        Ok(SocialBindings::find()
+            .filter(social_bindings::Column::Provider.eq(provider))
+            .filter(social_bindings::Column::RefreshToken.is_not_null())
+            .filter(social_bindings::Column::ExpiresAt.lt(before))
+            .all(self.conn.as_ref())
+            .await?)
+    }
+
+    //bindings of provider with a token stored before encryption, sealed tokens start with sealed_prefix
+    pub async fn get_unsealed_bindings(&self, provider: &str, sealed_prefix: &str) -> AppResult<Vec<social_bindings::Model>> {
+        let sealed = format!("{}%", sealed_prefix);
+        Ok(SocialBindings::find()
+            .filter(social_bindings::Column::Provider.eq(provider))
+            .filter(
+                Condition::any()
+                    .add(social_bindings::Column::AccessToken.not_like(sealed.as_str()))
+                    .add(social_bindings::Column::RefreshToken.not_like(sealed.as_str())),
+            )
+            .all(self.conn.as_ref())
+            .await?)
+    }
+
+    pub async fn delete_social_binding(&self, binding: social_bindings::Model) -> AppResult<()> {
+        binding.delete(self.conn.as_ref()).await?;
+
+        Ok(())
+    }
+
+    //delete a binding and remember it left its lamport id, cooldowns are counted from the release
+    pub async fn release_social_binding(
+        &self,
+        binding: social_bindings::Model,
+        reason: &str,
+    ) -> AppResult<binding_releases::Model> {
+        let txn = DbTxn::new(self.conn.as_ref()).await?;
+        let release = insert_binding_release(&txn.0, binding, reason).await?;
+        txn.commit_transaction().await?;
+
+        Ok(release)
+    }
+
+    //move an account from its previous lamport id to new.user_id, the account is never left unbound half way
+    pub async fn rebind_social_binding(
+        &self,
+        previous: social_bindings::Model,
+        reason: &str,
+        new: NewSocialBinding,
+    ) -> AppResult<(binding_releases::Model, social_bindings::Model)> {
+        let txn = DbTxn::new(self.conn.as_ref()).await?;
+        let release = insert_binding_release(&txn.0, previous, reason).await?;
+        let binding = insert_social_binding(&txn.0, new).await?;
+        txn.commit_transaction().await?;
+
+        Ok((release, binding))
+    }
+
+    //latest release of an account of provider
+    pub async fn get_last_release_by_external_id(
+        &self,
+        provider: &str,
+        external_id: &str,
+    ) -> AppResult<Option<binding_releases::Model>> {
+        Ok(BindingReleases::find()
+            .filter(binding_releases::Column::Provider.eq(provider))
+            .filter(binding_releases::Column::ExternalId.eq(external_id))
+            .order_by_desc(binding_releases::Column::ReleasedAt)
             .one(self.conn.as_ref())
-            .await? {
-                Some(binding) => Ok(binding),
-                None => Err(AppError::CustomError("Twitter binding has not existed".to_string())),
-            }
+            .await?)
     }
 
+    //latest account of provider that left user_id
+    pub async fn get_last_release_by_user_id(
+        &self,
+        user_id: &str,
+        provider: &str,
+    ) -> AppResult<Option<binding_releases::Model>> {
+        Ok(BindingReleases::find()
+            .filter(binding_releases::Column::UserId.eq(user_id))
+            .filter(binding_releases::Column::Provider.eq(provider))
+            .order_by_desc(binding_releases::Column::ReleasedAt)
+            .one(self.conn.as_ref())
+            .await?)
+    }
 }
-
@@ -1,30 +1,306 @@
 use crate::{
-    common::error::{AppError, AppResult},
+    common::{
+        consts,
+        error::{AppError, AppResult},
+    },
     database::{
-        entities::prelude::LamportId,
-        Storage,
+        entities::{
+            lamport_id, lamport_id_assignments, lamport_id_audit, lamport_id_reservations,
+            prelude::{LamportId, LamportIdAssignments, LamportIdAudit, LamportIdReservations, Users},
+            users,
+        },
+        DbTxn, Storage,
     },
 };
 use sea_orm::*;
 
 
+//walk forward from value until it is neither inside a reserved range nor assigned
+async fn skip_reserved_lamport_id<C: ConnectionTrait>(db: &C, value: i64) -> AppResult<i64> {
+    let mut candidate = value;
+    loop {
+        if let Some(range) = LamportIdReservations::find()
+            .filter(lamport_id_reservations::Column::StartValue.lte(candidate))
+            .filter(lamport_id_reservations::Column::EndValue.gte(candidate))
+            .order_by_desc(lamport_id_reservations::Column::EndValue)
+            .one(db)
+            .await?
+        {
+            candidate = range.end_value + 1;
+            continue;
+        }
+
+        let assigned = LamportIdAssignments::find()
+            .filter(lamport_id_assignments::Column::LamportValue.eq(candidate))
+            .count(db)
+            .await?;
+        if assigned > 0 {
+            candidate += 1;
+            continue;
+        }
+
+        return Ok(candidate);
+    }
+}
+
+async fn insert_lamport_id_audit<C: ConnectionTrait>(
+    db: &C,
+    action: &str,
+    start: i64,
+    end: i64,
+    address: Option<&str>,
+    reason: Option<&str>,
+    operator: &str,
+) -> AppResult<lamport_id_audit::Model> {
+    let audit = lamport_id_audit::ActiveModel {
+        action: Set(action.to_string()),
+        start_value: Set(start),
+        end_value: Set(end),
+        address: Set(address.map(|a| a.to_string())),
+        reason: Set(reason.map(|r| r.to_string())),
+        operator: Set(operator.to_string()),
+        created_at: Set(chrono::Utc::now().into()),
+        ..Default::default()
+    };
+
+    Ok(audit.insert(db).await?)
+}
+
 impl Storage {
+    //next lamport id for a normal sign-up, reserved ranges and assigned numbers are skipped
     pub async fn get_current_lamport_id(&self) -> AppResult<i64> {
         match LamportId::find().one(self.conn.as_ref()).await? {
-            Some(last) => Ok(last.current_value),
+            Some(last) => skip_reserved_lamport_id(self.conn.as_ref(), last.current_value).await,
             None => Err(AppError::CustomError("LamportId has not existed".into()))
         }
     }
 
-    pub async fn increase_lamport_id(&self) -> AppResult<i64> {
-        if let Some(last) = LamportId::find().one(self.conn.as_ref()).await? {
-            let mut last_mut = last.clone().into_active_model();
-            last_mut.current_value = Set(last.current_value + 1_i64);
-            last_mut.updated_at = Set(chrono::Utc::now().into());
-            last_mut.update(self.conn.as_ref()).await?;
-            Ok(last.current_value)
-        } else {
-            Err(AppError::CustomError("LamportId has not existed".into()))
+    //lock the counter row until txn ends, sign-ups and changes to reservations or assignments run one at a time
+    pub async fn lock_lamport_id(&self, txn: &DbTxn) -> AppResult<lamport_id::Model> {
+        match LamportId::find().lock_exclusive().one(&txn.0).await? {
+            Some(last) => Ok(last),
+            None => Err(AppError::CustomError("LamportId has not existed".into())),
+        }
+    }
+
+    //hand out the next lamport id inside txn, it is given back if txn is rolled back
+    pub async fn increase_lamport_id(&self, txn: &DbTxn) -> AppResult<i64> {
+        let last = self.lock_lamport_id(txn).await?;
+        let current = skip_reserved_lamport_id(&txn.0, last.current_value).await?;
+        let mut last_mut = last.into_active_model();
+        last_mut.current_value = Set(current + 1_i64);
+        last_mut.updated_at = Set(chrono::Utc::now().into());
+        last_mut.update(&txn.0).await?;
+        Ok(current)
+    }
+
+    //reserve [start, end] so that normal sign-ups never receive these numbers
+    pub async fn reserve_lamport_id_range(
+        &self,
+        start: i64,
+        end: i64,
+        reason: &str,
+        operator: &str,
+    ) -> AppResult<lamport_id_reservations::Model> {
+        if start < 1 || start > end {
+            return Err(AppError::InputValidateError("invalid lamport id range".into()));
         }
+
+        let txn = DbTxn::new(self.conn.as_ref()).await?;
+        self.lock_lamport_id(&txn).await?;
+
+        let overlapped = LamportIdReservations::find()
+            .filter(lamport_id_reservations::Column::StartValue.lte(end))
+            .filter(lamport_id_reservations::Column::EndValue.gte(start))
+            .count(&txn.0)
+            .await?;
+        if overlapped > 0 {
+            return Err(AppError::ConflictError(format!(
+                "Range {}-{} overlaps an existing reservation",
+                start, end
+            )));
+        }
+
+        let reservation = lamport_id_reservations::ActiveModel {
+            start_value: Set(start),
+            end_value: Set(end),
+            reason: Set(reason.to_string()),
+            created_by: Set(operator.to_string()),
+            created_at: Set(chrono::Utc::now().into()),
+            ..Default::default()
+        }
+        .insert(&txn.0)
+        .await?;
+
+        insert_lamport_id_audit(&txn.0, consts::LAMPORT_ID_ACTION_RESERVE, start, end, None, Some(reason), operator)
+            .await?;
+        txn.commit_transaction().await?;
+
+        Ok(reservation)
+    }
+
+    //release a reserved range, numbers below the counter are not handed out again
+    pub async fn release_lamport_id_range(&self, id: i32, operator: &str) -> AppResult<lamport_id_reservations::Model> {
+        let txn = DbTxn::new(self.conn.as_ref()).await?;
+        self.lock_lamport_id(&txn).await?;
+
+        let reservation = match LamportIdReservations::find_by_id(id).one(&txn.0).await? {
+            Some(r) => r,
+            None => return Err(AppError::CustomError(format!("Reservation {} has not existed", id))),
+        };
+
+        reservation.clone().delete(&txn.0).await?;
+
+        insert_lamport_id_audit(
+            &txn.0,
+            consts::LAMPORT_ID_ACTION_RELEASE,
+            reservation.start_value,
+            reservation.end_value,
+            None,
+            Some(reservation.reason.as_str()),
+            operator,
+        )
+        .await?;
+        txn.commit_transaction().await?;
+
+        Ok(reservation)
+    }
+
+    pub async fn get_lamport_id_reservations(&self) -> AppResult<Vec<lamport_id_reservations::Model>> {
+        Ok(LamportIdReservations::find()
+            .order_by_asc(lamport_id_reservations::Column::StartValue)
+            .all(self.conn.as_ref())
+            .await?)
+    }
+
+    //assign a specific number to an address, it is claimed when the address registers
+    pub async fn assign_lamport_id(
+        &self,
+        value: i64,
+        address: &str,
+        reason: &str,
+        operator: &str,
+    ) -> AppResult<lamport_id_assignments::Model> {
+        if value < 1 {
+            return Err(AppError::InputValidateError("invalid lamport id".into()));
+        }
+
+        let txn = DbTxn::new(self.conn.as_ref()).await?;
+        self.lock_lamport_id(&txn).await?;
+
+        let issued = Users::find()
+            .filter(users::Column::LamportId.eq(value.to_string()))
+            .count(&txn.0)
+            .await?;
+        if issued > 0 {
+            return Err(AppError::ConflictError(format!("Lamport ID {} has been issued", value)));
+        }
+
+        if self.is_user_exists_by_address(address).await? {
+            return Err(AppError::ConflictError(format!("Address {} already holds a Lamport ID", address)));
+        }
+
+        let assigned = LamportIdAssignments::find()
+            .filter(
+                Condition::any()
+                    .add(lamport_id_assignments::Column::LamportValue.eq(value))
+                    .add(lamport_id_assignments::Column::Address.eq(address)),
+            )
+            .count(&txn.0)
+            .await?;
+        if assigned > 0 {
+            return Err(AppError::ConflictError(format!(
+                "Lamport ID {} or address {} has been assigned",
+                value, address
+            )));
+        }
+
+        let assignment = lamport_id_assignments::ActiveModel {
+            lamport_value: Set(value),
+            address: Set(address.to_string()),
+            reason: Set(reason.to_string()),
+            assigned_by: Set(operator.to_string()),
+            claimed: Set(false),
+            created_at: Set(chrono::Utc::now().into()),
+            ..Default::default()
+        }
+        .insert(&txn.0)
+        .await?;
+
+        insert_lamport_id_audit(&txn.0, consts::LAMPORT_ID_ACTION_ASSIGN, value, value, Some(address), Some(reason), operator)
+            .await?;
+        txn.commit_transaction().await?;
+
+        Ok(assignment)
+    }
+
+    //release an assignment that has not been claimed yet
+    pub async fn release_lamport_id_assignment(&self, value: i64, operator: &str) -> AppResult<lamport_id_assignments::Model> {
+        let txn = DbTxn::new(self.conn.as_ref()).await?;
+        self.lock_lamport_id(&txn).await?;
+
+        let assignment = match LamportIdAssignments::find()
+            .filter(lamport_id_assignments::Column::LamportValue.eq(value))
+            .one(&txn.0)
+            .await?
+        {
+            Some(a) => a,
+            None => return Err(AppError::CustomError(format!("Lamport ID {} has not been assigned", value))),
+        };
+
+        if assignment.claimed {
+            return Err(AppError::ConflictError(format!("Lamport ID {} has been claimed", value)));
+        }
+
+        assignment.clone().delete(&txn.0).await?;
+
+        insert_lamport_id_audit(
+            &txn.0,
+            consts::LAMPORT_ID_ACTION_RELEASE,
+            value,
+            value,
+            Some(assignment.address.as_str()),
+            Some(assignment.reason.as_str()),
+            operator,
+        )
+        .await?;
+        txn.commit_transaction().await?;
+
+        Ok(assignment)
+    }
+
+    pub async fn get_lamport_id_assignments(&self) -> AppResult<Vec<lamport_id_assignments::Model>> {
+        Ok(LamportIdAssignments::find()
+            .order_by_asc(lamport_id_assignments::Column::LamportValue)
+            .all(self.conn.as_ref())
+            .await?)
+    }
+
+    //unclaimed assignment for address
+    pub async fn get_lamport_id_assignment_by_address(&self, address: &str) -> AppResult<Option<lamport_id_assignments::Model>> {
+        Ok(LamportIdAssignments::find()
+            .filter(lamport_id_assignments::Column::Address.eq(address))
+            .filter(lamport_id_assignments::Column::Claimed.eq(false))
+            .one(self.conn.as_ref())
+            .await?)
+    }
+
+    pub async fn claim_lamport_id_assignment(
+        &self,
+        txn: &DbTxn,
+        assignment: lamport_id_assignments::Model,
+    ) -> AppResult<lamport_id_assignments::Model> {
+        let mut active = assignment.into_active_model();
+        active.claimed = Set(true);
+        Ok(active.update(&txn.0).await?)
+    }
+
+    pub async fn get_lamport_id_audit_logs(&self, offset: i64, limit: i64) -> AppResult<Vec<lamport_id_audit::Model>> {
+        Ok(LamportIdAudit::find()
+            .order_by_desc(lamport_id_audit::Column::CreatedAt)
+            .offset(offset as u64)
+            .limit(limit as u64)
+            .all(self.conn.as_ref())
+            .await?)
     }
 }
@@ -0,0 +1,76 @@
+use crate::{
+    common::error::{AppError, AppResult},
+    database::{
+        entities::{linked_addresses, prelude::LinkedAddresses},
+        Storage,
+    },
+};
+use sea_orm::*;
+
+impl Storage {
+    //link an extra address to lamport id
+    pub async fn link_address(
+        &self,
+        lamport_id: &str,
+        address: &str,
+        sig: &str,
+    ) -> AppResult<linked_addresses::Model> {
+        if self.is_user_exists_by_address(address).await? {
+            return Err(AppError::ConflictError(format!(
+                "Address {} has been linked to a Lamport ID",
+                address
+            )));
+        }
+
+        let linked = linked_addresses::ActiveModel {
+            lamport_id: Set(lamport_id.to_string()),
+            address: Set(address.to_string()),
+            sig: Set(sig.to_string()),
+            created_at: Set(chrono::Utc::now().into()),
+            ..Default::default()
+        };
+
+        Ok(linked.insert(self.conn.as_ref()).await?)
+    }
+
+    //unlink address from lamport id, the primary address can not be unlinked
+    pub async fn unlink_address(&self, lamport_id: &str, address: &str) -> AppResult<linked_addresses::Model> {
+        match LinkedAddresses::find()
+            .filter(linked_addresses::Column::LamportId.eq(lamport_id))
+            .filter(linked_addresses::Column::Address.eq(address))
+            .one(self.conn.as_ref())
+            .await?
+        {
+            Some(linked) => {
+                linked.clone().delete(self.conn.as_ref()).await?;
+                Ok(linked)
+            }
+            None => Err(AppError::CustomError(format!(
+                "Address {} is not linked to {}",
+                address, lamport_id
+            ))),
+        }
+    }
+
+    pub async fn get_linked_addresses_by_uid(&self, lamport_id: &str) -> AppResult<Vec<linked_addresses::Model>> {
+        Ok(LinkedAddresses::find()
+            .filter(linked_addresses::Column::LamportId.eq(lamport_id))
+            .order_by_asc(linked_addresses::Column::CreatedAt)
+            .all(self.conn.as_ref())
+            .await?)
+    }
+
+    pub async fn count_linked_addresses_by_uid(&self, lamport_id: &str) -> AppResult<u64> {
+        Ok(LinkedAddresses::find()
+            .filter(linked_addresses::Column::LamportId.eq(lamport_id))
+            .count(self.conn.as_ref())
+            .await?)
+    }
+
+    pub async fn get_linked_address(&self, address: &str) -> AppResult<Option<linked_addresses::Model>> {
+        Ok(LinkedAddresses::find()
+            .filter(linked_addresses::Column::Address.eq(address))
+            .one(self.conn.as_ref())
+            .await?)
+    }
+}
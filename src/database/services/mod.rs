@@ -7,3 +7,16 @@ pub mod proposal;
 pub mod vote;
 pub mod binding;
 pub mod events;
+pub mod linked_address;
+pub mod role;
+pub mod sanction;
+pub mod admin_audit;
+pub mod attestation;
+pub mod sybil;
+pub mod profile;
+pub mod notification;
+pub mod account;
+pub mod nostr_key;
+pub mod nostr_ingest;
+pub mod nostr_delivery;
+pub mod nostr_relay;
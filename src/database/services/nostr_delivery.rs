@@ -0,0 +1,219 @@
+use crate::{
+    common::{consts, error::AppResult},
+    database::{
+        entities::{
+            nostr_deliveries, nostr_events,
+            prelude::{NostrDeliveries, NostrEvents},
+        },
+        Storage,
+    },
+};
+use sea_orm::{
+    sea_query::{Expr, Query, SimpleExpr},
+    *,
+};
+
+//created_at of a signed event
+pub fn signed_at(event: &nostr_sdk::Event) -> chrono::DateTime<chrono::Utc> {
+    chrono::DateTime::from_timestamp(event.created_at.as_u64() as i64, 0).unwrap_or_default()
+}
+
+//EXISTS a delivery row of the nostr_events row
+fn has_deliveries() -> SimpleExpr {
+    Expr::exists(
+        Query::select()
+            .expr(Expr::val(1))
+            .from(NostrDeliveries)
+            .and_where(
+                Expr::col((NostrDeliveries, nostr_deliveries::Column::EventId))
+                    .equals((NostrEvents, nostr_events::Column::EventId)),
+            )
+            .to_owned(),
+    )
+}
+
+impl Storage {
+    //kept until every write relay has the event, queued events may be signed again after a crash
+    //object is the domain object the event was published for, as (object_type, object_id)
+    pub async fn save_nostr_event(
+        &self,
+        event: &nostr_sdk::Event,
+        lamport_id: Option<String>,
+        object: Option<(String, String)>,
+    ) -> AppResult<nostr_events::Model> {
+        let event_id = event.id.to_hex();
+        if let Some(event) = self.get_nostr_event(event_id.as_str()).await? {
+            return Ok(event);
+        }
+
+        let (object_type, object_id) = object.unzip();
+        let active = nostr_events::ActiveModel {
+            event_id: Set(event_id),
+            kind: Set(event.kind.as_u16() as i32),
+            raw: Set(nostr_sdk::JsonUtil::as_json(event)),
+            created_at: Set(chrono::Utc::now().into()),
+            pubkey: Set(event.pubkey.to_hex()),
+            signed_at: Set(signed_at(event).into()),
+            lamport_id: Set(lamport_id),
+            object_type: Set(object_type),
+            object_id: Set(object_id),
+            ..Default::default()
+        };
+
+        Ok(active.insert(self.conn.as_ref()).await?)
+    }
+
+    pub async fn get_nostr_event(&self, event_id: &str) -> AppResult<Option<nostr_events::Model>> {
+        Ok(NostrEvents::find()
+            .filter(nostr_events::Column::EventId.eq(event_id))
+            .one(self.conn.as_ref())
+            .await?)
+    }
+
+    //signed events of a lamport id but those of hidden_kinds, newest first
+    pub async fn get_nostr_events_by_lamport_id(
+        &self,
+        lamport_id: &str,
+        hidden_kinds: &[i32],
+        offset: i64,
+        limit: i64,
+    ) -> AppResult<Vec<nostr_events::Model>> {
+        Ok(NostrEvents::find()
+            .filter(nostr_events::Column::LamportId.eq(lamport_id))
+            .filter(nostr_events::Column::Kind.is_not_in(hidden_kinds.iter().copied()))
+            .order_by_desc(nostr_events::Column::CreatedAt)
+            .offset(offset as u64)
+            .limit(limit as u64)
+            .all(self.conn.as_ref())
+            .await?)
+    }
+
+    //ids of the events published for a domain object, oldest first
+    pub async fn get_nostr_event_ids_by_object(&self, object_type: &str, object_id: &str) -> AppResult<Vec<String>> {
+        Ok(NostrEvents::find()
+            .filter(nostr_events::Column::ObjectType.eq(object_type))
+            .filter(nostr_events::Column::ObjectId.eq(object_id))
+            .order_by_asc(nostr_events::Column::CreatedAt)
+            .all(self.conn.as_ref())
+            .await?
+            .into_iter()
+            .map(|e| e.event_id)
+            .collect())
+    }
+
+    //one row per event and relay, every attempt updates it
+    pub async fn save_delivery(
+        &self,
+        event_id: &str,
+        relay: &str,
+        result: &Result<(), String>,
+        next_retry_at: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> AppResult<nostr_deliveries::Model> {
+        let (status, error) = match result {
+            Ok(()) => (consts::NOSTR_DELIVERY_OK, None),
+            Err(e) => (consts::NOSTR_DELIVERY_ERROR, Some(e.clone())),
+        };
+        let next_retry_at = next_retry_at.filter(|_| result.is_err()).map(|t| t.into());
+
+        let existing = NostrDeliveries::find()
+            .filter(nostr_deliveries::Column::EventId.eq(event_id))
+            .filter(nostr_deliveries::Column::Relay.eq(relay))
+            .one(self.conn.as_ref())
+            .await?;
+
+        match existing {
+            Some(delivery) => {
+                let attempts = delivery.attempts + 1;
+                let mut active = delivery.into_active_model();
+                active.status = Set(status.to_string());
+                active.error = Set(error);
+                active.attempts = Set(attempts);
+                active.next_retry_at = Set(next_retry_at);
+                active.updated_at = Set(chrono::Utc::now().into());
+                Ok(active.update(self.conn.as_ref()).await?)
+            }
+            None => {
+                let active = nostr_deliveries::ActiveModel {
+                    event_id: Set(event_id.to_string()),
+                    relay: Set(relay.to_string()),
+                    status: Set(status.to_string()),
+                    error: Set(error),
+                    attempts: Set(1),
+                    next_retry_at: Set(next_retry_at),
+                    updated_at: Set(chrono::Utc::now().into()),
+                    ..Default::default()
+                };
+                Ok(active.insert(self.conn.as_ref()).await?)
+            }
+        }
+    }
+
+    //failed deliveries whose backoff has passed
+    pub async fn get_due_deliveries(&self, limit: u64) -> AppResult<Vec<nostr_deliveries::Model>> {
+        let now: chrono::DateTime<chrono::FixedOffset> = chrono::Utc::now().into();
+
+        Ok(NostrDeliveries::find()
+            .filter(nostr_deliveries::Column::Status.eq(consts::NOSTR_DELIVERY_ERROR))
+            .filter(nostr_deliveries::Column::NextRetryAt.lte(now))
+            .order_by_asc(nostr_deliveries::Column::NextRetryAt)
+            .limit(limit)
+            .all(self.conn.as_ref())
+            .await?)
+    }
+
+    //stored events never sent to any relay, because queueing failed or the consumer stopped first
+    pub async fn get_unattempted_events(
+        &self,
+        stored_before: chrono::DateTime<chrono::Utc>,
+        limit: u64,
+    ) -> AppResult<Vec<nostr_events::Model>> {
+        Ok(NostrEvents::find()
+            .filter(Condition::all().not().add(has_deliveries()))
+            .filter(nostr_events::Column::CreatedAt.lt(stored_before))
+            .order_by_asc(nostr_events::Column::CreatedAt)
+            .limit(limit)
+            .all(self.conn.as_ref())
+            .await?)
+    }
+
+    //events missing from at least one relay or never sent, newest first, with all their deliveries
+    pub async fn get_undelivered_events(
+        &self,
+        offset: i64,
+        limit: i64,
+    ) -> AppResult<Vec<(nostr_events::Model, Vec<nostr_deliveries::Model>)>> {
+        let events = NostrEvents::find()
+            .filter(
+                Condition::any()
+                    .add(
+                        nostr_events::Column::EventId.in_subquery(
+                            Query::select()
+                                .column(nostr_deliveries::Column::EventId)
+                                .from(NostrDeliveries)
+                                .and_where(nostr_deliveries::Column::Status.eq(consts::NOSTR_DELIVERY_ERROR))
+                                .to_owned(),
+                        ),
+                    )
+                    .add(Condition::all().not().add(has_deliveries())),
+            )
+            .order_by_desc(nostr_events::Column::CreatedAt)
+            .offset(offset as u64)
+            .limit(limit as u64)
+            .all(self.conn.as_ref())
+            .await?;
+
+        let deliveries = NostrDeliveries::find()
+            .filter(nostr_deliveries::Column::EventId.is_in(events.iter().map(|e| e.event_id.clone())))
+            .order_by_asc(nostr_deliveries::Column::Relay)
+            .all(self.conn.as_ref())
+            .await?;
+
+        Ok(events
+            .into_iter()
+            .map(|e| {
+                let own = deliveries.iter().filter(|d| d.event_id == e.event_id).cloned().collect();
+                (e, own)
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,125 @@
+use crate::{
+    common::error::AppResult,
+    database::{
+        entities::{
+            nostr_ingested_events,
+            prelude::{NostrIngestedEvents, RemoteBindings},
+            remote_bindings,
+        },
+        Storage,
+    },
+};
+use sea_orm::*;
+
+impl Storage {
+    pub async fn is_nostr_event_ingested(&self, event_id: &str) -> AppResult<bool> {
+        Ok(NostrIngestedEvents::find()
+            .filter(nostr_ingested_events::Column::EventId.eq(event_id))
+            .one(self.conn.as_ref())
+            .await?
+            .is_some())
+    }
+
+    pub async fn record_ingested_event(
+        &self,
+        event_id: String,
+        pubkey: String,
+        kind: u16,
+        lamport_id: Option<String>,
+        outcome: String,
+        event_created_at: chrono::DateTime<chrono::Utc>,
+    ) -> AppResult<nostr_ingested_events::Model> {
+        let active = nostr_ingested_events::ActiveModel {
+            event_id: Set(event_id),
+            pubkey: Set(pubkey),
+            kind: Set(kind as i32),
+            lamport_id: Set(lamport_id),
+            outcome: Set(outcome),
+            event_created_at: Set(event_created_at.into()),
+            ingested_at: Set(chrono::Utc::now().into()),
+            ..Default::default()
+        };
+
+        Ok(active.insert(self.conn.as_ref()).await?)
+    }
+
+    //newest event seen, subscriptions resume from it after a restart
+    pub async fn get_last_ingested_at(&self) -> AppResult<Option<chrono::DateTime<chrono::Utc>>> {
+        Ok(NostrIngestedEvents::find()
+            .order_by_desc(nostr_ingested_events::Column::EventCreatedAt)
+            .one(self.conn.as_ref())
+            .await?
+            .map(|e| e.event_created_at.into()))
+    }
+
+    //a later binding of the same account on that server replaces the earlier one
+    pub async fn save_remote_binding(
+        &self,
+        server_pubkey: String,
+        event_id: String,
+        lamport_id: String,
+        provider: String,
+        account: String,
+        created_at: chrono::DateTime<chrono::Utc>,
+    ) -> AppResult<remote_bindings::Model> {
+        let existing = RemoteBindings::find()
+            .filter(remote_bindings::Column::ServerPubkey.eq(server_pubkey.as_str()))
+            .filter(remote_bindings::Column::Provider.eq(provider.as_str()))
+            .filter(remote_bindings::Column::Account.eq(account.as_str()))
+            .one(self.conn.as_ref())
+            .await?;
+
+        match existing {
+            Some(binding) => {
+                let mut active = binding.into_active_model();
+                active.event_id = Set(event_id);
+                active.lamport_id = Set(lamport_id);
+                active.created_at = Set(created_at.into());
+                Ok(active.update(self.conn.as_ref()).await?)
+            }
+            None => {
+                let active = remote_bindings::ActiveModel {
+                    server_pubkey: Set(server_pubkey),
+                    event_id: Set(event_id),
+                    lamport_id: Set(lamport_id),
+                    provider: Set(provider),
+                    account: Set(account),
+                    created_at: Set(created_at.into()),
+                    ..Default::default()
+                };
+                Ok(active.insert(self.conn.as_ref()).await?)
+            }
+        }
+    }
+
+    pub async fn delete_remote_binding(
+        &self,
+        server_pubkey: &str,
+        lamport_id: &str,
+        provider: &str,
+        account: &str,
+    ) -> AppResult<u64> {
+        let res = RemoteBindings::delete_many()
+            .filter(remote_bindings::Column::ServerPubkey.eq(server_pubkey))
+            .filter(remote_bindings::Column::LamportId.eq(lamport_id))
+            .filter(remote_bindings::Column::Provider.eq(provider))
+            .filter(remote_bindings::Column::Account.eq(account))
+            .exec(self.conn.as_ref())
+            .await?;
+
+        Ok(res.rows_affected)
+    }
+
+    //bindings of an account made on other lamport id servers
+    pub async fn get_remote_bindings_by_account(
+        &self,
+        provider: &str,
+        account: &str,
+    ) -> AppResult<Vec<remote_bindings::Model>> {
+        Ok(RemoteBindings::find()
+            .filter(remote_bindings::Column::Provider.eq(provider))
+            .filter(remote_bindings::Column::Account.eq(account))
+            .all(self.conn.as_ref())
+            .await?)
+    }
+}
@@ -0,0 +1,64 @@
+use crate::{
+    common::error::{AppError, AppResult},
+    database::{
+        entities::{prelude::Users, users},
+        Storage,
+    },
+};
+use sea_orm::*;
+
+impl Storage {
+    pub async fn get_user_by_nostr_pubkey(&self, pubkey: &str) -> AppResult<users::Model> {
+        match Users::find()
+            .filter(users::Column::NostrPubkey.eq(pubkey))
+            .one(self.conn.as_ref())
+            .await?
+        {
+            Some(user) => Ok(user),
+            None => Err(AppError::UserUnExisted(format!("User with nostr key {} has not existed", pubkey))),
+        }
+    }
+
+    //pubkey is hex, None unlinks the current key
+    pub async fn set_nostr_pubkey(&self, user: users::Model, pubkey: Option<String>) -> AppResult<users::Model> {
+        if let Some(key) = pubkey.as_deref() {
+            if let Ok(holder) = self.get_user_by_nostr_pubkey(key).await {
+                if holder.lamport_id != user.lamport_id {
+                    return Err(AppError::ConflictError("nostr key is linked to another lamport id".into()));
+                }
+            }
+        }
+
+        let mut active = user.into_active_model();
+        active.nostr_pubkey = Set(pubkey);
+        active.updated_at = Set(Some(chrono::Utc::now().into()));
+
+        Ok(active.update(self.conn.as_ref()).await?)
+    }
+
+    //linked nostr keys of lamport_ids, for p tags
+    pub async fn get_nostr_pubkeys(&self, lamport_ids: Vec<String>) -> AppResult<Vec<String>> {
+        if lamport_ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        Ok(Users::find()
+            .filter(users::Column::LamportId.is_in(lamport_ids))
+            .filter(users::Column::NostrPubkey.is_not_null())
+            .all(self.conn.as_ref())
+            .await?
+            .into_iter()
+            .filter_map(|u| u.nostr_pubkey)
+            .collect())
+    }
+
+    //current username to nostr key, for NIP-05
+    pub async fn get_nostr_pubkey_by_username(&self, username: &str) -> AppResult<Option<String>> {
+        Ok(Users::find()
+            .filter(users::Column::UserName.eq(username))
+            .filter(users::Column::DeletedAt.is_null())
+            .one(self.conn.as_ref())
+            .await?
+            .and_then(|u| u.nostr_pubkey))
+    }
+}
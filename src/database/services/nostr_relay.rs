@@ -0,0 +1,130 @@
+use super::nostr_delivery::signed_at;
+use crate::{
+    common::error::AppResult,
+    database::{
+        entities::{
+            nostr_events, nostr_relay_events,
+            prelude::{NostrEvents, NostrRelayEvents},
+        },
+        Storage,
+    },
+};
+use nostr_sdk::{Event, Filter, JsonUtil, Timestamp};
+use sea_orm::{sea_query::Expr, *};
+
+fn timestamp(t: Timestamp) -> chrono::DateTime<chrono::Utc> {
+    chrono::DateTime::from_timestamp(t.as_u64() as i64, 0).unwrap_or_default()
+}
+
+//the parts of a relay filter sql can answer, the caller still matches the signed event (search)
+fn filter_condition<C: ColumnTrait>(filter: &Filter, event_id: C, pubkey: C, kind: C, time: C) -> Condition {
+    let mut condition = Condition::all();
+    if let Some(ids) = filter.ids.as_ref().filter(|ids| !ids.is_empty()) {
+        condition = condition.add(event_id.is_in(ids.iter().map(|id| id.to_hex())));
+    }
+    if let Some(authors) = filter.authors.as_ref().filter(|authors| !authors.is_empty()) {
+        condition = condition.add(pubkey.is_in(authors.iter().map(|pk| pk.to_hex())));
+    }
+    if let Some(kinds) = filter.kinds.as_ref().filter(|kinds| !kinds.is_empty()) {
+        condition = condition.add(kind.is_in(kinds.iter().map(|k| k.as_u16() as i32)));
+    }
+    if let Some(since) = filter.since {
+        condition = condition.add(time.gte(timestamp(since)));
+    }
+    if let Some(until) = filter.until {
+        condition = condition.add(time.lte(timestamp(until)));
+    }
+
+    //#x filters match a tag named x with any of the values, tags are only kept in the signed json
+    for (tag, values) in filter.generic_tags.iter().filter(|(_, values)| !values.is_empty()) {
+        let any = values.iter().fold(Condition::any(), |any, value| {
+            any.add(Expr::cust_with_values(
+                "(raw::jsonb -> 'tags') @> $1::jsonb",
+                [serde_json::json!([[tag.to_string(), value]]).to_string()],
+            ))
+        });
+        condition = condition.add(any);
+    }
+
+    condition
+}
+
+impl Storage {
+    //our signed events matching filter, newest first
+    pub async fn get_nostr_events_page(
+        &self,
+        filter: &Filter,
+        offset: u64,
+        limit: u64,
+    ) -> AppResult<Vec<nostr_events::Model>> {
+        Ok(NostrEvents::find()
+            .filter(filter_condition(
+                filter,
+                nostr_events::Column::EventId,
+                nostr_events::Column::Pubkey,
+                nostr_events::Column::Kind,
+                nostr_events::Column::SignedAt,
+            ))
+            .order_by_desc(nostr_events::Column::SignedAt)
+            .order_by_desc(nostr_events::Column::Id)
+            .offset(offset)
+            .limit(limit)
+            .all(self.conn.as_ref())
+            .await?)
+    }
+
+    //events others wrote to the embedded relay matching filter, newest first
+    pub async fn get_relay_events_page(
+        &self,
+        filter: &Filter,
+        offset: u64,
+        limit: u64,
+    ) -> AppResult<Vec<nostr_relay_events::Model>> {
+        Ok(NostrRelayEvents::find()
+            .filter(filter_condition(
+                filter,
+                nostr_relay_events::Column::EventId,
+                nostr_relay_events::Column::Pubkey,
+                nostr_relay_events::Column::Kind,
+                nostr_relay_events::Column::CreatedAt,
+            ))
+            .order_by_desc(nostr_relay_events::Column::CreatedAt)
+            .order_by_desc(nostr_relay_events::Column::Id)
+            .offset(offset)
+            .limit(limit)
+            .all(self.conn.as_ref())
+            .await?)
+    }
+
+    pub async fn get_relay_event(&self, event_id: &str) -> AppResult<Option<nostr_relay_events::Model>> {
+        Ok(NostrRelayEvents::find()
+            .filter(nostr_relay_events::Column::EventId.eq(event_id))
+            .one(self.conn.as_ref())
+            .await?)
+    }
+
+    //a repeated event is kept once
+    pub async fn save_relay_event(&self, event: &Event) -> AppResult<()> {
+        let active = nostr_relay_events::ActiveModel {
+            event_id: Set(event.id.to_hex()),
+            pubkey: Set(event.pubkey.to_hex()),
+            kind: Set(event.kind.as_u16() as i32),
+            raw: Set(event.as_json()),
+            created_at: Set(signed_at(event).into()),
+            received_at: Set(chrono::Utc::now().into()),
+            ..Default::default()
+        };
+
+        NostrRelayEvents::insert(active)
+            .on_conflict(
+                sea_query::OnConflict::column(nostr_relay_events::Column::EventId)
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .do_nothing()
+            .exec(self.conn.as_ref())
+            .await?;
+
+        Ok(())
+    }
+}
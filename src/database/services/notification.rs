@@ -0,0 +1,121 @@
+use crate::{
+    common::error::AppResult,
+    database::{
+        entities::{
+            groups, notification_preferences,
+            prelude::{Groups, NotificationPreferences, Proposals, Users, Vote},
+            proposals, users, vote,
+        },
+        Storage,
+    },
+};
+use sea_orm::*;
+use std::collections::BTreeSet;
+
+impl Storage {
+    //preferences of user_id, None until the user saves them and everything is on
+    pub async fn get_notification_preferences(
+        &self,
+        user_id: &str,
+    ) -> AppResult<Option<notification_preferences::Model>> {
+        Ok(NotificationPreferences::find()
+            .filter(notification_preferences::Column::UserId.eq(user_id))
+            .one(self.conn.as_ref())
+            .await?)
+    }
+
+    pub async fn save_notification_preferences(
+        &self,
+        user_id: &str,
+        new_proposal: bool,
+        proposal_closing: bool,
+        invited: bool,
+        attested: bool,
+    ) -> AppResult<notification_preferences::Model> {
+        let mut active = match self.get_notification_preferences(user_id).await? {
+            Some(prefs) => prefs.into_active_model(),
+            None => notification_preferences::ActiveModel {
+                user_id: Set(user_id.to_string()),
+                ..Default::default()
+            },
+        };
+        active.new_proposal = Set(new_proposal);
+        active.proposal_closing = Set(proposal_closing);
+        active.invited = Set(invited);
+        active.attested = Set(attested);
+        active.updated_at = Set(chrono::Utc::now().into());
+
+        Ok(active.save(self.conn.as_ref()).await?.try_into_model()?)
+    }
+
+    //mark the email of user_id verified, unless it changed since the link was sent
+    pub async fn verify_user_email(&self, user_id: &str, email: &str) -> AppResult<Option<users::Model>> {
+        let user = self.get_user_by_uid(user_id).await?;
+        if user.email != email {
+            return Ok(None);
+        }
+
+        let mut active = user.into_active_model();
+        active.email_verified_at = Set(Some(chrono::Utc::now().into()));
+        active.updated_at = Set(Some(chrono::Utc::now().into()));
+
+        Ok(Some(active.update(self.conn.as_ref()).await?))
+    }
+
+    //lamport ids taking part in a group: its creator, proposal creators and voters
+    pub async fn get_group_member_ids(&self, group_id: &str) -> AppResult<Vec<String>> {
+        let mut members = BTreeSet::new();
+
+        if let Some(group) = Groups::find()
+            .filter(groups::Column::GroupId.eq(group_id))
+            .one(self.conn.as_ref())
+            .await?
+        {
+            members.insert(group.created_by);
+        }
+
+        let proposals = Proposals::find()
+            .filter(proposals::Column::GroupId.eq(group_id))
+            .all(self.conn.as_ref())
+            .await?;
+        let proposal_ids: Vec<String> = proposals.iter().map(|p| p.proposal_id.clone()).collect();
+        members.extend(proposals.into_iter().map(|p| p.created_by));
+
+        if !proposal_ids.is_empty() {
+            let voters = Vote::find()
+                .filter(vote::Column::ProposalId.is_in(proposal_ids))
+                .all(self.conn.as_ref())
+                .await?;
+            members.extend(voters.into_iter().map(|v| v.voter_id));
+        }
+
+        Ok(members.into_iter().collect())
+    }
+
+    //visible proposals ending between from and to
+    pub async fn get_proposals_ending_between(
+        &self,
+        from: chrono::DateTime<chrono::Utc>,
+        to: chrono::DateTime<chrono::Utc>,
+    ) -> AppResult<Vec<proposals::Model>> {
+        Ok(Proposals::find()
+            .filter(proposals::Column::Hidden.eq(false))
+            .filter(proposals::Column::EndTime.gt(from))
+            .filter(proposals::Column::EndTime.lte(to))
+            .all(self.conn.as_ref())
+            .await?)
+    }
+
+    //users with a verified email among lamport_ids
+    pub async fn get_users_with_verified_email(&self, lamport_ids: Vec<String>) -> AppResult<Vec<users::Model>> {
+        if lamport_ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        Ok(Users::find()
+            .filter(users::Column::LamportId.is_in(lamport_ids))
+            .filter(users::Column::EmailVerifiedAt.is_not_null())
+            .all(self.conn.as_ref())
+            .await?)
+    }
+}
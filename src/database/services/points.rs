@@ -34,6 +34,17 @@ impl Storage {
         Ok(point)
     }
 
+    //whether lamport_id was ever awarded point_type for description, rewards given once check it
+    pub async fn has_points(&self, user_uid: &str, point_type: &str, description: &str) -> AppResult<bool> {
+        Ok(Points::find()
+            .filter(points::Column::LamportId.eq(user_uid))
+            .filter(points::Column::PointType.eq(point_type))
+            .filter(points::Column::Description.eq(description))
+            .count(self.conn.as_ref())
+            .await?
+            > 0)
+    }
+
     pub async fn get_user_points(&self, user_uid: &str) -> AppResult<i64> {
         match Points::find()
             .filter(points::Column::LamportId.eq(user_uid))
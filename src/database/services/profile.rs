@@ -0,0 +1,262 @@
+use crate::{
+    common::error::{AppError, AppResult},
+    database::{
+        entities::{
+            events, groups,
+            prelude::{Events, Groups, PrivacySettings, Proposals, UsernameHistory, Vote},
+            privacy_settings, proposals, username_history, users, vote,
+        },
+        Storage,
+    },
+};
+use sea_orm::*;
+use serde::{Deserialize, Serialize};
+
+/// What a public profile shows besides its addresses, which are published in signed events anyway.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrivacyOptions {
+    pub show_bindings: bool,
+    pub show_attestations: bool,
+    pub show_points: bool,
+    pub show_groups: bool,
+    pub show_events: bool,
+}
+
+impl Default for PrivacyOptions {
+    fn default() -> Self {
+        Self {
+            show_bindings: true,
+            show_attestations: true,
+            show_points: true,
+            show_groups: true,
+            show_events: true,
+        }
+    }
+}
+
+impl From<privacy_settings::Model> for PrivacyOptions {
+    fn from(settings: privacy_settings::Model) -> Self {
+        Self {
+            show_bindings: settings.show_bindings,
+            show_attestations: settings.show_attestations,
+            show_points: settings.show_points,
+            show_groups: settings.show_groups,
+            show_events: settings.show_events,
+        }
+    }
+}
+
+impl Storage {
+    //only the given fields change, None keeps the current value
+    pub async fn update_profile(
+        &self,
+        user: users::Model,
+        name: Option<String>,
+        image: Option<String>,
+        bio: Option<String>,
+        email: Option<String>,
+    ) -> AppResult<users::Model> {
+        let mut active = user.into_active_model();
+        if let Some(name) = name {
+            active.name = Set(name);
+        }
+        if let Some(image) = image {
+            active.image = Set(image);
+        }
+        if let Some(bio) = bio {
+            active.bio = Set(bio);
+        }
+        if let Some(email) = email {
+            //a new address has to be verified again
+            if email != *active.email.as_ref() {
+                active.email_verified_at = Set(None);
+            }
+            active.email = Set(email);
+        }
+        active.updated_at = Set(Some(chrono::Utc::now().into()));
+
+        Ok(active.update(self.conn.as_ref()).await?)
+    }
+
+    //a username is taken by a current user or kept as the old handle of another lamport id
+    pub async fn is_username_taken(&self, username: &str, user_id: Option<&str>) -> AppResult<bool> {
+        if self.is_user_exists_by_username(username).await? {
+            return Ok(true);
+        }
+
+        Ok(match self.get_username_history_by_old_name(username).await? {
+            Some(history) => Some(history.user_id.as_str()) != user_id,
+            None => false,
+        })
+    }
+
+    pub async fn get_username_history_by_old_name(
+        &self,
+        username: &str,
+    ) -> AppResult<Option<username_history::Model>> {
+        Ok(UsernameHistory::find()
+            .filter(username_history::Column::OldUserName.eq(username))
+            .one(self.conn.as_ref())
+            .await?)
+    }
+
+    pub async fn get_username_history_by_user_id(&self, user_id: &str) -> AppResult<Vec<username_history::Model>> {
+        Ok(UsernameHistory::find()
+            .filter(username_history::Column::UserId.eq(user_id))
+            .order_by_desc(username_history::Column::ChangedAt)
+            .all(self.conn.as_ref())
+            .await?)
+    }
+
+    pub async fn get_last_username_change(&self, user_id: &str) -> AppResult<Option<username_history::Model>> {
+        Ok(UsernameHistory::find()
+            .filter(username_history::Column::UserId.eq(user_id))
+            .order_by_desc(username_history::Column::ChangedAt)
+            .one(self.conn.as_ref())
+            .await?)
+    }
+
+    //rename user and keep the old handle so it still redirects, sig is the signed change request
+    pub async fn change_username(
+        &self,
+        user: users::Model,
+        new_user_name: &str,
+        sig: &str,
+    ) -> AppResult<(users::Model, username_history::Model)> {
+        if self.is_username_taken(new_user_name, Some(user.lamport_id.as_str())).await? {
+            return Err(AppError::ConflictError("Name is already taken".to_string()));
+        }
+
+        //taking back one of its own old handles, it stops being a redirect
+        if let Some(reclaimed) = self.get_username_history_by_old_name(new_user_name).await? {
+            reclaimed.delete(self.conn.as_ref()).await?;
+        }
+
+        let history = username_history::ActiveModel {
+            user_id: Set(user.lamport_id.clone()),
+            old_user_name: Set(user.user_name.clone()),
+            new_user_name: Set(new_user_name.to_string()),
+            sig: Set(sig.to_string()),
+            changed_at: Set(chrono::Utc::now().into()),
+            ..Default::default()
+        }
+        .insert(self.conn.as_ref())
+        .await?;
+
+        let mut active = user.into_active_model();
+        active.user_name = Set(new_user_name.to_string());
+        active.updated_at = Set(Some(chrono::Utc::now().into()));
+
+        Ok((active.update(self.conn.as_ref()).await?, history))
+    }
+
+    //current user of a username, old handles resolve to whoever holds them now
+    pub async fn resolve_username(&self, username: &str) -> AppResult<(users::Model, Option<String>)> {
+        if self.is_user_exists_by_username(username).await? {
+            return Ok((self.get_user_by_username(username).await?, None));
+        }
+
+        match self.get_username_history_by_old_name(username).await? {
+            Some(history) => Ok((self.get_user_by_uid(history.user_id.as_str()).await?, Some(username.to_string()))),
+            None => Err(AppError::UserUnExisted(format!("User {} has not existed", username))),
+        }
+    }
+
+    //privacy of user_id, the defaults until it saves its own
+    pub async fn get_privacy_options(&self, user_id: &str) -> AppResult<PrivacyOptions> {
+        Ok(PrivacySettings::find()
+            .filter(privacy_settings::Column::UserId.eq(user_id))
+            .one(self.conn.as_ref())
+            .await?
+            .map(PrivacyOptions::from)
+            .unwrap_or_default())
+    }
+
+    pub async fn save_privacy_options(&self, user_id: &str, options: &PrivacyOptions) -> AppResult<PrivacyOptions> {
+        let mut active = match PrivacySettings::find()
+            .filter(privacy_settings::Column::UserId.eq(user_id))
+            .one(self.conn.as_ref())
+            .await?
+        {
+            Some(settings) => settings.into_active_model(),
+            None => privacy_settings::ActiveModel {
+                user_id: Set(user_id.to_string()),
+                ..Default::default()
+            },
+        };
+        active.show_bindings = Set(options.show_bindings);
+        active.show_attestations = Set(options.show_attestations);
+        active.show_points = Set(options.show_points);
+        active.show_groups = Set(options.show_groups);
+        active.show_events = Set(options.show_events);
+        active.updated_at = Set(chrono::Utc::now().into());
+
+        Ok(active.save(self.conn.as_ref()).await?.try_into_model()?.into())
+    }
+
+    //1 for the most points, lamport ids with equal points share a rank
+    pub async fn get_points_rank(&self, points: i64) -> AppResult<u64> {
+        let ahead = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                self.conn.get_database_backend(),
+                "SELECT COUNT(*) FROM (SELECT lamport_id FROM points GROUP BY lamport_id HAVING SUM(amounts) > $1) t",
+                [points.into()],
+            ))
+            .await?
+            .map(|row| row.try_get_by_index::<i64>(0))
+            .transpose()?
+            .unwrap_or(0);
+
+        Ok(ahead as u64 + 1)
+    }
+
+    //groups lamport_id created, proposed in or voted in
+    pub async fn get_groups_by_member(&self, lamport_id: &str) -> AppResult<Vec<groups::Model>> {
+        let mut group_ids: Vec<String> = Proposals::find()
+            .filter(proposals::Column::CreatedBy.eq(lamport_id))
+            .all(self.conn.as_ref())
+            .await?
+            .into_iter()
+            .map(|p| p.group_id)
+            .collect();
+
+        let voted: Vec<String> = Vote::find()
+            .filter(vote::Column::VoterId.eq(lamport_id))
+            .all(self.conn.as_ref())
+            .await?
+            .into_iter()
+            .map(|v| v.proposal_id)
+            .collect();
+        if !voted.is_empty() {
+            group_ids.extend(
+                Proposals::find()
+                    .filter(proposals::Column::ProposalId.is_in(voted))
+                    .all(self.conn.as_ref())
+                    .await?
+                    .into_iter()
+                    .map(|p| p.group_id),
+            );
+        }
+
+        Ok(Groups::find()
+            .filter(
+                Condition::any()
+                    .add(groups::Column::CreatedBy.eq(lamport_id))
+                    .add(groups::Column::GroupId.is_in(group_ids)),
+            )
+            .order_by_asc(groups::Column::CreatedAt)
+            .all(self.conn.as_ref())
+            .await?)
+    }
+
+    //newest events of lamport_id first
+    pub async fn get_recent_events_by_lamport_id(&self, lamport_id: &str, limit: u64) -> AppResult<Vec<events::Model>> {
+        Ok(Events::find()
+            .filter(events::Column::LamportId.eq(lamport_id))
+            .order_by_desc(events::Column::CreatedAt)
+            .limit(limit)
+            .all(self.conn.as_ref())
+            .await?)
+    }
+}
@@ -1,5 +1,5 @@
 use crate::{
-    common::error::{AppResult,AppError},
+    common::{consts, error::{AppResult,AppError}},
     database::{
         entities::{prelude::Proposals, proposals},
         Storage,
@@ -10,6 +10,7 @@ use uuid::Uuid;
 
 impl Storage {
     //create create_proposal function
+    #[allow(clippy::too_many_arguments)]
     pub async fn create_proposal(
         &self,
         title: String,
@@ -42,6 +43,7 @@ impl Storage {
     //count proposals by creator
     pub async fn count_proposals_by_creator(&self, creator: &str) -> AppResult<u64> {
         Ok(Proposals::find()
+            .filter(proposals::Column::Hidden.eq(false))
             .filter(proposals::Column::CreatedBy.contains(creator))
             .count(self.conn.as_ref())
             .await?)
@@ -50,6 +52,7 @@ impl Storage {
     //get proposals list by creator offset and limit
     pub async fn get_proposals_list_by_creator(&self, creator: String, offset: i64, limit: i64) -> AppResult<Vec<proposals::Model>> {
         Ok(Proposals::find()
+            .filter(proposals::Column::Hidden.eq(false))
             .filter(proposals::Column::CreatedBy.contains(creator))
             .order_by_asc(proposals::Column::CreatedAt)
             .offset(offset as u64)
@@ -61,6 +64,7 @@ impl Storage {
     //count proposals by group_id
     pub async fn count_proposals_by_groupid(&self, group_id: &str) -> AppResult<u64> {
         Ok(Proposals::find()
+            .filter(proposals::Column::Hidden.eq(false))
             .filter(proposals::Column::GroupId.contains(group_id))
             .count(self.conn.as_ref())
             .await?)
@@ -68,6 +72,7 @@ impl Storage {
 
     pub async fn get_proposals_list_by_groupid(&self, group_id: &str, offset: i64, limit: i64) -> AppResult<Vec<proposals::Model>> {
         Ok(Proposals::find()
+            .filter(proposals::Column::Hidden.eq(false))
             .filter(proposals::Column::GroupId.contains(group_id))
             .order_by_asc(proposals::Column::CreatedAt)
             .offset(offset as u64)
@@ -78,6 +83,7 @@ impl Storage {
 
     pub async fn get_proposals_list_with_votes_by_groupid(&self, group_id: &str, offset: i64, limit: i64) -> AppResult<Vec<(proposals::Model, u64)>> {
         let proposals = Proposals::find()
+            .filter(proposals::Column::Hidden.eq(false))
             .filter(proposals::Column::GroupId.contains(group_id))
             .order_by_asc(proposals::Column::CreatedAt)
             .offset(offset as u64)
@@ -106,6 +112,7 @@ impl Storage {
         status: Option<String>,
     ) -> AppResult<Vec<(proposals::Model, u64)>> {
         let mut query = Proposals::find()
+            .filter(proposals::Column::Hidden.eq(false))
             .filter(proposals::Column::GroupId.contains(group_id))
             .offset(offset as u64)
             .limit(limit as u64);
@@ -139,6 +146,7 @@ impl Storage {
 
     pub async fn get_proposal_by_proposal_id(&self, proposal_id: &str) -> AppResult<proposals::Model> {
         match Proposals::find()
+            .filter(proposals::Column::Hidden.eq(false))
             .filter(proposals::Column::ProposalId.eq(proposal_id))
             .one(self.conn.as_ref())
             .await? {
@@ -150,6 +158,37 @@ impl Storage {
             }
     }
 
-}
+    //hide or restore a proposal, hidden proposals are left out of lists, detail and voting
+    pub async fn set_proposal_hidden(
+        &self,
+        proposal_id: &str,
+        hidden: bool,
+        reason: Option<&str>,
+        operator: &str,
+    ) -> AppResult<proposals::Model> {
+        let proposal = match Proposals::find()
+            .filter(proposals::Column::ProposalId.eq(proposal_id))
+            .one(self.conn.as_ref())
+            .await? {
+                Some(proposal) => proposal,
+                None => return Err(AppError::CustomError(format!(
+                            "Proposal {} has not existed",
+                            proposal_id
+                ))),
+            };
 
+        let mut active = proposal.into_active_model();
+        active.hidden = Set(hidden);
+        active.updated_at = Set(chrono::Utc::now().into());
+        let proposal = active.update(self.conn.as_ref()).await?;
 
+        let action = if hidden {
+            consts::ADMIN_ACTION_HIDE_PROPOSAL
+        } else {
+            consts::ADMIN_ACTION_UNHIDE_PROPOSAL
+        };
+        self.create_admin_audit(operator, action, proposal_id, reason).await?;
+
+        Ok(proposal)
+    }
+}
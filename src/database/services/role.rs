@@ -0,0 +1,107 @@
+use crate::{
+    common::{
+        consts,
+        error::{AppError, AppResult},
+        rbac::{Permission, Role},
+    },
+    database::{
+        entities::{prelude::UserRoles, user_roles},
+        Storage,
+    },
+};
+use sea_orm::*;
+use std::str::FromStr;
+
+impl Storage {
+    pub async fn grant_role(&self, lamport_id: &str, role: Role, operator: &str) -> AppResult<user_roles::Model> {
+        //the grantee has to be a registered user
+        self.get_user_by_uid(lamport_id).await?;
+
+        if self.get_user_role(lamport_id, role).await?.is_some() {
+            return Err(AppError::ConflictError(format!(
+                "{} already has role {}",
+                lamport_id, role
+            )));
+        }
+
+        let granted = user_roles::ActiveModel {
+            lamport_id: Set(lamport_id.to_string()),
+            role: Set(role.as_str().to_string()),
+            granted_by: Set(operator.to_string()),
+            created_at: Set(chrono::Utc::now().into()),
+            ..Default::default()
+        }
+        .insert(self.conn.as_ref())
+        .await?;
+
+        self.create_admin_audit(operator, consts::ADMIN_ACTION_GRANT_ROLE, lamport_id, Some(role.as_str()))
+            .await?;
+
+        Ok(granted)
+    }
+
+    pub async fn revoke_role(&self, lamport_id: &str, role: Role, operator: &str) -> AppResult<user_roles::Model> {
+        let granted = match self.get_user_role(lamport_id, role).await? {
+            Some(r) => r,
+            None => {
+                return Err(AppError::CustomError(format!(
+                    "{} does not have role {}",
+                    lamport_id, role
+                )))
+            }
+        };
+
+        granted.clone().delete(self.conn.as_ref()).await?;
+
+        self.create_admin_audit(operator, consts::ADMIN_ACTION_REVOKE_ROLE, lamport_id, Some(role.as_str()))
+            .await?;
+
+        Ok(granted)
+    }
+
+    async fn get_user_role(&self, lamport_id: &str, role: Role) -> AppResult<Option<user_roles::Model>> {
+        Ok(UserRoles::find()
+            .filter(user_roles::Column::LamportId.eq(lamport_id))
+            .filter(user_roles::Column::Role.eq(role.as_str()))
+            .one(self.conn.as_ref())
+            .await?)
+    }
+
+    pub async fn get_roles_by_uid(&self, lamport_id: &str) -> AppResult<Vec<Role>> {
+        let granted = UserRoles::find()
+            .filter(user_roles::Column::LamportId.eq(lamport_id))
+            .all(self.conn.as_ref())
+            .await?;
+
+        //rows with a role this build does not know are ignored
+        Ok(granted.iter().filter_map(|r| Role::from_str(&r.role).ok()).collect())
+    }
+
+    //role assignments, optionally only those of one role
+    pub async fn get_role_assignments(&self, role: Option<Role>) -> AppResult<Vec<user_roles::Model>> {
+        let mut query = UserRoles::find().order_by_asc(user_roles::Column::CreatedAt);
+        if let Some(role) = role {
+            query = query.filter(user_roles::Column::Role.eq(role.as_str()));
+        }
+
+        Ok(query.all(self.conn.as_ref()).await?)
+    }
+
+    //lamport ids in `bootstrap_admins` are admins without a row in user_roles
+    pub async fn has_permission(
+        &self,
+        lamport_id: &str,
+        permission: Permission,
+        bootstrap_admins: &[String],
+    ) -> AppResult<bool> {
+        if bootstrap_admins.iter().any(|a| a == lamport_id) {
+            return Ok(true);
+        }
+
+        Ok(self
+            .get_roles_by_uid(lamport_id)
+            .await?
+            .iter()
+            .any(|r| r.grants(permission)))
+    }
+}
@@ -0,0 +1,91 @@
+use crate::{
+    common::{
+        consts,
+        error::{AppError, AppResult},
+        rbac::SanctionKind,
+    },
+    database::{
+        entities::{prelude::UserSanctions, user_sanctions},
+        Storage,
+    },
+};
+use sea_orm::*;
+
+impl Storage {
+    //ban or suspend a lamport id, a newer sanction replaces the current one
+    pub async fn sanction_user(
+        &self,
+        lamport_id: &str,
+        kind: SanctionKind,
+        reason: &str,
+        expires_at: Option<chrono::DateTime<chrono::Utc>>,
+        operator: &str,
+    ) -> AppResult<user_sanctions::Model> {
+        self.get_user_by_uid(lamport_id).await?;
+
+        if let Some(current) = self.get_user_sanction(lamport_id).await? {
+            current.delete(self.conn.as_ref()).await?;
+        }
+
+        let sanction = user_sanctions::ActiveModel {
+            lamport_id: Set(lamport_id.to_string()),
+            kind: Set(kind.as_str().to_string()),
+            reason: Set(reason.to_string()),
+            expires_at: Set(expires_at.map(|t| t.into())),
+            created_by: Set(operator.to_string()),
+            created_at: Set(chrono::Utc::now().into()),
+            ..Default::default()
+        }
+        .insert(self.conn.as_ref())
+        .await?;
+
+        let action = match kind {
+            SanctionKind::Ban => consts::ADMIN_ACTION_BAN,
+            SanctionKind::Suspend => consts::ADMIN_ACTION_SUSPEND,
+        };
+        self.create_admin_audit(operator, action, lamport_id, Some(reason)).await?;
+
+        Ok(sanction)
+    }
+
+    pub async fn lift_sanction(&self, lamport_id: &str, operator: &str) -> AppResult<user_sanctions::Model> {
+        let sanction = match self.get_user_sanction(lamport_id).await? {
+            Some(s) => s,
+            None => return Err(AppError::CustomError(format!("{} is not sanctioned", lamport_id))),
+        };
+
+        sanction.clone().delete(self.conn.as_ref()).await?;
+
+        self.create_admin_audit(operator, consts::ADMIN_ACTION_LIFT_SANCTION, lamport_id, Some(sanction.kind.as_str()))
+            .await?;
+
+        Ok(sanction)
+    }
+
+    async fn get_user_sanction(&self, lamport_id: &str) -> AppResult<Option<user_sanctions::Model>> {
+        Ok(UserSanctions::find()
+            .filter(user_sanctions::Column::LamportId.eq(lamport_id))
+            .one(self.conn.as_ref())
+            .await?)
+    }
+
+    //sanction still in force, an ended suspension counts as none
+    pub async fn get_active_sanction(&self, lamport_id: &str) -> AppResult<Option<user_sanctions::Model>> {
+        Ok(self
+            .get_user_sanction(lamport_id)
+            .await?
+            .filter(|s| match s.expires_at {
+                Some(t) => t > chrono::Utc::now(),
+                None => true,
+            }))
+    }
+
+    pub async fn get_sanctions(&self, offset: i64, limit: i64) -> AppResult<Vec<user_sanctions::Model>> {
+        Ok(UserSanctions::find()
+            .order_by_desc(user_sanctions::Column::CreatedAt)
+            .offset(offset as u64)
+            .limit(limit as u64)
+            .all(self.conn.as_ref())
+            .await?)
+    }
+}
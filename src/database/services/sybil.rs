@@ -0,0 +1,126 @@
+use crate::{
+    common::{
+        consts,
+        error::{AppError, AppResult},
+    },
+    database::{
+        entities::{
+            attestations,
+            prelude::{Attestations, SocialBindings, Users},
+            social_bindings, users,
+        },
+        Storage,
+    },
+    helpers::sybil::{HumanityScore, SybilSignals},
+};
+use sea_orm::*;
+
+//votes on the same proposal and choice this close together count as mirrored
+const MIRRORED_VOTE_SECONDS: i64 = 30;
+//registrations from one ip within this many hours of each other are counted together
+const REGISTRATION_WINDOW_HOURS: i64 = 24;
+const UNKNOWN_IP: &str = "unknown";
+
+impl Storage {
+    pub async fn get_sybil_signals(&self, lamport_id: &str) -> AppResult<SybilSignals> {
+        let user = self.get_user_by_uid(lamport_id).await?;
+        let now = chrono::Utc::now();
+
+        let twitter_bound_days = SocialBindings::find()
+            .filter(social_bindings::Column::UserId.eq(lamport_id))
+            .filter(social_bindings::Column::Provider.eq(consts::PROVIDER_TWITTER))
+            .one(self.conn.as_ref())
+            .await?
+            .map(|b| (now - b.created_at.with_timezone(&chrono::Utc)).num_days());
+
+        let attestations = Attestations::find()
+            .filter(attestations::Column::Subject.eq(lamport_id))
+            .filter(attestations::Column::Revoked.eq(false))
+            .count(self.conn.as_ref())
+            .await?;
+
+        let inviter_fanout = match user.invited_by.as_deref() {
+            Some(code) => Some(self.count_invited_users_by_code(code).await?),
+            None => None,
+        };
+        let invitees = self.count_invited_users_by_code(&user.invite_code).await?;
+        let invitees_verified = Users::find()
+            .filter(users::Column::InvitedBy.eq(Some(user.invite_code.clone())))
+            .filter(users::Column::Verified.eq(true))
+            .count(self.conn.as_ref())
+            .await?;
+
+        let wallets = 1 + self.count_linked_addresses_by_uid(lamport_id).await?;
+        let votes = self.count_votes_by_voter_id(lamport_id).await?;
+
+        let created_at = user.created_at.map(|t| t.with_timezone(&chrono::Utc));
+
+        Ok(SybilSignals {
+            twitter_bound_days,
+            verified: user.verified,
+            attestations,
+            inviter_fanout,
+            invitees,
+            invitees_verified,
+            wallets,
+            votes,
+            mirrored_votes: self.count_mirrored_votes(lamport_id).await?,
+            same_ip_registrations: self.count_same_ip_registrations(&user).await?,
+            account_age_days: created_at.map_or(0, |t| (now - t).num_days()),
+        })
+    }
+
+    pub async fn get_humanity_score(&self, lamport_id: &str) -> AppResult<HumanityScore> {
+        Ok(self.get_sybil_signals(lamport_id).await?.score())
+    }
+
+    //score of lamport_id, an error when it is below min
+    pub async fn require_humanity_score(&self, lamport_id: &str, min: u32) -> AppResult<HumanityScore> {
+        let score = self.get_humanity_score(lamport_id).await?;
+        if score.score < min {
+            return Err(AppError::Forbidden(format!(
+                "humanity score {} is below the required {}",
+                score.score, min
+            )));
+        }
+
+        Ok(score)
+    }
+
+    //votes of voter_id with a twin from another voter, same proposal and choice cast within seconds
+    async fn count_mirrored_votes(&self, voter_id: &str) -> AppResult<u64> {
+        let row = self
+            .conn
+            .query_one(Statement::from_sql_and_values(
+                self.conn.get_database_backend(),
+                r#"SELECT COUNT(DISTINCT v.id) FROM vote v INNER JOIN vote o
+                ON o.proposal_id = v.proposal_id AND o.choice = v.choice AND o.voter_id <> v.voter_id
+                AND ABS(EXTRACT(EPOCH FROM (o.created_at - v.created_at))) <= $2
+                WHERE v.voter_id = $1"#,
+                [voter_id.into(), MIRRORED_VOTE_SECONDS.into()],
+            ))
+            .await?;
+
+        Ok(match row {
+            Some(r) => r.try_get_by::<i64, _>(0)? as u64,
+            None => 0,
+        })
+    }
+
+    async fn count_same_ip_registrations(&self, user: &users::Model) -> AppResult<Option<u64>> {
+        let (ip, created_at) = match (user.register_ip.as_deref(), user.created_at) {
+            (Some(ip), Some(created_at)) if ip != UNKNOWN_IP => (ip, created_at),
+            _ => return Ok(None),
+        };
+        let window = chrono::Duration::hours(REGISTRATION_WINDOW_HOURS);
+
+        Ok(Some(
+            Users::find()
+                .filter(users::Column::RegisterIp.eq(ip))
+                .filter(users::Column::LamportId.ne(user.lamport_id.as_str()))
+                .filter(users::Column::CreatedAt.between(created_at - window, created_at + window))
+                .count(self.conn.as_ref())
+                .await?,
+        ))
+    }
+}
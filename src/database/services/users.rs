@@ -2,7 +2,7 @@ use crate::{
     common::error::{AppError, AppResult},
     database::{
         entities::{prelude::Users, users},
-        Storage,
+        DbTxn, Storage,
     },
 };
 use sea_orm::prelude::Expr;
@@ -20,7 +20,31 @@ impl Storage {
             ))?
             .to_string();
 
-        let user_uid =  self.get_current_lamport_id().await.unwrap().to_string();
+        //a handle held now or in the past by a lamport id is not handed to a new account
+        if let ActiveValue::Set(user_name) = &active_user.user_name {
+            if !user_name.is_empty() && self.is_username_taken(user_name, None).await? {
+                return Err(AppError::ConflictError(format!("Name {} is already taken", user_name)));
+            }
+        }
+
+        let user_address: String = active_user
+            .address
+            .try_as_ref()
+            .ok_or(AppError::CustomError(
+                "cannot get address from active user".into(),
+            ))?
+            .to_string();
+
+        //the number, the user and the counter are written together, the counter row stays locked until commit
+        let txn = DbTxn::new(self.conn.as_ref()).await?;
+        self.lock_lamport_id(&txn).await?;
+
+        //an admin assigned number takes precedence over the allocator
+        let assignment = self.get_lamport_id_assignment_by_address(&user_address).await?;
+        let user_uid = match &assignment {
+            Some(a) => a.lamport_value.to_string(),
+            None => self.increase_lamport_id(&txn).await?.to_string(),
+        };
 
         if self
             .is_user_exists(&user_uid, &user_invite_code)
@@ -34,8 +58,11 @@ impl Storage {
 
         active_user.lamport_id = Set(user_uid.clone());
 
-        let created_user = active_user.insert(self.conn.as_ref()).await?;
-        self.increase_lamport_id().await.unwrap();
+        let created_user = active_user.insert(&txn.0).await?;
+        if let Some(a) = assignment {
+            self.claim_lamport_id_assignment(&txn, a).await?;
+        }
+        txn.commit_transaction().await?;
 
         Ok(created_user)
     }
@@ -88,6 +115,18 @@ impl Storage {
         }
     }
 
+    //the account an X login signed up, the X id stays when the handle is renamed on either side
+    pub async fn get_user_by_x_id(&self, x_id: &str) -> AppResult<Option<users::Model>> {
+        if x_id.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Users::find()
+            .filter(users::Column::XId.eq(x_id))
+            .filter(users::Column::DeletedAt.is_null())
+            .one(self.conn.as_ref())
+            .await?)
+    }
 
     pub async fn is_user_exists_by_code(&self, code: &str) -> AppResult<bool> {
         let existing = Users::find()
@@ -165,7 +204,7 @@ impl Storage {
         Ok(Users::find().count(self.conn.as_ref()).await?)
     }
 
-    //get user by address
+    //get user by primary or linked address
     pub async fn get_user_by_address(&self, address: &str) -> AppResult<users::Model> {
         match Users::find()
             .filter(users::Column::Address.eq(address))
@@ -173,30 +212,14 @@ impl Storage {
             .await? 
         {
             Some(user) => Ok(user),
-            None => Err(AppError::UserUnExisted(format!(
-                "User {} has not existed",
-                address
-            ))),
-        }
-    }
-
-    //update user's verifyed
-    pub async fn update_user(&self, address: &str, verifier: &str) -> AppResult<users::Model> {
-        if let Some(mut user) = Users::find()
-            .filter(users::Column::Address.eq(address))
-                .one(self.conn.as_ref())
-                .await?
-                .map(|u| u.into_active_model()) {
-                    user.verified = Set(true);
-                    user.verified_by = Set(Some(verifier.to_string()));
-                    Ok(user.update(self.conn.as_ref()).await?)
-        } else {
-            Err(AppError::UserUnExisted(format!(
-                "User {} has not existed",
-                address
-            )))
+            None => match self.get_linked_address(address).await? {
+                Some(linked) => self.get_user_by_uid(linked.lamport_id.as_str()).await,
+                None => Err(AppError::UserUnExisted(format!(
+                    "User {} has not existed",
+                    address
+                ))),
+            },
         }
-
     }
 
     pub async fn is_user_exists_by_address(&self, address: &str) -> AppResult<bool> {
@@ -205,7 +228,7 @@ impl Storage {
             .one(self.conn.as_ref())
             .await?;
 
-        Ok(user.is_some())
+        Ok(user.is_some() || self.get_linked_address(address).await?.is_some())
     }
 
 }
@@ -48,6 +48,20 @@ impl Storage {
         Ok(count)
     }
 
+    //sum of vote weights by proposal_id and choice, equals count * 100 unless votes are weighted
+    pub async fn sum_vote_weight_by_proposal_id_and_choice(&self, proposal_id: &str, choice: &str) -> AppResult<i64> {
+        let weight = Vote::find()
+            .select_only()
+            .column_as(vote::Column::Weight.sum(), "weight")
+            .filter(vote::Column::ProposalId.eq(proposal_id))
+            .filter(vote::Column::Choice.eq(choice))
+            .into_tuple::<Option<i64>>()
+            .one(self.conn.as_ref())
+            .await?;
+
+        Ok(weight.flatten().unwrap_or(0))
+    }
+
     //count votes by group_id, group_id has many proposals, proposals has many votes
     pub async fn count_votes_by_group_id(&self, group_id: &str) -> AppResult<i64> {
         let count = self.conn.query_one(Statement::from_string(
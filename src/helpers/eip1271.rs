@@ -0,0 +1,189 @@
+use super::{eip191::parse_signature, eip712::parse_address};
+use crate::common::{
+    config::Eip1271Config,
+    error::{AppError, AppResult},
+};
+use alloy_primitives::{Address, Bytes, B256};
+use alloy_sol_types::{sol, SolCall};
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Duration;
+
+sol! {
+    function isValidSignature(bytes32 hash, bytes signature) external view returns (bytes4 magicValue);
+}
+
+//bytes4(keccak256("isValidSignature(bytes32,bytes)"))
+pub const EIP1271_MAGIC_VALUE: [u8; 4] = [0x16, 0x26, 0xba, 0x7e];
+
+/// Asks a smart-contract wallet whether it accepts `signature` for `hash` (EIP-1271).
+#[async_trait]
+pub trait SignatureChecker: Send + Sync {
+    async fn is_valid_signature(&self, wallet: Address, hash: B256, signature: &[u8]) -> AppResult<bool>;
+}
+
+/// EIP-1271 checker backed by an `eth_call` against a JSON-RPC node.
+pub struct RpcSignatureChecker {
+    rpc_url: String,
+    client: reqwest::Client,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcResponse {
+    result: Option<String>,
+    error: Option<serde_json::Value>,
+}
+
+impl RpcSignatureChecker {
+    pub fn new(rpc_url: &str, timeout: u64) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(timeout))
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            rpc_url: rpc_url.to_string(),
+            client,
+        }
+    }
+}
+
+#[async_trait]
+impl SignatureChecker for RpcSignatureChecker {
+    async fn is_valid_signature(&self, wallet: Address, hash: B256, signature: &[u8]) -> AppResult<bool> {
+        let call = isValidSignatureCall {
+            hash,
+            signature: Bytes::copy_from_slice(signature),
+        };
+
+        let body = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "eth_call",
+            "params": [
+                {
+                    "to": wallet.to_string(),
+                    "data": format!("0x{}", hex::encode(call.abi_encode())),
+                },
+                "latest"
+            ]
+        });
+
+        let resp: RpcResponse = self
+            .client
+            .post(&self.rpc_url)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| AppError::RequestError(format!("eip1271 rpc request error: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| AppError::RequestError(format!("eip1271 rpc response error: {}", e)))?;
+
+        //a revert means the wallet rejected the signature
+        if let Some(err) = resp.error {
+            tracing::warn!("eip1271 call to {} reverted: {:?}", wallet, err);
+            return Ok(false);
+        }
+
+        //an EOA has no code and returns empty data
+        let data = match resp.result {
+            Some(r) => hex::decode(r.trim_start_matches("0x")).unwrap_or_default(),
+            None => return Ok(false),
+        };
+
+        Ok(data.len() >= 4 && data[..4] == EIP1271_MAGIC_VALUE)
+    }
+}
+
+pub fn signature_checker(config: &Eip1271Config) -> Option<Arc<dyn SignatureChecker>> {
+    config
+        .rpc_url
+        .as_ref()
+        .filter(|url| !url.is_empty())
+        .map(|url| Arc::new(RpcSignatureChecker::new(url, config.timeout)) as Arc<dyn SignatureChecker>)
+}
+
+//recover an EOA first and fall back to EIP-1271 for contract wallets
+pub async fn verify_hash_signature(
+    checker: Option<&dyn SignatureChecker>,
+    hash: B256,
+    sig: &str,
+    expect_address: &str,
+) -> AppResult<bool> {
+    if let Ok(signature) = parse_signature(sig) {
+        if let Ok(recovered_address) = signature.recover_address_from_prehash(&hash) {
+            if recovered_address.to_string() == *expect_address {
+                return Ok(true);
+            }
+        }
+    }
+
+    let checker = match checker {
+        Some(c) => c,
+        None => return Ok(false),
+    };
+
+    //contract wallet signatures are not limited to 65 bytes
+    let signature = match hex::decode(sig.trim_start_matches("0x")) {
+        Ok(s) => s,
+        Err(_) => return Err(AppError::InvalidSignature),
+    };
+
+    checker
+        .is_valid_signature(parse_address(expect_address)?, hash, &signature)
+        .await
+}
+
+#[cfg(test)]
+#[derive(Default)]
+pub struct MockSignatureChecker {
+    approved: Vec<(Address, B256, Vec<u8>)>,
+}
+
+#[cfg(test)]
+impl MockSignatureChecker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn approve(mut self, wallet: Address, hash: B256, signature: &[u8]) -> Self {
+        self.approved.push((wallet, hash, signature.to_vec()));
+        self
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl SignatureChecker for MockSignatureChecker {
+    async fn is_valid_signature(&self, wallet: Address, hash: B256, signature: &[u8]) -> AppResult<bool> {
+        Ok(self
+            .approved
+            .iter()
+            .any(|(w, h, s)| *w == wallet && *h == hash && s.as_slice() == signature))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, eip191_hash_message};
+
+    #[tokio::test]
+    async fn test_eip1271_contract_wallet() {
+        let safe = address!("5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed");
+        let hash = eip191_hash_message("lamport id");
+        //two concatenated owner signatures, as a 2-of-n Safe would produce
+        let signature = [0x11u8; 130];
+        let sig = format!("0x{}", hex::encode(signature));
+
+        let checker = MockSignatureChecker::new().approve(safe, hash, &signature);
+
+        assert!(verify_hash_signature(Some(&checker), hash, &sig, &safe.to_string()).await.unwrap());
+        assert!(!verify_hash_signature(None, hash, &sig, &safe.to_string()).await.unwrap());
+
+        let other = eip191_hash_message("other");
+        assert!(!verify_hash_signature(Some(&checker), other, &sig, &safe.to_string()).await.unwrap());
+    }
+}
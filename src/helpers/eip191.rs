@@ -1,11 +1,15 @@
-use alloy_primitives::{aliases::B256, keccak256, PrimitiveSignature};
+use alloy_primitives::{aliases::B256, eip191_hash_message, keccak256, PrimitiveSignature};
 use serde::{Deserialize, Serialize};
 use crate::common::error::{AppResult,AppError};
 use hex::FromHex;
 
-pub fn verify_signature(msg: impl Serialize, sig: &str, expect_address: &str) -> AppResult<bool> {
-    let messgae = serde_json::to_string(&msg)?;
+//hash that personal_sign produces for the json encoded msg
+pub fn hash_message(msg: impl Serialize) -> AppResult<B256> {
+    Ok(eip191_hash_message(serde_json::to_string(&msg)?))
+}
 
+//parse a 0x prefixed 65 bytes r|s|v hex signature
+pub fn parse_signature(sig: &str) -> AppResult<PrimitiveSignature> {
     if sig.len() != 132 {
         return Err(AppError::CustomError("Invalid signature length!".to_string()));
     }
@@ -13,18 +17,9 @@ pub fn verify_signature(msg: impl Serialize, sig: &str, expect_address: &str) ->
     let r = alloy_primitives::FixedBytes(<[u8; 32]>::from_hex(&sig[2..66]).map_err(|_| AppError::InvalidSignature)?);
     let s = alloy_primitives::FixedBytes(<[u8; 32]>::from_hex(&sig[66..130]).map_err(|_| AppError::InvalidSignature)?);
     let v = &sig[130..132] != "1b";
-    
-    let signature = PrimitiveSignature::from_scalars_and_parity(r, s, v);
-
-    match signature.recover_address_from_msg(messgae) {
-        Ok(recovered_address) => {
-            Ok(recovered_address.to_string() == *expect_address)
-        },
-        Err(_) => Ok(false),
-    }
-}
-
 
+    Ok(PrimitiveSignature::from_scalars_and_parity(r, s, v))
+}
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RegisterMessage {
@@ -0,0 +1,130 @@
+use crate::common::{
+    config::Eip712Config,
+    error::{AppError, AppResult},
+};
+use super::{
+    eip1271::{verify_hash_signature, SignatureChecker},
+    eip191::hash_message,
+};
+use alloy_primitives::{Address, U256};
+use alloy_sol_types::{sol, Eip712Domain, SolStruct};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::str::FromStr;
+
+sol! {
+    #[derive(Debug)]
+    struct Register {
+        string user_name;
+        string email;
+        string image;
+        address wallet;
+        string nonce;
+    }
+
+    #[derive(Debug)]
+    struct Login {
+        string content;
+        address wallet;
+        string nonce;
+    }
+
+    #[derive(Debug)]
+    struct CreateProposal {
+        string title;
+        string description;
+        string[] options;
+        string group_id;
+        string end_time;
+    }
+
+    #[derive(Debug)]
+    struct CastVote {
+        string proposal_id;
+        string choice;
+        string channel;
+    }
+}
+
+//how the client signed the payload, EIP-191 stays the default for older clients
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SignatureType {
+    #[default]
+    Eip191,
+    Eip712,
+}
+
+//request payloads that can be signed as EIP-712 typed data
+pub trait TypedMessage {
+    type Typed: SolStruct;
+
+    fn to_typed(&self) -> AppResult<Self::Typed>;
+}
+
+pub fn domain(config: &Eip712Config) -> AppResult<Eip712Domain> {
+    let verifying_contract = match &config.verifying_contract {
+        Some(c) => Some(parse_address(c)?),
+        None => None,
+    };
+
+    Ok(Eip712Domain::new(
+        Some(Cow::Owned(config.name.clone())),
+        Some(Cow::Owned(config.version.clone())),
+        Some(U256::from(config.chain_id)),
+        verifying_contract,
+        None,
+    ))
+}
+
+pub fn parse_address(address: &str) -> AppResult<Address> {
+    Address::from_str(address).map_err(|_| AppError::InputValidateError(format!("invalid address: {}", address)))
+}
+
+//verify msg with the scheme the client selected, contract wallets go through checker
+pub async fn verify_signature_by_type<T: Serialize + TypedMessage>(
+    sig_type: SignatureType,
+    msg: &T,
+    sig: &str,
+    expect_address: &str,
+    config: &Eip712Config,
+    checker: Option<&dyn SignatureChecker>,
+) -> AppResult<bool> {
+    let hash = match sig_type {
+        SignatureType::Eip191 => hash_message(msg)?,
+        SignatureType::Eip712 => msg.to_typed()?.eip712_signing_hash(&domain(config)?),
+    };
+
+    verify_hash_signature(checker, hash, sig, expect_address).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::hex;
+    use alloy_signer::SignerSync;
+    use alloy_signer_local::PrivateKeySigner;
+
+    #[tokio::test]
+    async fn test_eip712_vote() {
+        let signer = PrivateKeySigner::from_slice(hex::decode("227dbb8586117d55284e26620bc76534dfbd2394be34cf4a09cb775d593b6f2b").unwrap().as_slice()).unwrap();
+        let domain = domain(&Eip712Config::default()).unwrap();
+
+        let vote = CastVote {
+            proposal_id: "293dbe4f-0b6b-462d-a778-2dceab12256b".to_string(),
+            choice: "For".to_string(),
+            channel: "web".to_string(),
+        };
+
+        let signature = signer.sign_hash_sync(&vote.eip712_signing_hash(&domain)).unwrap();
+        let sig = format!("0x{}", hex::encode(signature.as_bytes()));
+
+        assert!(verify_hash_signature(None, vote.eip712_signing_hash(&domain), &sig, &signer.address().to_string()).await.unwrap());
+
+        let other = CastVote {
+            choice: "Against".to_string(),
+            ..vote
+        };
+        assert!(!verify_hash_signature(None, other.eip712_signing_hash(&domain), &sig, &signer.address().to_string()).await.unwrap());
+    }
+}
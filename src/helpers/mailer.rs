@@ -0,0 +1,149 @@
+use crate::common::{
+    config::{MailConfig, MailTransport, SmtpConfig},
+    error::{AppError, AppResult},
+};
+use async_trait::async_trait;
+use lettre::{
+    message::{header::ContentType, Mailbox},
+    transport::smtp::authentication::Credentials,
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+use std::{path::PathBuf, sync::Arc};
+
+/// Sends mail to users, magic links and notifications go through it.
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, to: &str, subject: &str, body: &str) -> AppResult<()>;
+}
+
+/// Writes mail to the log instead of sending it, for development.
+pub struct LogMailer;
+
+#[async_trait]
+impl Mailer for LogMailer {
+    async fn send(&self, to: &str, subject: &str, body: &str) -> AppResult<()> {
+        tracing::info!("[mail] to: {} subject: {}\n{}", to, subject, body);
+        Ok(())
+    }
+}
+
+/// Writes each mail to its own file under a directory, so tests can read what was sent.
+pub struct FileMailer {
+    dir: PathBuf,
+}
+
+impl FileMailer {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+#[async_trait]
+impl Mailer for FileMailer {
+    async fn send(&self, to: &str, subject: &str, body: &str) -> AppResult<()> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+
+        let file = self.dir.join(format!(
+            "{}-{}.eml",
+            chrono::Utc::now().format("%Y%m%d%H%M%S"),
+            uuid::Uuid::new_v4()
+        ));
+        tokio::fs::write(file, format!("To: {}\nSubject: {}\n\n{}\n", to, subject, body)).await?;
+
+        Ok(())
+    }
+}
+
+/// Sends mail through an SMTP relay.
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    pub fn new(config: &SmtpConfig, from: &str) -> AppResult<Self> {
+        let builder = if config.starttls {
+            AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host)
+        } else {
+            AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host)
+        }
+        .map_err(|e| AppError::CustomError(format!("invalid smtp relay {}: {}", config.host, e)))?
+        .port(config.port);
+
+        let builder = if config.username.is_empty() {
+            builder
+        } else {
+            builder.credentials(Credentials::new(config.username.clone(), config.password.clone()))
+        };
+
+        Ok(Self {
+            transport: builder.build(),
+            from: from
+                .parse()
+                .map_err(|e| AppError::CustomError(format!("invalid mail.from {}: {}", from, e)))?,
+        })
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, to: &str, subject: &str, body: &str) -> AppResult<()> {
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to
+                .parse()
+                .map_err(|_e| AppError::InputValidateError(format!("invalid email {}", to)))?)
+            .subject(subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(body.to_string())
+            .map_err(|e| AppError::CustomError(format!("failed to build mail: {}", e)))?;
+
+        self.transport
+            .send(message)
+            .await
+            .map_err(|e| AppError::RequestError(format!("failed to send mail: {}", e)))?;
+
+        Ok(())
+    }
+}
+
+//the mailer picked by mail.transport
+pub fn mailer(config: &MailConfig) -> AppResult<Arc<dyn Mailer>> {
+    Ok(match config.transport {
+        MailTransport::Log => Arc::new(LogMailer),
+        MailTransport::File => Arc::new(FileMailer::new(config.dir.as_str())),
+        MailTransport::Smtp => {
+            let smtp = config
+                .smtp
+                .as_ref()
+                .ok_or(AppError::CustomError("mail.smtp is not set".into()))?;
+            Arc::new(SmtpMailer::new(smtp, config.from.as_str())?)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_file_mailer_writes_one_file_per_mail() {
+        let dir = std::env::temp_dir().join(format!("mailer-{}", uuid::Uuid::new_v4()));
+        let mailer = FileMailer::new(&dir);
+
+        mailer.send("a@example.com", "hello", "first").await.unwrap();
+        mailer.send("b@example.com", "hello", "second").await.unwrap();
+
+        let mut bodies = vec![];
+        for entry in std::fs::read_dir(&dir).unwrap() {
+            bodies.push(std::fs::read_to_string(entry.unwrap().path()).unwrap());
+        }
+        bodies.sort();
+
+        assert_eq!(bodies.len(), 2);
+        assert!(bodies[0].starts_with("To: a@example.com\nSubject: hello\n"));
+        assert!(bodies[1].ends_with("second\n"));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,2 +1,10 @@
 pub mod google_auth;
 pub mod eip191;
+pub mod eip1271;
+pub mod eip712;
+pub mod siwe;
+pub mod web_of_trust;
+pub mod sybil;
+pub mod token_cipher;
+pub mod mailer;
+pub mod nostr_challenge;
@@ -0,0 +1,72 @@
+use crate::common::{
+    consts,
+    error::{AppError, AppResult},
+};
+use nostr::{Event, Kind, PublicKey, Timestamp};
+
+//value of the first tag named name
+fn tag_value<'a>(event: &'a Event, name: &str) -> Option<&'a str> {
+    event.tags.iter().find_map(|t| match t.as_slice() {
+        [n, v, ..] if n == name => Some(v.as_str()),
+        _ => None,
+    })
+}
+
+/// Checks a challenge event signed by the key a Lamport ID wants to link, NIP-42 style:
+/// kind 22242 with `challenge` and `LamportID` tags, signed recently. Returns the proved pubkey.
+pub fn verify_challenge_event(event: &Event, challenge: &str, lamport_id: &str, now: Timestamp) -> AppResult<PublicKey> {
+    event.verify().map_err(|_| AppError::InvalidSignature)?;
+
+    if event.kind != Kind::from(consts::NOSTR_CHALLENGE_KIND) {
+        return Err(AppError::InputValidateError(format!(
+            "challenge event must be kind {}",
+            consts::NOSTR_CHALLENGE_KIND
+        )));
+    }
+
+    if tag_value(event, "challenge") != Some(challenge) {
+        return Err(AppError::InputValidateError("challenge mismatch".into()));
+    }
+
+    if tag_value(event, "LamportID") != Some(lamport_id) {
+        return Err(AppError::InputValidateError("lamport id mismatch".into()));
+    }
+
+    let age = now.as_u64().abs_diff(event.created_at.as_u64());
+    if age > consts::NOSTR_CHALLENGE_EXPIRATION {
+        return Err(AppError::InputValidateError("challenge event is too old or in the future".into()));
+    }
+
+    Ok(event.pubkey)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nostr::{EventBuilder, Keys, Tag};
+
+    fn challenge_event(keys: &Keys, kind: u16, challenge: &str, lamport_id: &str) -> Event {
+        EventBuilder::new(Kind::from(kind), "")
+            .tags(vec![
+                Tag::parse(["challenge", challenge]).unwrap(),
+                Tag::parse(["LamportID", lamport_id]).unwrap(),
+            ])
+            .sign_with_keys(keys)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_verify_challenge_event() {
+        let keys = Keys::generate();
+        let event = challenge_event(&keys, consts::NOSTR_CHALLENGE_KIND, "abc", "42");
+        let now = Timestamp::now();
+
+        assert_eq!(verify_challenge_event(&event, "abc", "42", now).unwrap(), keys.public_key());
+        assert!(verify_challenge_event(&event, "abd", "42", now).is_err());
+        assert!(verify_challenge_event(&event, "abc", "43", now).is_err());
+        assert!(verify_challenge_event(&event, "abc", "42", now + consts::NOSTR_CHALLENGE_EXPIRATION + 1).is_err());
+
+        let wrong_kind = challenge_event(&keys, 1, "abc", "42");
+        assert!(verify_challenge_event(&wrong_kind, "abc", "42", now).is_err());
+    }
+}
@@ -0,0 +1,329 @@
+use super::eip1271::{verify_hash_signature, SignatureChecker};
+use crate::common::{
+    config::SiweConfig,
+    error::{AppError, AppResult},
+};
+use alloy_primitives::{eip191_hash_message, Address};
+use chrono::{DateTime, Duration, Utc};
+use std::fmt;
+use std::str::FromStr;
+
+const PREAMBLE: &str = " wants you to sign in with your Ethereum account:";
+const URI_TAG: &str = "URI: ";
+const VERSION_TAG: &str = "Version: ";
+const CHAIN_TAG: &str = "Chain ID: ";
+const NONCE_TAG: &str = "Nonce: ";
+const ISSUED_AT_TAG: &str = "Issued At: ";
+const EXPIRATION_TAG: &str = "Expiration Time: ";
+const NOT_BEFORE_TAG: &str = "Not Before: ";
+const REQUEST_ID_TAG: &str = "Request ID: ";
+const RESOURCES_TAG: &str = "Resources:";
+
+//allowed clock drift between wallet and server
+const CLOCK_SKEW_SECONDS: i64 = 60;
+
+/// An EIP-4361 (Sign-In with Ethereum) message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SiweMessage {
+    pub scheme: Option<String>,
+    pub domain: String,
+    pub address: String,
+    pub statement: Option<String>,
+    pub uri: String,
+    pub version: String,
+    pub chain_id: u64,
+    pub nonce: String,
+    pub issued_at: DateTime<Utc>,
+    pub expiration_time: Option<DateTime<Utc>>,
+    pub not_before: Option<DateTime<Utc>>,
+    pub request_id: Option<String>,
+    pub resources: Vec<String>,
+}
+
+fn siwe_error(msg: &str) -> AppError {
+    AppError::SiweError(msg.to_string())
+}
+
+fn tagged<'a>(line: Option<&'a str>, tag: &str) -> AppResult<&'a str> {
+    line.and_then(|l| l.strip_prefix(tag))
+        .ok_or_else(|| AppError::SiweError(format!("missing `{}`", tag.trim_end())))
+}
+
+fn parse_time(value: &str) -> AppResult<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|t| t.with_timezone(&Utc))
+        .map_err(|_| AppError::SiweError(format!("invalid timestamp: {}", value)))
+}
+
+impl FromStr for SiweMessage {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> AppResult<Self> {
+        let mut lines = s.split('\n').peekable();
+
+        let origin = lines
+            .next()
+            .and_then(|l| l.strip_suffix(PREAMBLE))
+            .ok_or_else(|| siwe_error("missing preamble"))?;
+        let (scheme, domain) = match origin.split_once("://") {
+            Some((scheme, domain)) => (Some(scheme.to_string()), domain.to_string()),
+            None => (None, origin.to_string()),
+        };
+        if domain.is_empty() {
+            return Err(siwe_error("missing domain"));
+        }
+
+        let address = lines.next().ok_or_else(|| siwe_error("missing address"))?;
+        Address::parse_checksummed(address, None).map_err(|_| siwe_error("address is not EIP-55 checksummed"))?;
+
+        if lines.next() != Some("") {
+            return Err(siwe_error("missing blank line after address"));
+        }
+
+        let statement = match lines.next() {
+            Some("") => None,
+            Some(statement) => {
+                if lines.next() != Some("") {
+                    return Err(siwe_error("missing blank line after statement"));
+                }
+                Some(statement.to_string())
+            }
+            None => return Err(siwe_error("message is truncated")),
+        };
+
+        let uri = tagged(lines.next(), URI_TAG)?.to_string();
+        let version = tagged(lines.next(), VERSION_TAG)?.to_string();
+        let chain_id = tagged(lines.next(), CHAIN_TAG)?
+            .parse::<u64>()
+            .map_err(|_| siwe_error("invalid chain id"))?;
+        let nonce = tagged(lines.next(), NONCE_TAG)?.to_string();
+        if nonce.len() < 8 || !nonce.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(siwe_error("nonce must be at least 8 alphanumeric characters"));
+        }
+        let issued_at = parse_time(tagged(lines.next(), ISSUED_AT_TAG)?)?;
+
+        let mut expiration_time = None;
+        if let Some(v) = lines.peek().and_then(|l| l.strip_prefix(EXPIRATION_TAG)) {
+            expiration_time = Some(parse_time(v)?);
+            lines.next();
+        }
+
+        let mut not_before = None;
+        if let Some(v) = lines.peek().and_then(|l| l.strip_prefix(NOT_BEFORE_TAG)) {
+            not_before = Some(parse_time(v)?);
+            lines.next();
+        }
+
+        let mut request_id = None;
+        if let Some(v) = lines.peek().and_then(|l| l.strip_prefix(REQUEST_ID_TAG)) {
+            request_id = Some(v.to_string());
+            lines.next();
+        }
+
+        let mut resources = Vec::new();
+        if lines.peek() == Some(&RESOURCES_TAG) {
+            lines.next();
+            while let Some(v) = lines.peek().and_then(|l| l.strip_prefix("- ")) {
+                resources.push(v.to_string());
+                lines.next();
+            }
+        }
+
+        if lines.any(|l| !l.is_empty()) {
+            return Err(siwe_error("unexpected trailing content"));
+        }
+
+        Ok(Self {
+            scheme,
+            domain,
+            address: address.to_string(),
+            statement,
+            uri,
+            version,
+            chain_id,
+            nonce,
+            issued_at,
+            expiration_time,
+            not_before,
+            request_id,
+            resources,
+        })
+    }
+}
+
+impl fmt::Display for SiweMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.scheme {
+            Some(scheme) => writeln!(f, "{}://{}{}", scheme, self.domain, PREAMBLE)?,
+            None => writeln!(f, "{}{}", self.domain, PREAMBLE)?,
+        }
+        writeln!(f, "{}", self.address)?;
+        writeln!(f)?;
+        if let Some(statement) = &self.statement {
+            writeln!(f, "{}", statement)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "{}{}", URI_TAG, self.uri)?;
+        writeln!(f, "{}{}", VERSION_TAG, self.version)?;
+        writeln!(f, "{}{}", CHAIN_TAG, self.chain_id)?;
+        writeln!(f, "{}{}", NONCE_TAG, self.nonce)?;
+        write!(f, "{}{}", ISSUED_AT_TAG, self.issued_at.to_rfc3339())?;
+        if let Some(t) = &self.expiration_time {
+            write!(f, "\n{}{}", EXPIRATION_TAG, t.to_rfc3339())?;
+        }
+        if let Some(t) = &self.not_before {
+            write!(f, "\n{}{}", NOT_BEFORE_TAG, t.to_rfc3339())?;
+        }
+        if let Some(id) = &self.request_id {
+            write!(f, "\n{}{}", REQUEST_ID_TAG, id)?;
+        }
+        if !self.resources.is_empty() {
+            write!(f, "\n{}", RESOURCES_TAG)?;
+            for r in &self.resources {
+                write!(f, "\n- {}", r)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl SiweMessage {
+    /// Checks the message against the server configuration and the current time.
+    ///
+    /// The nonce is not checked here, it has to be consumed from the nonce cache by the caller.
+    pub fn validate(&self, config: &SiweConfig, now: DateTime<Utc>) -> AppResult<()> {
+        if self.domain != config.domain {
+            return Err(siwe_error("domain mismatch"));
+        }
+
+        //same origin as the configured uri, a prefix check would let https://lamport.example.evil.com through
+        let expected = url::Url::parse(config.uri.as_str())?;
+        let uri = url::Url::parse(self.uri.as_str()).map_err(|_| siwe_error("invalid uri"))?;
+        if uri.scheme() != expected.scheme()
+            || uri.host_str() != expected.host_str()
+            || uri.port_or_known_default() != expected.port_or_known_default()
+        {
+            return Err(siwe_error("uri mismatch"));
+        }
+
+        if self.version != "1" {
+            return Err(siwe_error("unsupported version"));
+        }
+
+        if !config.chain_ids.contains(&self.chain_id) {
+            return Err(siwe_error("unsupported chain id"));
+        }
+
+        let skew = Duration::seconds(CLOCK_SKEW_SECONDS);
+        if self.issued_at > now + skew {
+            return Err(siwe_error("issued in the future"));
+        }
+        if self.issued_at + Duration::seconds(config.max_age) < now {
+            return Err(siwe_error("message is too old"));
+        }
+
+        if let Some(expiration) = self.expiration_time {
+            if expiration <= now {
+                return Err(siwe_error("message has expired"));
+            }
+        }
+
+        if let Some(not_before) = self.not_before {
+            if not_before > now + skew {
+                return Err(siwe_error("message is not yet valid"));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+//personal_sign over the raw message text
+pub async fn verify_siwe_signature(
+    message: &str,
+    sig: &str,
+    expect_address: &str,
+    checker: Option<&dyn SignatureChecker>,
+) -> AppResult<bool> {
+    verify_hash_signature(checker, eip191_hash_message(message), sig, expect_address).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::hex;
+    use alloy_signer::SignerSync;
+    use alloy_signer_local::PrivateKeySigner;
+
+    fn config() -> SiweConfig {
+        SiweConfig {
+            domain: "lamport.example".to_string(),
+            uri: "https://lamport.example".to_string(),
+            chain_ids: vec![1],
+            max_age: 600,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_siwe_round_trip() {
+        let signer = PrivateKeySigner::from_slice(hex::decode("227dbb8586117d55284e26620bc76534dfbd2394be34cf4a09cb775d593b6f2b").unwrap().as_slice()).unwrap();
+        let now = Utc::now();
+
+        let message = SiweMessage {
+            scheme: Some("https".to_string()),
+            domain: "lamport.example".to_string(),
+            address: signer.address().to_checksum(None),
+            statement: Some("Sign in to Lamport ID".to_string()),
+            uri: "https://lamport.example/login".to_string(),
+            version: "1".to_string(),
+            chain_id: 1,
+            nonce: "k3Hd9sLq2XyZ".to_string(),
+            issued_at: now,
+            expiration_time: Some(now + Duration::minutes(5)),
+            not_before: None,
+            request_id: None,
+            resources: vec!["https://lamport.example/terms".to_string()],
+        };
+
+        let text = message.to_string();
+        let parsed = SiweMessage::from_str(&text).unwrap();
+        assert_eq!(parsed.address, message.address);
+        assert_eq!(parsed.resources, message.resources);
+        assert_eq!(parsed.to_string(), text);
+        parsed.validate(&config(), now).unwrap();
+
+        let signature = signer.sign_message_sync(text.as_bytes()).unwrap();
+        let sig = format!("0x{}", hex::encode(signature.as_bytes()));
+        assert!(verify_siwe_signature(&text, &sig, &parsed.address, None).await.unwrap());
+
+        assert!(parsed.validate(&config(), now + Duration::minutes(6)).is_err());
+
+        let other_chain = SiweMessage { chain_id: 5, ..parsed.clone() };
+        assert!(other_chain.validate(&config(), now).is_err());
+
+        for uri in ["https://lamport.example.evil.com/login", "http://lamport.example/login", "https://lamport.example:8443/login"] {
+            let other_uri = SiweMessage { uri: uri.to_string(), ..parsed.clone() };
+            assert!(other_uri.validate(&config(), now).is_err(), "{}", uri);
+        }
+        let default_port = SiweMessage { uri: "https://lamport.example:443/login".to_string(), ..parsed };
+        default_port.validate(&config(), now).unwrap();
+    }
+
+    #[test]
+    fn test_siwe_without_statement() {
+        let text = "lamport.example wants you to sign in with your Ethereum account:\n\
+            0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed\n\
+            \n\
+            \n\
+            URI: https://lamport.example\n\
+            Version: 1\n\
+            Chain ID: 1\n\
+            Nonce: 32891756aBcD\n\
+            Issued At: 2021-09-30T16:25:24Z";
+
+        let parsed = SiweMessage::from_str(text).unwrap();
+        assert_eq!(parsed.statement, None);
+        assert_eq!(parsed.scheme, None);
+        assert_eq!(parsed.nonce, "32891756aBcD");
+    }
+}
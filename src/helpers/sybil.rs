@@ -0,0 +1,225 @@
+use serde::Serialize;
+
+//points every account starts from, the factors below move it within 0..=100
+const BASE_SCORE: i32 = 10;
+const MAX_SCORE: i32 = 100;
+
+const TWITTER_BOUND: i32 = 5;
+const TWITTER_AGE_MAX: i32 = 15;
+const TWITTER_AGE_FULL_DAYS: i64 = 180;
+
+const VERIFIED: i32 = 10;
+const ATTESTATION: i32 = 4;
+const ATTESTATION_MAX_COUNTED: u64 = 5;
+
+const INVITER_FANOUT_LIMIT: u64 = 50;
+const INVITER_FANOUT_PENALTY: i32 = -10;
+const INVITEE_POOR_MIN: u64 = 5;
+const INVITEE_POOR_RATIO: f64 = 0.2;
+const INVITEE_POOR_PENALTY: i32 = -10;
+const INVITEE_VERIFIED: i32 = 2;
+const INVITEE_VERIFIED_MAX_COUNTED: u64 = 5;
+
+const MULTI_WALLET: i32 = 5;
+
+const VOTE: i32 = 1;
+const VOTE_MAX_COUNTED: u64 = 10;
+const MIRRORED_MIN_VOTES: u64 = 5;
+const MIRRORED_RATIO: f64 = 0.5;
+const MIRRORED_PENALTY: i32 = -20;
+
+const SAME_IP_HEAVY: u64 = 10;
+const SAME_IP_HEAVY_PENALTY: i32 = -25;
+const SAME_IP_LIGHT: u64 = 3;
+const SAME_IP_LIGHT_PENALTY: i32 = -10;
+const SAME_IP_NONE: i32 = 5;
+
+const ACCOUNT_AGE_MAX: i32 = 15;
+const ACCOUNT_AGE_FULL_DAYS: i64 = 365;
+
+/// Signals about one Lamport ID gathered from the database, see `Storage::get_sybil_signals`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SybilSignals {
+    //days since the twitter account was bound, none without a binding
+    pub twitter_bound_days: Option<i64>,
+    pub verified: bool,
+    //active attestations received
+    pub attestations: u64,
+    //accounts invited with the same code as this one
+    pub inviter_fanout: Option<u64>,
+    pub invitees: u64,
+    pub invitees_verified: u64,
+    //primary and linked addresses
+    pub wallets: u64,
+    pub votes: u64,
+    //votes matching another voter's proposal and choice within seconds
+    pub mirrored_votes: u64,
+    //other accounts registered from the same ip around the same time, none when the ip is unknown
+    pub same_ip_registrations: Option<u64>,
+    pub account_age_days: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScoreFactor {
+    pub signal: &'static str,
+    pub points: i32,
+    pub detail: String,
+}
+
+/// Humanity score in `0..=100` and the factors that produced it.
+#[derive(Debug, Clone, Serialize)]
+pub struct HumanityScore {
+    pub score: u32,
+    pub factors: Vec<ScoreFactor>,
+}
+
+//linear share of max_points, full at full_days
+fn age_points(days: i64, full_days: i64, max_points: i32) -> i32 {
+    (days.clamp(0, full_days) * max_points as i64 / full_days) as i32
+}
+
+impl SybilSignals {
+    pub fn score(&self) -> HumanityScore {
+        let mut factors = vec![ScoreFactor {
+            signal: "base",
+            points: BASE_SCORE,
+            detail: "every account starts here".to_string(),
+        }];
+        let mut add = |signal: &'static str, points: i32, detail: String| {
+            if points != 0 {
+                factors.push(ScoreFactor { signal, points, detail });
+            }
+        };
+
+        if let Some(days) = self.twitter_bound_days {
+            add(
+                "twitter",
+                TWITTER_BOUND + age_points(days, TWITTER_AGE_FULL_DAYS, TWITTER_AGE_MAX),
+                format!("twitter bound {} days ago", days),
+            );
+        }
+
+        if self.verified {
+            add("verified", VERIFIED, "verified by attestations".to_string());
+        }
+        add(
+            "attestations",
+            self.attestations.min(ATTESTATION_MAX_COUNTED) as i32 * ATTESTATION,
+            format!("{} active attestations", self.attestations),
+        );
+
+        if let Some(fanout) = self.inviter_fanout {
+            if fanout > INVITER_FANOUT_LIMIT {
+                add(
+                    "inviter_fanout",
+                    INVITER_FANOUT_PENALTY,
+                    format!("invite code shared by {} accounts", fanout),
+                );
+            }
+        }
+        if self.invitees >= INVITEE_POOR_MIN
+            && (self.invitees_verified as f64) < self.invitees as f64 * INVITEE_POOR_RATIO
+        {
+            add(
+                "invitees",
+                INVITEE_POOR_PENALTY,
+                format!("{} of {} invitees verified", self.invitees_verified, self.invitees),
+            );
+        } else {
+            add(
+                "invitees",
+                self.invitees_verified.min(INVITEE_VERIFIED_MAX_COUNTED) as i32 * INVITEE_VERIFIED,
+                format!("{} verified invitees", self.invitees_verified),
+            );
+        }
+
+        if self.wallets > 1 {
+            add("wallets", MULTI_WALLET, format!("{} wallets linked", self.wallets));
+        }
+
+        if self.votes >= MIRRORED_MIN_VOTES
+            && self.mirrored_votes as f64 > self.votes as f64 * MIRRORED_RATIO
+        {
+            add(
+                "votes",
+                MIRRORED_PENALTY,
+                format!("{} of {} votes mirror another voter", self.mirrored_votes, self.votes),
+            );
+        } else {
+            add(
+                "votes",
+                self.votes.min(VOTE_MAX_COUNTED) as i32 * VOTE,
+                format!("{} votes cast", self.votes),
+            );
+        }
+
+        match self.same_ip_registrations {
+            Some(n) if n >= SAME_IP_HEAVY => add(
+                "registration_ip",
+                SAME_IP_HEAVY_PENALTY,
+                format!("{} other accounts registered from the same ip", n),
+            ),
+            Some(n) if n >= SAME_IP_LIGHT => add(
+                "registration_ip",
+                SAME_IP_LIGHT_PENALTY,
+                format!("{} other accounts registered from the same ip", n),
+            ),
+            Some(0) => add(
+                "registration_ip",
+                SAME_IP_NONE,
+                "no other account registered from the same ip".to_string(),
+            ),
+            _ => {}
+        }
+
+        add(
+            "account_age",
+            age_points(self.account_age_days, ACCOUNT_AGE_FULL_DAYS, ACCOUNT_AGE_MAX),
+            format!("registered {} days ago", self.account_age_days),
+        );
+
+        let total: i32 = factors.iter().map(|f| f.points).sum();
+
+        HumanityScore {
+            score: total.clamp(0, MAX_SCORE) as u32,
+            factors,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_humanity_score() {
+        let fresh = SybilSignals::default().score();
+        assert_eq!(fresh.score, BASE_SCORE as u32);
+
+        let established = SybilSignals {
+            twitter_bound_days: Some(365),
+            verified: true,
+            attestations: 3,
+            wallets: 2,
+            votes: 12,
+            mirrored_votes: 1,
+            same_ip_registrations: Some(0),
+            account_age_days: 365,
+            ..Default::default()
+        }
+        .score();
+        assert_eq!(established.score, 87);
+
+        let farmed = SybilSignals {
+            inviter_fanout: Some(200),
+            votes: 10,
+            mirrored_votes: 9,
+            same_ip_registrations: Some(40),
+            account_age_days: 2,
+            ..Default::default()
+        }
+        .score();
+        assert_eq!(farmed.score, 0);
+        assert!(farmed.factors.iter().any(|f| f.signal == "votes" && f.points < 0));
+    }
+}
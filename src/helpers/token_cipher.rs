@@ -0,0 +1,90 @@
+use crate::common::error::{AppError, AppResult};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    XChaCha20Poly1305, XNonce,
+};
+use sha2::{Digest, Sha256};
+
+//marks a sealed value, anything else is a token stored before encryption was added
+pub const SEALED_PREFIX: &str = "enc:v1:";
+const NONCE_LEN: usize = 24;
+
+/// Encrypts OAuth tokens before they are stored, keyed by `twitter.token_secret`.
+#[derive(Clone)]
+pub struct TokenCipher(XChaCha20Poly1305);
+
+impl TokenCipher {
+    pub fn new(secret: &str) -> AppResult<Self> {
+        if secret.is_empty() {
+            return Err(AppError::CustomError("twitter.token_secret is not set".into()));
+        }
+
+        let key = Sha256::digest(secret.as_bytes());
+        Ok(Self(XChaCha20Poly1305::new(&key)))
+    }
+
+    pub fn encrypt(&self, plain: &str) -> AppResult<String> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let sealed = self
+            .0
+            .encrypt(&nonce, plain.as_bytes())
+            .map_err(|_| AppError::CustomError("failed to encrypt token".into()))?;
+
+        let mut out = nonce.to_vec();
+        out.extend(sealed);
+        Ok(format!("{}{}", SEALED_PREFIX, STANDARD.encode(out)))
+    }
+
+    pub fn is_sealed(stored: &str) -> bool {
+        stored.starts_with(SEALED_PREFIX)
+    }
+
+    //plaintext tokens from before encryption are sealed once at startup, any left are refused
+    pub fn decrypt(&self, stored: &str) -> AppResult<String> {
+        let Some(encoded) = stored.strip_prefix(SEALED_PREFIX) else {
+            return Err(AppError::CustomError("token is not encrypted".into()));
+        };
+
+        let raw = STANDARD
+            .decode(encoded)
+            .map_err(|_| AppError::CustomError("malformed encrypted token".into()))?;
+        if raw.len() < NONCE_LEN {
+            return Err(AppError::CustomError("malformed encrypted token".into()));
+        }
+
+        let (nonce, sealed) = raw.split_at(NONCE_LEN);
+        let plain = self
+            .0
+            .decrypt(XNonce::from_slice(nonce), sealed)
+            .map_err(|_| AppError::CustomError("failed to decrypt token".into()))?;
+
+        String::from_utf8(plain).map_err(|_| AppError::CustomError("failed to decrypt token".into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_cipher() {
+        let cipher = TokenCipher::new("test-secret").unwrap();
+
+        let sealed = cipher.encrypt("access-token").unwrap();
+        assert!(sealed.starts_with(SEALED_PREFIX));
+        assert!(!sealed.contains("access-token"));
+        assert_eq!(cipher.decrypt(&sealed).unwrap(), "access-token");
+
+        //nonces differ, so the same token never seals to the same value
+        assert_ne!(cipher.encrypt("access-token").unwrap(), sealed);
+
+        assert!(TokenCipher::is_sealed(&sealed));
+        assert!(!TokenCipher::is_sealed("legacy-plaintext"));
+        assert!(cipher.decrypt("legacy-plaintext").is_err());
+
+        let other = TokenCipher::new("other-secret").unwrap();
+        assert!(other.decrypt(&sealed).is_err());
+        assert!(TokenCipher::new("").is_err());
+    }
+}
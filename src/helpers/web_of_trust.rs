@@ -0,0 +1,124 @@
+use std::collections::{HashMap, HashSet};
+
+/// When attestations verify an account, built from `VerificationConfig` and the granted roles.
+#[derive(Debug, Clone)]
+pub struct VerificationPolicy {
+    //active attestations from verified attesters needed
+    pub required: usize,
+    //attesters whose single attestation is enough, they are always treated as verified
+    pub trusted_roots: HashSet<String>,
+}
+
+impl VerificationPolicy {
+    pub fn new(required: usize, trusted_roots: HashSet<String>) -> Self {
+        Self {
+            required: required.max(1),
+            trusted_roots,
+        }
+    }
+
+    pub fn is_trusted(&self, lamport_id: &str) -> bool {
+        self.trusted_roots.contains(lamport_id)
+    }
+}
+
+/// Recompute the verification of every account in `region`.
+///
+/// `region` is an account whose attestations changed plus everyone it reaches through
+/// attestations, `attestations` are the active `(attester, subject)` pairs into the region
+/// and `outside_verified` the verified attesters outside it. The region starts unverified
+/// and accounts are verified until nothing changes, so a cycle of attestations can not keep
+/// itself verified once its last outside support is revoked.
+///
+/// Returns the attester credited with each verification, `None` for accounts left unverified.
+/// Trusted roots in the region are not part of the result.
+pub fn resolve_verification(
+    region: &[String],
+    attestations: &[(String, String)],
+    outside_verified: &HashSet<String>,
+    policy: &VerificationPolicy,
+) -> HashMap<String, Option<String>> {
+    let mut verified_by: HashMap<String, Option<String>> = region
+        .iter()
+        .filter(|s| !policy.is_trusted(s))
+        .map(|s| (s.clone(), None))
+        .collect();
+
+    let counts = |attester: &str, current: &HashMap<String, Option<String>>| {
+        policy.is_trusted(attester)
+            || match current.get(attester) {
+                Some(by) => by.is_some(),
+                None => outside_verified.contains(attester),
+            }
+    };
+
+    loop {
+        let mut changed = false;
+
+        for subject in region {
+            //trusted roots and accounts verified in an earlier pass are skipped
+            if !matches!(verified_by.get(subject), Some(None)) {
+                continue;
+            }
+
+            let attesters: Vec<&String> = attestations
+                .iter()
+                .filter(|(a, s)| s == subject && a != subject && counts(a, &verified_by))
+                .map(|(a, _)| a)
+                .collect();
+
+            let by = match attesters.iter().find(|a| policy.is_trusted(a)) {
+                Some(root) => Some(*root),
+                None if attesters.len() >= policy.required => attesters.first().copied(),
+                None => None,
+            };
+
+            if let Some(by) = by {
+                verified_by.insert(subject.clone(), Some(by.clone()));
+                changed = true;
+            }
+        }
+
+        if !changed {
+            return verified_by;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(edges: &[(&str, &str)]) -> Vec<(String, String)> {
+        edges.iter().map(|(a, s)| (a.to_string(), s.to_string())).collect()
+    }
+
+    fn ids(v: &[&str]) -> Vec<String> {
+        v.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_resolve_verification() {
+        let policy = VerificationPolicy::new(2, HashSet::from(["root".to_string()]));
+        let outside = HashSet::from(["x".to_string(), "y".to_string()]);
+
+        //root vouches for a, a and x for b, only x for c
+        let edges = pairs(&[("root", "a"), ("a", "b"), ("x", "b"), ("x", "c")]);
+        let resolved = resolve_verification(&ids(&["a", "b", "c"]), &edges, &outside, &policy);
+        assert_eq!(resolved["a"].as_deref(), Some("root"));
+        assert!(resolved["b"].is_some());
+        assert_eq!(resolved["c"], None);
+
+        //once root revokes a, b loses one of its two attesters
+        let edges = pairs(&[("a", "b"), ("x", "b"), ("x", "c")]);
+        let resolved = resolve_verification(&ids(&["a", "b", "c"]), &edges, &outside, &policy);
+        assert_eq!(resolved["a"], None);
+        assert_eq!(resolved["b"], None);
+
+        //a cycle does not keep itself verified
+        let edges = pairs(&[("a", "b"), ("b", "a"), ("x", "a"), ("y", "b")]);
+        let resolved = resolve_verification(&ids(&["a", "b"]), &edges, &HashSet::new(), &policy);
+        assert_eq!(resolved["a"], None);
+        assert_eq!(resolved["b"], None);
+    }
+}
@@ -1,4 +1,3 @@
-use logging;
 use acl_lamport_id::commands;
 
 pub const LOG_PATH: &str = "logs";
@@ -1,4 +1,9 @@
-use crate::common::error::{AppError, AppResult};
+use super::schema::{self, LamportEvent};
+use crate::common::{
+    config::{RelayConfig, RelayRole},
+    consts,
+    error::{AppError, AppResult},
+};
 use nostr::event::{Event, EventId, UnsignedEvent};
 use nostr_sdk::prelude::*;
 use serde::{Deserialize, Serialize};
@@ -7,23 +12,33 @@ use serde::{Deserialize, Serialize};
 pub struct NostrClient {
     pub signer: Keys,   // The cryptographic keys used for signing events.
     pub client: Client, // The underlying Nostr SDK client.
+    pub write_relays: Vec<String>,
 }
 
 impl NostrClient {
-    pub async fn new(priv_key: &str, relay: Option<&str>) -> AppResult<Self> {
+    pub async fn new(priv_key: &str, relays: &[RelayConfig]) -> AppResult<Self> {
         let keys = Keys::parse(priv_key)?;
         let opts = Options::new().gossip(true);
         let client_builder = Client::builder().signer(keys.clone()).opts(opts);
         let client = client_builder.build();
 
-        if let Some(url) = relay {
-            client.add_relay(url).await?;
+        for relay in relays {
+            match relay.role {
+                RelayRole::Read => client.add_read_relay(relay.url.as_str()).await?,
+                RelayRole::Write => client.add_write_relay(relay.url.as_str()).await?,
+                RelayRole::ReadWrite => client.add_relay(relay.url.as_str()).await?,
+            };
         }
         client.connect().await;
 
         Ok(Self {
             signer: keys,
             client,
+            write_relays: relays
+                .iter()
+                .filter(|r| r.role.writes())
+                .map(|r| r.url.clone())
+                .collect(),
         })
     }
 
@@ -40,21 +55,83 @@ impl NostrClient {
         Ok(self.client.send_event(event).await?.id().to_owned())
     }
 
+    //events we published about lamport_id, relays can not filter on LamportID so it is matched here
+    pub async fn fetch_lamport_events(&self, lamport_id: &str) -> AppResult<Vec<Event>> {
+        let filter = Filter::new()
+            .author(self.get_pub_key())
+            .kinds(consts::NOSTR_LAMPORT_KINDS.iter().map(|k| Kind::from(*k)));
+        let events = self
+            .client
+            .fetch_events(vec![filter], std::time::Duration::from_secs(consts::NOSTR_FETCH_TIMEOUT))
+            .await?;
+
+        Ok(events
+            .into_iter()
+            .filter(|e| {
+                e.tags
+                    .iter()
+                    .any(|t| matches!(t.as_slice(), [name, value, ..] if name == schema::TAG_LAMPORT_ID && value == lamport_id))
+            })
+            .collect())
+    }
+
+    //sends to each relay on its own, so one relay failing does not hide the others' results
+    pub async fn publish_to(&self, event: &Event, relays: &[String]) -> Vec<(String, Result<(), String>)> {
+        let sends = relays.iter().map(|relay| async move {
+            let result = match self.client.send_event_to([relay.as_str()], event.clone()).await {
+                Ok(output) if output.success.is_empty() => {
+                    Err(output.failed.into_values().next().unwrap_or_default())
+                }
+                Ok(_) => Ok(()),
+                Err(e) => Err(e.to_string()),
+            };
+            (relay.clone(), result)
+        });
+
+        futures::future::join_all(sends).await
+    }
+
     pub async fn sign_and_send(&self, msg: &LamportBinding) -> AppResult<EventId> {
-        let event: UnsignedEvent = msg.clone().into();
+        let event: UnsignedEvent = msg.clone().try_into()?;
         let signed = event.sign(&self.signer.clone()).await?;
         tracing::info!("Signed event: {:?}", signed);
         self.send_event(signed).await
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub enum LamportType {
     Create,
     Invite,
     Bind,
     Vote,
     Voting,
+    Attest,
+    Revoke,
+    Unbind,
+    Delete,
+    Proposal,
+}
+
+//parses the debug names written to the lamport_type tag
+impl std::str::FromStr for LamportType {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Create" => Ok(Self::Create),
+            "Invite" => Ok(Self::Invite),
+            "Bind" => Ok(Self::Bind),
+            "Vote" => Ok(Self::Vote),
+            "Voting" => Ok(Self::Voting),
+            "Attest" => Ok(Self::Attest),
+            "Revoke" => Ok(Self::Revoke),
+            "Unbind" => Ok(Self::Unbind),
+            "Delete" => Ok(Self::Delete),
+            "Proposal" => Ok(Self::Proposal),
+            _ => Err(AppError::InvalidLamportType),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -66,17 +143,19 @@ pub struct LamportBinding {
     pub lamport_type: Option<LamportType>,
 }
 
-impl From<LamportBinding> for UnsignedEvent {
-    fn from(lamport: LamportBinding) -> Self {
-        let tags: Vec<Tag> = lamport
+impl TryFrom<LamportBinding> for UnsignedEvent {
+    type Error = AppError;
+
+    fn try_from(lamport: LamportBinding) -> Result<Self, Self::Error> {
+        let tags = lamport
             .tags
-            .iter()
-            .map(|tag| Tag::parse(tag).unwrap())
-            .collect::<Vec<Tag>>();
-        EventBuilder::new(lamport.kind, lamport.content)
-            .tags(tags)
-            .build(lamport.pubkey)
+            .into_iter()
+            .map(Tag::parse)
+            .collect::<Result<Vec<Tag>, _>>()?;
 
+        Ok(EventBuilder::new(lamport.kind, lamport.content)
+            .tags(tags)
+            .build(lamport.pubkey))
     }
 }
 
@@ -92,7 +171,7 @@ impl LamportBinding {
             kind,
             tags: Default::default(),
             content: content.to_owned(),
-            lamport_type: lamport_type,
+            lamport_type,
         }
     }
 
@@ -100,44 +179,45 @@ impl LamportBinding {
         Ok(serde_json::to_string(self)?)
     }
 
-    //pub fn add_tag(&mut self, tag: &str) {
-    //    self.tags.push(tag.to_owned());
-    //}
+    //the first LamportID tag, the lamport id the event is published for
+    pub fn lamport_id(&self) -> Option<String> {
+        schema::tag(&self.tags, schema::TAG_LAMPORT_ID).map(|v| v.to_string())
+    }
 
-    pub fn new_kind2321(pubkey: PublicKey, lamport_id: &str, twitter: &str) -> Self {
-        Self {
-            pubkey,
-            kind: Kind::Custom(2321),
-            tags: vec![
-                //format!("LamportID = {}", lamport_id),
-                //format!("Twitter = {}", twitter),
-                //format!("lmport_type = {:?}", LamportType::Create),
-                vec!["LamportID".to_string(), lamport_id.to_string()],
-                vec!["Twitter".to_string(), twitter.to_string()],
-                vec!["lmport_type".to_string(), format!("{:?}", LamportType::Create)],
-            ],
-            content: format!("LamportID:{}", lamport_id),
-            lamport_type: Some(LamportType::Create),
+    //lamport ids the event is about, their linked nostr keys go in p tags
+    pub fn tagged_lamport_ids(&self) -> Vec<String> {
+        self.tags
+            .iter()
+            .filter(|t| t.len() > 1 && (t[0] == schema::TAG_LAMPORT_ID || t[0] == schema::TAG_SUBJECT))
+            .map(|t| t[1].clone())
+            .collect()
+    }
+
+    pub fn add_p_tags(&mut self, pubkeys: Vec<String>) {
+        for pubkey in pubkeys {
+            if !self.tags.iter().any(|t| t.len() > 1 && t[0] == "p" && t[1] == pubkey) {
+                self.tags.push(vec!["p".to_string(), pubkey]);
+            }
         }
     }
 
+    //binding of a social account
+    pub fn new_kind2321(pubkey: PublicKey, lamport_id: &str, provider: &str, account: &str) -> Self {
+        schema::CreateEvent {
+            lamport_id: lamport_id.to_string(),
+            provider: provider.to_string(),
+            account: account.to_string(),
+        }
+        .build(pubkey)
+    }
+
     pub fn new_kind2322(pubkey: PublicKey, lamport_id: &str, address: &str, sig: &str) -> Self {
-        Self {
-            pubkey,
-            kind: Kind::Custom(2322),
-            tags: vec![
-                //vec!["LamportID", lamport_id],
-                //vec!["Address", address],
-                //vec!["sig", sig],
-                //vec!["lmport_type", format!("{:?}", LamportType::Bind)],
-                vec!["LamportID".to_string(), lamport_id.to_string()],
-                vec!["Address".to_string(), address.to_string()],
-                vec!["sig".to_string(), sig.to_string()],
-                vec!["lmport_type".to_string(), format!("{:?}", LamportType::Bind)],
-            ],
-            content: format!("LamportID:{} bind address:{}", lamport_id, address),
-            lamport_type: Some(LamportType::Bind),
+        schema::BindEvent {
+            lamport_id: lamport_id.to_string(),
+            address: address.to_string(),
+            sig: sig.to_string(),
         }
+        .build(pubkey)
     }
 
     pub fn new_kind2323(
@@ -147,53 +227,77 @@ impl LamportBinding {
         invitee: &str,
         link: &str,
     ) -> Self {
-        Self {
-            pubkey,
-            kind: Kind::Custom(2323),
-            tags: vec![
-                //format!("LamportID = {}", lamport_id),
-                //format!("p = {}", project),
-                //format!("Invitee = {}", invitee),
-                //format!("lmport_type = {:?}", LamportType::Invite),
-                //"i =invite".to_string(),
-                vec!["LamportID".to_string(), lamport_id.to_string()],
-                vec!["p".to_string(), project.to_string()],
-                vec!["Invitee".to_string(), invitee.to_string()],
-                vec!["lmport_type".to_string(), format!("{:?}", LamportType::Invite)],
-                vec!["i".to_string(), "invite".to_string()],
-            ],
-            content: format!("{} Invite {}, Link:{}", lamport_id, invitee, link),
-            lamport_type: Some(LamportType::Invite),
+        schema::InviteEvent {
+            lamport_id: lamport_id.to_string(),
+            project: project.to_string(),
+            invitee: invitee.to_string(),
+            link: link.to_string(),
         }
+        .build(pubkey)
     }
 
-    pub fn new_kind_vote(
+    pub fn new_kind2324(
         pubkey: PublicKey,
-        lamport_id: &str,
-        vote_id: &str,
-        title: &str,
-        content: &str,
-        start_time: &str,
-        end_time: &str,
-        options: &str,
+        attester: &str,
+        subject: &str,
+        address: &str,
         sig: &str,
     ) -> Self {
+        schema::AttestEvent {
+            attester: attester.to_string(),
+            subject: subject.to_string(),
+            address: address.to_string(),
+            sig: sig.to_string(),
+        }
+        .build(pubkey)
+    }
+
+    pub fn new_kind2325(pubkey: PublicKey, attester: &str, subject: &str, revoked_by: &str) -> Self {
+        schema::RevokeEvent {
+            attester: attester.to_string(),
+            subject: subject.to_string(),
+            revoked_by: revoked_by.to_string(),
+        }
+        .build(pubkey)
+    }
+
+    //retracts the kind 2321 binding of account, the account may have moved to another lamport id
+    pub fn new_kind2326(pubkey: PublicKey, lamport_id: &str, provider: &str, account: &str, reason: &str) -> Self {
+        schema::UnbindEvent {
+            lamport_id: lamport_id.to_string(),
+            provider: provider.to_string(),
+            account: account.to_string(),
+            reason: reason.to_string(),
+        }
+        .build(pubkey)
+    }
+
+    pub fn new_kind2327(pubkey: PublicKey, proposal: schema::ProposalEvent) -> Self {
+        proposal.build(pubkey)
+    }
+
+    pub fn new_kind2328(pubkey: PublicKey, vote: schema::VoteEvent) -> Self {
+        vote.build(pubkey)
+    }
+
+    //NIP-09 deletion of events we signed for a lamport id that deleted its account
+    pub fn new_kind5(pubkey: PublicKey, events: &[(EventId, Kind)]) -> Self {
+        let mut tags: Vec<Vec<String>> = events
+            .iter()
+            .map(|(id, _)| vec!["e".to_string(), id.to_hex()])
+            .collect();
+        let mut kinds: Vec<u16> = events.iter().map(|(_, kind)| kind.as_u16()).collect();
+        kinds.sort_unstable();
+        kinds.dedup();
+        tags.extend(kinds.into_iter().map(|k| vec!["k".to_string(), k.to_string()]));
+        tags.push(vec![schema::TAG_TYPE.to_string(), format!("{:?}", LamportType::Delete)]);
+
         Self {
             pubkey,
-            kind: Kind::Custom(1),
-            tags: vec![
-                vec!["LamportID".to_string(), lamport_id.to_string()],
-                vec!["vote_id".to_string(), vote_id.to_string()],
-                vec!["title".to_string(), title.to_string()],
-                vec!["content".to_string(), content.to_string()],
-                vec!["start_time".to_string(), start_time.to_string()],
-                vec!["end_time".to_string(), end_time.to_string()],
-                vec!["options".to_string(), options.to_string()],
-                vec!["sig".to_string(), sig.to_string()],
-            ],
-            content: format!("{} Vote {}, Title:{}", lamport_id, vote_id, title),
-            lamport_type: Some(LamportType::Vote),
+            kind: Kind::EventDeletion,
+            tags,
+            content: "account deleted".to_string(),
+            lamport_type: Some(LamportType::Delete),
         }
     }
-
 }
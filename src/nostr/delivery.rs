@@ -0,0 +1,184 @@
+use super::{LamportBinding, NostrClient};
+use crate::{
+    common::{
+        config::NostrDeliveryConfig,
+        consts,
+        error::{AppError, AppResult},
+    },
+    database::Storage,
+    queue::msg_queue::RedisStreamPool,
+};
+use nostr_sdk::prelude::*;
+use serde::{Deserialize, Serialize};
+
+//when a relay that failed attempts times is tried again, None gives up on it
+pub fn next_retry_at(
+    attempts: i32,
+    config: &NostrDeliveryConfig,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Option<chrono::DateTime<chrono::Utc>> {
+    if attempts >= config.max_attempts {
+        return None;
+    }
+    let factor = 1u64.checked_shl(attempts.max(1) as u32 - 1).unwrap_or(u64::MAX);
+    let secs = config.backoff_base.saturating_mul(factor).min(config.backoff_max);
+
+    Some(now + chrono::Duration::seconds(secs as i64))
+}
+
+async fn record(
+    store: &Storage,
+    config: &NostrDeliveryConfig,
+    event_id: &str,
+    relay: &str,
+    attempts: i32,
+    result: &Result<(), String>,
+) -> AppResult<()> {
+    if let Err(e) = result {
+        tracing::warn!("nostr event {} not accepted by {}: {}", event_id, relay, e);
+    }
+    let retry_at = next_retry_at(attempts, config, chrono::Utc::now());
+    store.save_delivery(event_id, relay, result, retry_at).await?;
+
+    Ok(())
+}
+
+/// A message on the nostr queue.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum NostrJob {
+    //signed and stored by sign_and_queue, only delivery is left
+    Signed { event_id: String },
+    //queued before events were signed up front
+    Unsigned(LamportBinding),
+}
+
+//p tags users with a linked nostr key, signs the event and stores it linked to object (type, id)
+pub async fn sign_event(
+    store: &Storage,
+    nclient: &NostrClient,
+    mut msg: LamportBinding,
+    object: Option<(&str, &str)>,
+) -> AppResult<Event> {
+    match store.get_nostr_pubkeys(msg.tagged_lamport_ids()).await {
+        Ok(pubkeys) => msg.add_p_tags(pubkeys),
+        Err(e) => tracing::error!("Failed to get nostr keys: {:?}", e),
+    }
+
+    //kind 5 deletions carry no LamportID tag, they are linked to their user instead
+    let lamport_id = match object {
+        Some((consts::NOSTR_OBJECT_USER, id)) => Some(id.to_string()),
+        _ => msg.lamport_id(),
+    };
+    let event = nclient.sign(msg.try_into()?).await?;
+    tracing::info!("Signed event: {:?}", event);
+    store
+        .save_nostr_event(
+            &event,
+            lamport_id,
+            object.map(|(t, id)| (t.to_string(), id.to_string())),
+        )
+        .await?;
+
+    Ok(event)
+}
+
+//signs now so the caller can return the event id, relays get the event from the nostr queue
+pub async fn sign_and_queue(
+    store: &Storage,
+    nclient: &NostrClient,
+    queue: &RedisStreamPool,
+    msg: LamportBinding,
+    object: Option<(&str, &str)>,
+) -> AppResult<String> {
+    let event = sign_event(store, nclient, msg, object).await?;
+    let event_id = event.id.to_hex();
+    queue
+        .add_queue_req_ex(consts::NOSTR_TOPIC, NostrJob::Signed { event_id: event_id.clone() })
+        .await?;
+
+    Ok(event_id)
+}
+
+//sends a signed event to every write relay, recording each relay's result, returns how many took it
+pub async fn deliver(store: &Storage, nclient: &NostrClient, config: &NostrDeliveryConfig, event: &Event) -> AppResult<usize> {
+    let event_id = event.id.to_hex();
+    let mut delivered = 0;
+    for (relay, result) in nclient.publish_to(event, &nclient.write_relays).await {
+        if result.is_ok() {
+            delivered += 1;
+        }
+        record(store, config, event_id.as_str(), relay.as_str(), 1, &result).await?;
+    }
+
+    Ok(delivered)
+}
+
+pub async fn process(
+    store: &Storage,
+    nclient: &NostrClient,
+    config: &NostrDeliveryConfig,
+    job: NostrJob,
+) -> AppResult<Event> {
+    let event = match job {
+        NostrJob::Signed { event_id } => match store.get_nostr_event(event_id.as_str()).await? {
+            Some(stored) => Event::from_json(stored.raw.as_str())?,
+            None => return Err(AppError::CustomError(format!("nostr event {} is not stored", event_id))),
+        },
+        NostrJob::Unsigned(msg) => sign_event(store, nclient, msg, None).await?,
+    };
+    deliver(store, nclient, config, &event).await?;
+
+    Ok(event)
+}
+
+//sends stored events again to the relays that failed them, and to every write relay when they
+//were never sent, returns how many deliveries got through
+pub async fn retry_failed(store: &Storage, nclient: &NostrClient, config: &NostrDeliveryConfig, limit: u64) -> AppResult<usize> {
+    let mut delivered = 0;
+    //events stored moments ago are still in the queue
+    let stored_before = chrono::Utc::now() - chrono::Duration::seconds(consts::NOSTR_UNQUEUED_GRACE);
+    for stored in store.get_unattempted_events(stored_before, limit).await? {
+        let event = Event::from_json(stored.raw.as_str())?;
+        delivered += deliver(store, nclient, config, &event).await?;
+    }
+
+    for delivery in store.get_due_deliveries(limit).await? {
+        let Some(stored) = store.get_nostr_event(delivery.event_id.as_str()).await? else {
+            continue;
+        };
+        let event = Event::from_json(stored.raw.as_str())?;
+
+        for (relay, result) in nclient.publish_to(&event, std::slice::from_ref(&delivery.relay)).await {
+            if result.is_ok() {
+                delivered += 1;
+            }
+            record(store, config, delivery.event_id.as_str(), relay.as_str(), delivery.attempts + 1, &result).await?;
+        }
+    }
+
+    Ok(delivered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_retry_at() {
+        let config = NostrDeliveryConfig {
+            retry_interval: 60,
+            backoff_base: 30,
+            backoff_max: 3600,
+            max_attempts: 10,
+        };
+        let now = chrono::Utc::now();
+        let wait = |attempts| next_retry_at(attempts, &config, now).map(|t| (t - now).num_seconds());
+
+        assert_eq!(wait(1), Some(30));
+        assert_eq!(wait(2), Some(60));
+        assert_eq!(wait(5), Some(480));
+        assert_eq!(wait(8), Some(3600));
+        assert_eq!(wait(10), None);
+    }
+}
@@ -0,0 +1,333 @@
+use super::schema::{self, BindEvent, CreateEvent, LamportEvent, UnbindEvent, VoteEvent};
+use crate::{
+    common::{
+        config::ScoringConfig,
+        consts,
+        error::{AppError, AppResult},
+    },
+    database::Storage,
+    server::vote::vote_service,
+};
+use nostr_sdk::prelude::*;
+use sea_orm::{sqlx, DbErr, RuntimeErr};
+use std::collections::HashSet;
+
+//provider recorded for remote wallet bindings
+pub const REMOTE_ADDRESS_PROVIDER: &str = "address";
+
+/// What a relay event means for us, decided before touching the database.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Incoming {
+    //a vote signed by the voter's own nostr key, the key is checked against the database
+    Vote(VoteEvent),
+    //bindings published by a trusted lamport id server
+    RemoteCreate(CreateEvent),
+    RemoteBind(BindEvent),
+    RemoteUnbind(UnbindEvent),
+    Ignored(&'static str),
+}
+
+/// Reads Lamport events published by others and reconciles them into the database.
+#[derive(Debug, Clone)]
+pub struct Ingestor {
+    //our server key, our own events are already in the database
+    own: PublicKey,
+    trusted: HashSet<PublicKey>,
+}
+
+impl Ingestor {
+    pub fn new(own: PublicKey, trusted_servers: &[String]) -> AppResult<Self> {
+        let trusted = trusted_servers
+            .iter()
+            .map(|k| PublicKey::parse(k))
+            .collect::<Result<HashSet<_>, _>>()?;
+
+        Ok(Self { own, trusted })
+    }
+
+    pub fn filter(&self, since: Option<Timestamp>) -> Filter {
+        let filter = Filter::new().kinds([
+            Kind::from(schema::KIND_CREATE),
+            Kind::from(schema::KIND_BIND),
+            Kind::from(schema::KIND_UNBIND),
+            Kind::from(schema::KIND_VOTE),
+        ]);
+        match since {
+            Some(since) => filter.since(since),
+            None => filter,
+        }
+    }
+
+    pub fn classify(&self, event: &Event) -> AppResult<Incoming> {
+        event.verify().map_err(|_| AppError::InvalidSignature)?;
+
+        if event.pubkey == self.own {
+            return Ok(Incoming::Ignored("own event"));
+        }
+
+        let kind = event.kind.as_u16();
+        if kind == schema::KIND_VOTE {
+            return Ok(Incoming::Vote(VoteEvent::from_event(event)?));
+        }
+        if !self.trusted.contains(&event.pubkey) {
+            return Ok(Incoming::Ignored("untrusted author"));
+        }
+
+        Ok(match kind {
+            schema::KIND_CREATE => Incoming::RemoteCreate(CreateEvent::from_event(event)?),
+            schema::KIND_BIND => Incoming::RemoteBind(BindEvent::from_event(event)?),
+            schema::KIND_UNBIND => Incoming::RemoteUnbind(UnbindEvent::from_event(event)?),
+            _ => Incoming::Ignored("unsupported kind"),
+        })
+    }
+
+    //applies one event, the returned outcome is recorded with the event id so relays repeating it are skipped
+    pub async fn ingest(&self, store: &Storage, scoring: &ScoringConfig, event: &Event) -> AppResult<Option<String>> {
+        let event_id = event.id.to_hex();
+        if event.pubkey == self.own || store.is_nostr_event_ingested(event_id.as_str()).await? {
+            return Ok(None);
+        }
+
+        let (lamport_id, outcome) = match self.classify(event) {
+            Ok(incoming) => {
+                let lamport_id = match &incoming {
+                    Incoming::Vote(v) => Some(v.lamport_id.clone()),
+                    Incoming::RemoteCreate(c) => Some(c.lamport_id.clone()),
+                    Incoming::RemoteBind(b) => Some(b.lamport_id.clone()),
+                    Incoming::RemoteUnbind(u) => Some(u.lamport_id.clone()),
+                    Incoming::Ignored(_) => None,
+                };
+                //transient failures are not recorded so the event is applied when a relay sends it again
+                let outcome = match reconcile(store, scoring, event, incoming).await {
+                    Ok(outcome) => outcome,
+                    Err(e) if is_transient(&e) => return Err(e),
+                    Err(e) => format!("rejected: {}", e),
+                };
+                (lamport_id, outcome)
+            }
+            Err(e) => (None, format!("invalid: {}", e)),
+        };
+
+        store
+            .record_ingested_event(
+                event_id,
+                event.pubkey.to_hex(),
+                event.kind.as_u16(),
+                lamport_id,
+                outcome.clone(),
+                chrono::DateTime::from_timestamp(event.created_at.as_u64() as i64, 0).unwrap_or_default(),
+            )
+            .await?;
+
+        Ok(Some(outcome))
+    }
+
+    //subscribes to relays until the connection pool shuts down, resuming from the newest ingested event
+    pub async fn run(&self, relays: &[String], store: &Storage, scoring: &ScoringConfig) -> AppResult<()> {
+        let client = Client::default();
+        for relay in relays {
+            client.add_relay(relay.as_str()).await?;
+        }
+        client.connect().await;
+
+        //events are not delivered in created_at order, so resume a little before the newest one
+        let since = store.get_last_ingested_at().await?.map(|t| {
+            Timestamp::from((t.timestamp() as u64).saturating_sub(consts::NOSTR_INGEST_SINCE_MARGIN))
+        });
+        let mut notifications = client.notifications();
+        client.subscribe(vec![self.filter(since)], None).await?;
+
+        loop {
+            match notifications.recv().await {
+                Ok(RelayPoolNotification::Event { event, .. }) => match self.ingest(store, scoring, &event).await {
+                    Ok(Some(outcome)) => tracing::info!("ingested nostr event {}: {}", event.id, outcome),
+                    Ok(None) => {}
+                    Err(e) => tracing::error!("Failed to ingest nostr event {}: {:?}", event.id, e),
+                },
+                Ok(RelayPoolNotification::Shutdown) => return Ok(()),
+                Ok(_) => {}
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
+                    tracing::warn!("nostr ingest lagged, {} notifications dropped", n)
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => return Ok(()),
+            }
+        }
+    }
+}
+
+//connection, pool and timeout failures, the event itself may be fine
+fn is_transient(e: &AppError) -> bool {
+    match e {
+        AppError::SeaOrmDBError(e) => is_transient_db(e),
+        AppError::RedisError(e) => e.is_io_error() || e.is_timeout() || e.is_connection_dropped() || e.is_connection_refusal(),
+        AppError::RequestError(_)
+        | AppError::IoError(_)
+        | AppError::NostrSdkClientError(_)
+        | AppError::NostrSdkDBError(_) => true,
+        _ => false,
+    }
+}
+
+//constraint violations and bad rows fail the same way on every retry
+fn is_transient_db(e: &DbErr) -> bool {
+    match e {
+        DbErr::ConnectionAcquire(_) | DbErr::Conn(_) => true,
+        DbErr::Exec(RuntimeErr::SqlxError(e)) | DbErr::Query(RuntimeErr::SqlxError(e)) => matches!(
+            e,
+            sqlx::Error::Io(_)
+                | sqlx::Error::Tls(_)
+                | sqlx::Error::Protocol(_)
+                | sqlx::Error::PoolTimedOut
+                | sqlx::Error::PoolClosed
+                | sqlx::Error::WorkerCrashed
+        ),
+        _ => false,
+    }
+}
+
+async fn reconcile(store: &Storage, scoring: &ScoringConfig, event: &Event, incoming: Incoming) -> AppResult<String> {
+    let server = event.pubkey.to_hex();
+    let created_at = chrono::DateTime::from_timestamp(event.created_at.as_u64() as i64, 0).unwrap_or_default();
+
+    match incoming {
+        Incoming::Vote(v) => {
+            //the author must be the nostr key linked to the voting lamport id
+            let voter = store.get_user_by_nostr_pubkey(event.pubkey.to_hex().as_str()).await?;
+            if voter.lamport_id != v.lamport_id {
+                return Err(AppError::Forbidden("nostr key is not linked to the lamport id".into()));
+            }
+            let proposal = store.get_proposal_by_proposal_id(v.proposal_id.as_str()).await?;
+            let vote = vote_service::cast_vote(
+                store,
+                scoring,
+                voter.lamport_id.as_str(),
+                &proposal,
+                v.choice.as_str(),
+                consts::VOTE_CHANNEL_NOSTR,
+            )
+            .await?;
+            Ok(format!("vote {}", vote.uid))
+        }
+        Incoming::RemoteCreate(c) => {
+            store
+                .save_remote_binding(server, event.id.to_hex(), c.lamport_id, c.provider, c.account, created_at)
+                .await?;
+            Ok("remote binding".to_string())
+        }
+        Incoming::RemoteBind(b) => {
+            store
+                .save_remote_binding(
+                    server,
+                    event.id.to_hex(),
+                    b.lamport_id,
+                    REMOTE_ADDRESS_PROVIDER.to_string(),
+                    b.address,
+                    created_at,
+                )
+                .await?;
+            Ok("remote address binding".to_string())
+        }
+        Incoming::RemoteUnbind(u) => {
+            let n = store
+                .delete_remote_binding(server.as_str(), u.lamport_id.as_str(), u.provider.as_str(), u.account.as_str())
+                .await?;
+            Ok(format!("remote unbinding, {} removed", n))
+        }
+        Incoming::Ignored(reason) => Ok(format!("ignored: {}", reason)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signed<T: LamportEvent>(payload: &T, keys: &Keys) -> Event {
+        let unsigned: UnsignedEvent = payload.build(keys.public_key()).try_into().unwrap();
+        unsigned.sign_with_keys(keys).unwrap()
+    }
+
+    #[test]
+    fn test_classify() {
+        let own = Keys::generate();
+        let trusted = Keys::generate();
+        let stranger = Keys::generate();
+        let ingestor = Ingestor::new(own.public_key(), &[trusted.public_key().to_bech32().unwrap()]).unwrap();
+
+        let vote = VoteEvent {
+            lamport_id: "1001".to_string(),
+            vote_id: "v1".to_string(),
+            proposal_id: "p1".to_string(),
+            choice: "yes".to_string(),
+            title: "Fund the docs".to_string(),
+            options: vec!["yes".to_string(), "no".to_string()],
+            start_time: "".to_string(),
+            end_time: "".to_string(),
+            sig: "".to_string(),
+        };
+        assert_eq!(ingestor.classify(&signed(&vote, &stranger)).unwrap(), Incoming::Vote(vote.clone()));
+        assert_eq!(ingestor.classify(&signed(&vote, &own)).unwrap(), Incoming::Ignored("own event"));
+
+        let create = CreateEvent {
+            lamport_id: "2001".to_string(),
+            provider: "twitter".to_string(),
+            account: "alice".to_string(),
+        };
+        assert_eq!(
+            ingestor.classify(&signed(&create, &trusted)).unwrap(),
+            Incoming::RemoteCreate(create.clone())
+        );
+        assert_eq!(
+            ingestor.classify(&signed(&create, &stranger)).unwrap(),
+            Incoming::Ignored("untrusted author")
+        );
+    }
+
+    #[test]
+    fn test_classify_rejects_forged_events() {
+        let own = Keys::generate();
+        let voter = Keys::generate();
+        let ingestor = Ingestor::new(own.public_key(), &[]).unwrap();
+
+        let vote = VoteEvent {
+            lamport_id: "1001".to_string(),
+            vote_id: "v1".to_string(),
+            proposal_id: "p1".to_string(),
+            choice: "yes".to_string(),
+            title: "".to_string(),
+            options: vec![],
+            start_time: "".to_string(),
+            end_time: "".to_string(),
+            sig: "".to_string(),
+        };
+        //the choice is changed after signing
+        let mut json: serde_json::Value = serde_json::from_str(&signed(&vote, &voter).as_json()).unwrap();
+        let tags = json["tags"].as_array_mut().unwrap();
+        for tag in tags.iter_mut() {
+            if tag[0] == "choice" {
+                tag[1] = serde_json::Value::String("no".to_string());
+            }
+        }
+        let forged = Event::from_json(json.to_string()).unwrap();
+
+        assert!(matches!(ingestor.classify(&forged), Err(AppError::InvalidSignature)));
+    }
+
+    #[test]
+    fn test_is_transient() {
+        assert!(is_transient(&AppError::SeaOrmDBError(DbErr::Conn(RuntimeErr::Internal(
+            "connection reset".into()
+        )))));
+        assert!(is_transient(&AppError::SeaOrmDBError(DbErr::Exec(RuntimeErr::SqlxError(
+            sqlx::Error::PoolTimedOut
+        )))));
+        assert!(!is_transient(&AppError::SeaOrmDBError(DbErr::Exec(RuntimeErr::SqlxError(
+            sqlx::Error::RowNotFound
+        )))));
+        assert!(!is_transient(&AppError::SeaOrmDBError(DbErr::Custom("bad row".into()))));
+        assert!(!is_transient(&AppError::UnknownError));
+        assert!(is_transient(&AppError::RequestError("timeout".into())));
+        assert!(!is_transient(&AppError::Forbidden("banned".into())));
+        assert!(!is_transient(&AppError::InputValidateError("proposal not active".into())));
+        assert!(!is_transient(&AppError::InvalidSignature));
+    }
+}
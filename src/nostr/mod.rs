@@ -1,3 +1,8 @@
 mod client;
+pub mod delivery;
+pub mod ingest;
+pub mod relay;
+pub mod schema;
 
 pub use client::*;
+pub use schema::LamportEvent;
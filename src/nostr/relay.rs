@@ -0,0 +1,474 @@
+use super::schema::{
+    self, AttestEvent, BindEvent, CreateEvent, InviteEvent, LamportEvent, ProposalEvent, RevokeEvent, UnbindEvent,
+    VoteEvent,
+};
+use crate::{
+    common::{
+        config::NostrRelayConfig,
+        consts,
+        error::{AppError, AppResult},
+    },
+    database::Storage,
+};
+use async_trait::async_trait;
+use nostr_sdk::prelude::*;
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+    time::{Duration, Instant},
+};
+use tokio::sync::broadcast;
+
+/// Where the embedded relay reads and keeps events.
+#[async_trait]
+pub trait RelayStore: Send + Sync {
+    //stored events matching filter, newest first, at most limit
+    async fn query(&self, filter: &Filter, limit: usize) -> AppResult<Vec<Event>>;
+    async fn has_event(&self, id: &EventId) -> AppResult<bool>;
+    //keeps an event written by someone else, apart from the events we signed
+    async fn save_event(&self, event: &Event) -> AppResult<()>;
+}
+
+//reads pages of raw events until limit of them match filter or NOSTR_RELAY_MAX_SCAN rows were read
+async fn scan<F, Fut>(filter: &Filter, limit: usize, page: F) -> AppResult<Vec<Event>>
+where
+    F: Fn(u64) -> Fut,
+    Fut: Future<Output = AppResult<Vec<String>>>,
+{
+    let mut events = Vec::new();
+    let mut offset = 0;
+    while events.len() < limit && offset < consts::NOSTR_RELAY_MAX_SCAN {
+        let rows = page(offset).await?;
+        if rows.is_empty() {
+            break;
+        }
+        offset += rows.len() as u64;
+
+        //sql has matched everything but search, the signed event is checked anyway
+        for raw in rows {
+            let event = Event::from_json(raw.as_str())?;
+            if filter.match_event(&event) {
+                events.push(event);
+                if events.len() == limit {
+                    break;
+                }
+            }
+        }
+    }
+
+    Ok(events)
+}
+
+#[async_trait]
+impl RelayStore for Storage {
+    async fn query(&self, filter: &Filter, limit: usize) -> AppResult<Vec<Event>> {
+        let mut events = scan(filter, limit, |offset| async move {
+            let page = self.get_nostr_events_page(filter, offset, consts::NOSTR_RELAY_BATCH).await?;
+            Ok(page.into_iter().map(|e| e.raw).collect())
+        })
+        .await?;
+        let written = scan(filter, limit, |offset| async move {
+            let page = self.get_relay_events_page(filter, offset, consts::NOSTR_RELAY_BATCH).await?;
+            Ok(page.into_iter().map(|e| e.raw).collect())
+        })
+        .await?;
+
+        //the newest of both tables
+        events.extend(written);
+        events.sort_by_key(|e| std::cmp::Reverse(e.created_at));
+        events.truncate(limit);
+
+        Ok(events)
+    }
+
+    async fn has_event(&self, id: &EventId) -> AppResult<bool> {
+        let id = id.to_hex();
+        Ok(self.get_nostr_event(id.as_str()).await?.is_some() || self.get_relay_event(id.as_str()).await?.is_some())
+    }
+
+    async fn save_event(&self, event: &Event) -> AppResult<()> {
+        self.save_relay_event(event).await
+    }
+}
+
+/// NIP-01 relay over our signed events and the lamport events others write to it,
+/// for deployments without an external relay.
+#[derive(Debug, Clone)]
+pub struct EmbeddedRelay {
+    config: NostrRelayConfig,
+    //events stored after a connection opened, pushed to its live subscriptions
+    live: broadcast::Sender<Event>,
+}
+
+//messages of one type a connection sent in the current minute
+#[derive(Debug)]
+struct RateWindow {
+    max: u32,
+    started: Instant,
+    count: u32,
+}
+
+impl RateWindow {
+    fn new(max: u32) -> Self {
+        Self {
+            max,
+            started: Instant::now(),
+            count: 0,
+        }
+    }
+
+    //false once max messages were counted this minute
+    fn allow(&mut self, now: Instant) -> bool {
+        if now.duration_since(self.started) >= Duration::from_secs(60) {
+            self.started = now;
+            self.count = 0;
+        }
+        if self.count >= self.max {
+            return false;
+        }
+        self.count += 1;
+
+        true
+    }
+}
+
+/// Subscriptions of one connection.
+#[derive(Debug)]
+pub struct Session {
+    subscriptions: HashMap<SubscriptionId, Vec<Filter>>,
+    max_subscriptions: usize,
+    reqs: RateWindow,
+    events: RateWindow,
+    pub live: broadcast::Receiver<Event>,
+}
+
+impl Session {
+    //a REQ with an open id replaces its filters
+    pub fn subscribe(&mut self, id: SubscriptionId, filters: Vec<Filter>) -> Result<(), String> {
+        if !self.subscriptions.contains_key(&id) && self.subscriptions.len() >= self.max_subscriptions {
+            return Err(format!("error: at most {} subscriptions", self.max_subscriptions));
+        }
+        self.subscriptions.insert(id, filters);
+
+        Ok(())
+    }
+
+    pub fn close(&mut self, id: &SubscriptionId) {
+        self.subscriptions.remove(id);
+    }
+
+    //EVENT messages for the subscriptions a new event matches
+    pub fn matching(&self, event: &Event) -> Vec<RelayMessage> {
+        self.subscriptions
+            .iter()
+            .filter(|(_, filters)| filters.iter().any(|f| f.match_event(event)))
+            .map(|(id, _)| RelayMessage::event(id.clone(), event.clone()))
+            .collect()
+    }
+}
+
+impl EmbeddedRelay {
+    pub fn new(config: &NostrRelayConfig) -> Self {
+        let (live, _) = broadcast::channel(consts::NOSTR_RELAY_CHANNEL);
+
+        Self {
+            config: config.clone(),
+            live,
+        }
+    }
+
+    pub fn max_message_size(&self) -> usize {
+        self.config.max_message_size
+    }
+
+    pub fn session(&self) -> Session {
+        Session {
+            subscriptions: HashMap::new(),
+            max_subscriptions: self.config.max_subscriptions,
+            reqs: RateWindow::new(self.config.max_reqs_per_minute),
+            events: RateWindow::new(self.config.max_events_per_minute),
+            live: self.live.subscribe(),
+        }
+    }
+
+    //hands an event to open connections, nothing happens when none is listening
+    pub fn notify(&self, event: &Event) {
+        let _ = self.live.send(event.clone());
+    }
+
+    //answers one client message, replies are sent in order
+    pub async fn handle(&self, store: &dyn RelayStore, session: &mut Session, text: &str) -> Vec<RelayMessage> {
+        if text.len() > self.config.max_message_size {
+            return vec![RelayMessage::notice(format!(
+                "error: messages are limited to {} bytes",
+                self.config.max_message_size
+            ))];
+        }
+        let msg = match ClientMessage::from_json(text) {
+            Ok(msg) => msg,
+            Err(e) => return vec![RelayMessage::notice(format!("error: {}", e))],
+        };
+
+        match msg {
+            ClientMessage::Req { subscription_id, filters } => {
+                if !session.reqs.allow(Instant::now()) {
+                    return vec![RelayMessage::closed(subscription_id, "rate-limited: too many requests")];
+                }
+                if let Err(e) = session.subscribe(subscription_id.clone(), filters.clone()) {
+                    return vec![RelayMessage::closed(subscription_id, e)];
+                }
+
+                let mut replies = Vec::new();
+                let mut sent = HashSet::new();
+                for filter in filters.iter() {
+                    let limit = filter.limit.unwrap_or(self.config.max_limit).min(self.config.max_limit);
+                    match store.query(filter, limit).await {
+                        Ok(events) => {
+                            for event in events.into_iter().filter(|e| sent.insert(e.id)) {
+                                replies.push(RelayMessage::event(subscription_id.clone(), event));
+                            }
+                        }
+                        Err(e) => {
+                            tracing::error!("relay query {:?} err: {:?}", filter, e);
+                            session.close(&subscription_id);
+                            return vec![RelayMessage::closed(subscription_id, "error: could not read events")];
+                        }
+                    }
+                }
+                replies.push(RelayMessage::eose(subscription_id));
+                replies
+            }
+            ClientMessage::Close(subscription_id) => {
+                session.close(&subscription_id);
+                vec![RelayMessage::closed(subscription_id, "")]
+            }
+            ClientMessage::Event(event) => {
+                if !session.events.allow(Instant::now()) {
+                    return vec![RelayMessage::ok(event.id, false, "rate-limited: too many events")];
+                }
+                vec![self.accept(store, *event).await]
+            }
+            _ => vec![RelayMessage::notice("error: unsupported message")],
+        }
+    }
+
+    //writes are kept only for lamport kinds that parse under the schema
+    async fn accept(&self, store: &dyn RelayStore, event: Event) -> RelayMessage {
+        if event.verify().is_err() {
+            return RelayMessage::ok(event.id, false, "invalid: bad signature");
+        }
+        if let Err(e) = check_event(&event) {
+            return RelayMessage::ok(event.id, false, format!("blocked: {}", e));
+        }
+
+        match store.has_event(&event.id).await {
+            Ok(true) => return RelayMessage::ok(event.id, true, "duplicate: already have this event"),
+            Ok(false) => {}
+            Err(e) => {
+                tracing::error!("relay read {} err: {:?}", event.id, e);
+                return RelayMessage::ok(event.id, false, "error: could not store event");
+            }
+        }
+
+        //events written by others are served, they are not part of a lamport id's history
+        if let Err(e) = store.save_event(&event).await {
+            tracing::error!("relay save {} err: {:?}", event.id, e);
+            return RelayMessage::ok(event.id, false, "error: could not store event");
+        }
+        self.notify(&event);
+
+        RelayMessage::ok(event.id, true, "")
+    }
+}
+
+//writes are limited to the current lamport kinds
+fn check_event(event: &Event) -> AppResult<()> {
+    match event.kind.as_u16() {
+        schema::KIND_CREATE => CreateEvent::from_event(event).map(|_| ()),
+        schema::KIND_BIND => BindEvent::from_event(event).map(|_| ()),
+        schema::KIND_INVITE => InviteEvent::from_event(event).map(|_| ()),
+        schema::KIND_ATTEST => AttestEvent::from_event(event).map(|_| ()),
+        schema::KIND_REVOKE => RevokeEvent::from_event(event).map(|_| ()),
+        schema::KIND_UNBIND => UnbindEvent::from_event(event).map(|_| ()),
+        schema::KIND_PROPOSAL => ProposalEvent::from_event(event).map(|_| ()),
+        schema::KIND_VOTE => VoteEvent::from_event(event).map(|_| ()),
+        //kind 1 votes are still read from relays, new ones must use the current kind
+        _ => Err(AppError::InvalidLamportType),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signed<T: LamportEvent>(payload: &T, keys: &Keys) -> Event {
+        let unsigned: UnsignedEvent = payload.build(keys.public_key()).try_into().unwrap();
+        unsigned.sign_with_keys(keys).unwrap()
+    }
+
+    #[test]
+    fn test_session_matching() {
+        let relay = EmbeddedRelay::new(&NostrRelayConfig {
+            enabled: true,
+            max_subscriptions: 2,
+            max_limit: 10,
+            ..Default::default()
+        });
+        let keys = Keys::generate();
+        let create = signed(
+            &CreateEvent {
+                lamport_id: "1001".to_string(),
+                provider: "twitter".to_string(),
+                account: "alice".to_string(),
+            },
+            &keys,
+        );
+
+        let mut session = relay.session();
+        let creates = SubscriptionId::new("creates");
+        let votes = SubscriptionId::new("votes");
+        session.subscribe(creates.clone(), vec![Filter::new().kind(Kind::from(schema::KIND_CREATE)).author(keys.public_key())]).unwrap();
+        session.subscribe(votes.clone(), vec![Filter::new().kind(Kind::from(schema::KIND_VOTE))]).unwrap();
+        assert_eq!(session.matching(&create), vec![RelayMessage::event(creates.clone(), create.clone())]);
+
+        //the limit counts open subscriptions, replacing one is allowed
+        assert!(session.subscribe(SubscriptionId::new("more"), vec![Filter::new()]).is_err());
+        session.subscribe(votes.clone(), vec![Filter::new()]).unwrap();
+        assert_eq!(session.matching(&create).len(), 2);
+
+        session.close(&creates);
+        session.close(&votes);
+        assert!(session.matching(&create).is_empty());
+    }
+
+    #[test]
+    fn test_check_event() {
+        let keys = Keys::generate();
+        let bind = signed(
+            &BindEvent {
+                lamport_id: "1001".to_string(),
+                address: "0xabc".to_string(),
+                sig: "".to_string(),
+            },
+            &keys,
+        );
+        assert!(check_event(&bind).is_ok());
+
+        let note = EventBuilder::text_note("hello").sign_with_keys(&keys).unwrap();
+        assert!(check_event(&note).is_err());
+
+        let legacy_vote = EventBuilder::new(Kind::from(schema::LEGACY_KIND_VOTE), "")
+            .tags([
+                Tag::parse(["LamportID", "1001"]).unwrap(),
+                Tag::parse(["vote_id", "v1"]).unwrap(),
+                Tag::parse(["options", "yes,no"]).unwrap(),
+                Tag::parse(["sig", ""]).unwrap(),
+            ])
+            .sign_with_keys(&keys)
+            .unwrap();
+        assert!(VoteEvent::from_event(&legacy_vote).is_ok());
+        assert!(check_event(&legacy_vote).is_err());
+    }
+
+    //events kept in memory, in the order they were saved
+    #[derive(Default)]
+    struct MemoryStore(std::sync::Mutex<Vec<Event>>);
+
+    #[async_trait]
+    impl RelayStore for MemoryStore {
+        async fn query(&self, filter: &Filter, limit: usize) -> AppResult<Vec<Event>> {
+            let mut events: Vec<Event> = self.0.lock().unwrap().iter().filter(|e| filter.match_event(e)).cloned().collect();
+            events.sort_by_key(|e| std::cmp::Reverse(e.created_at));
+            events.truncate(limit);
+            Ok(events)
+        }
+
+        async fn has_event(&self, id: &EventId) -> AppResult<bool> {
+            Ok(self.0.lock().unwrap().iter().any(|e| e.id == *id))
+        }
+
+        async fn save_event(&self, event: &Event) -> AppResult<()> {
+            self.0.lock().unwrap().push(event.clone());
+            Ok(())
+        }
+    }
+
+    fn ok_message(replies: &[RelayMessage]) -> (bool, String) {
+        match replies {
+            [RelayMessage::Ok { status, message, .. }] => (*status, message.clone()),
+            _ => panic!("expected one OK, got {:?}", replies),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handle_round_trip() {
+        let relay = EmbeddedRelay::new(&NostrRelayConfig {
+            enabled: true,
+            max_subscriptions: 2,
+            max_limit: 10,
+            max_message_size: 4096,
+            max_events_per_minute: 3,
+            max_reqs_per_minute: 10,
+        });
+        let store = MemoryStore::default();
+        let mut session = relay.session();
+        let keys = Keys::generate();
+        let create = signed(
+            &CreateEvent {
+                lamport_id: "1001".to_string(),
+                provider: "twitter".to_string(),
+                account: "alice".to_string(),
+            },
+            &keys,
+        );
+
+        //EVENT is stored once and pushed to open connections
+        let event = ClientMessage::event(create.clone()).as_json();
+        assert_eq!(ok_message(&relay.handle(&store, &mut session, &event).await), (true, "".to_string()));
+        assert_eq!(session.live.recv().await.unwrap(), create);
+        let (status, message) = ok_message(&relay.handle(&store, &mut session, &event).await);
+        assert!(status && message.starts_with("duplicate:"));
+        let note = EventBuilder::text_note("hello").sign_with_keys(&keys).unwrap();
+        let (status, message) = ok_message(&relay.handle(&store, &mut session, &ClientMessage::event(note.clone()).as_json()).await);
+        assert!(!status && message.starts_with("blocked:"));
+        assert_eq!(store.0.lock().unwrap().len(), 1);
+
+        //REQ answers from the store, then stays open for new events
+        let creates = SubscriptionId::new("creates");
+        let req = ClientMessage::req(creates.clone(), vec![Filter::new().kind(Kind::from(schema::KIND_CREATE))]);
+        assert_eq!(
+            relay.handle(&store, &mut session, &req.as_json()).await,
+            vec![RelayMessage::event(creates.clone(), create.clone()), RelayMessage::eose(creates.clone())]
+        );
+        let other = SubscriptionId::new("other");
+        let req = ClientMessage::req(other.clone(), vec![Filter::new().author(Keys::generate().public_key())]);
+        assert_eq!(relay.handle(&store, &mut session, &req.as_json()).await, vec![RelayMessage::eose(other.clone())]);
+        assert_eq!(session.matching(&create), vec![RelayMessage::event(creates.clone(), create.clone())]);
+
+        //CLOSE ends the subscription
+        assert_eq!(
+            relay.handle(&store, &mut session, &ClientMessage::close(creates.clone()).as_json()).await,
+            vec![RelayMessage::closed(creates, "")]
+        );
+        assert!(session.matching(&create).is_empty());
+
+        //a fourth EVENT within the minute and oversized messages are refused
+        let (status, message) = ok_message(&relay.handle(&store, &mut session, &event).await);
+        assert!(!status && message.starts_with("rate-limited:"));
+        let big = ClientMessage::event(EventBuilder::text_note("x".repeat(5000)).sign_with_keys(&keys).unwrap());
+        assert!(matches!(
+            relay.handle(&store, &mut session, &big.as_json()).await.as_slice(),
+            [RelayMessage::Notice(_)]
+        ));
+    }
+
+    #[test]
+    fn test_rate_window() {
+        let mut window = RateWindow::new(2);
+        let start = Instant::now();
+        assert!(window.allow(start));
+        assert!(window.allow(start));
+        assert!(!window.allow(start + Duration::from_secs(59)));
+        assert!(window.allow(start + Duration::from_secs(61)));
+    }
+}
@@ -0,0 +1,70 @@
+use super::*;
+
+/// Kind 2324, a Lamport ID vouching for another one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttestEvent {
+    pub attester: String,
+    pub subject: String,
+    pub address: String,
+    pub sig: String,
+}
+
+impl LamportEvent for AttestEvent {
+    const KIND: u16 = KIND_ATTEST;
+    const TYPE: LamportType = LamportType::Attest;
+
+    fn tags(&self) -> Vec<Vec<String>> {
+        vec![
+            vec![TAG_LAMPORT_ID.to_string(), self.attester.clone()],
+            vec![TAG_SUBJECT.to_string(), self.subject.clone()],
+            vec![TAG_ADDRESS.to_string(), self.address.clone()],
+            vec![TAG_SIG.to_string(), self.sig.clone()],
+        ]
+    }
+
+    fn content(&self) -> String {
+        format!("{} attests {}, address:{}", self.attester, self.subject, self.address)
+    }
+
+    fn from_parts(tags: &[Vec<String>], _content: &str, _version: u32) -> AppResult<Self> {
+        Ok(Self {
+            attester: required_tag(tags, TAG_LAMPORT_ID)?,
+            subject: required_tag(tags, TAG_SUBJECT)?,
+            address: required_tag(tags, TAG_ADDRESS)?,
+            sig: tag(tags, TAG_SIG).unwrap_or_default().to_string(),
+        })
+    }
+}
+
+/// Kind 2325, an attestation withdrawn by its attester or an admin.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RevokeEvent {
+    pub attester: String,
+    pub subject: String,
+    pub revoked_by: String,
+}
+
+impl LamportEvent for RevokeEvent {
+    const KIND: u16 = KIND_REVOKE;
+    const TYPE: LamportType = LamportType::Revoke;
+
+    fn tags(&self) -> Vec<Vec<String>> {
+        vec![
+            vec![TAG_LAMPORT_ID.to_string(), self.attester.clone()],
+            vec![TAG_SUBJECT.to_string(), self.subject.clone()],
+            vec!["RevokedBy".to_string(), self.revoked_by.clone()],
+        ]
+    }
+
+    fn content(&self) -> String {
+        format!("{} revokes attestation of {}", self.attester, self.subject)
+    }
+
+    fn from_parts(tags: &[Vec<String>], _content: &str, _version: u32) -> AppResult<Self> {
+        Ok(Self {
+            attester: required_tag(tags, TAG_LAMPORT_ID)?,
+            subject: required_tag(tags, TAG_SUBJECT)?,
+            revoked_by: required_tag(tags, "RevokedBy")?,
+        })
+    }
+}
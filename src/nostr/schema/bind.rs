@@ -0,0 +1,34 @@
+use super::*;
+
+/// Kind 2322, a wallet address bound to a Lamport ID.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BindEvent {
+    pub lamport_id: String,
+    pub address: String,
+    pub sig: String,
+}
+
+impl LamportEvent for BindEvent {
+    const KIND: u16 = KIND_BIND;
+    const TYPE: LamportType = LamportType::Bind;
+
+    fn tags(&self) -> Vec<Vec<String>> {
+        vec![
+            vec![TAG_LAMPORT_ID.to_string(), self.lamport_id.clone()],
+            vec![TAG_ADDRESS.to_string(), self.address.clone()],
+            vec![TAG_SIG.to_string(), self.sig.clone()],
+        ]
+    }
+
+    fn content(&self) -> String {
+        format!("LamportID:{} bind address:{}", self.lamport_id, self.address)
+    }
+
+    fn from_parts(tags: &[Vec<String>], _content: &str, _version: u32) -> AppResult<Self> {
+        Ok(Self {
+            lamport_id: required_tag(tags, TAG_LAMPORT_ID)?,
+            address: required_tag(tags, TAG_ADDRESS)?,
+            sig: tag(tags, TAG_SIG).unwrap_or_default().to_string(),
+        })
+    }
+}
@@ -0,0 +1,47 @@
+use super::*;
+use crate::common::consts;
+
+/// Kind 2321, a social account bound to a Lamport ID.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreateEvent {
+    pub lamport_id: String,
+    pub provider: String,
+    pub account: String,
+}
+
+impl LamportEvent for CreateEvent {
+    const KIND: u16 = KIND_CREATE;
+    const TYPE: LamportType = LamportType::Create;
+
+    //twitter bindings keep their Twitter tag for existing readers
+    fn tags(&self) -> Vec<Vec<String>> {
+        let mut tags = vec![
+            vec![TAG_LAMPORT_ID.to_string(), self.lamport_id.clone()],
+            vec!["provider".to_string(), self.provider.clone()],
+            vec!["account".to_string(), self.account.clone()],
+        ];
+        if self.provider == consts::PROVIDER_TWITTER {
+            tags.push(vec!["Twitter".to_string(), self.account.clone()]);
+        }
+        tags
+    }
+
+    fn content(&self) -> String {
+        format!("LamportID:{}", self.lamport_id)
+    }
+
+    //the first version 0 events only had the Twitter tag
+    fn from_parts(tags: &[Vec<String>], _content: &str, _version: u32) -> AppResult<Self> {
+        let (provider, account) = match (tag(tags, "provider"), tag(tags, "account"), tag(tags, "Twitter")) {
+            (Some(provider), Some(account), _) => (provider.to_string(), account.to_string()),
+            (None, None, Some(account)) => (consts::PROVIDER_TWITTER.to_string(), account.to_string()),
+            _ => return Err(AppError::InvalidNostrEvent("missing provider or account tag".into())),
+        };
+
+        Ok(Self {
+            lamport_id: required_tag(tags, TAG_LAMPORT_ID)?,
+            provider,
+            account,
+        })
+    }
+}
@@ -0,0 +1,47 @@
+use super::*;
+
+/// Kind 2323, an invitation sent by a Lamport ID.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InviteEvent {
+    pub lamport_id: String,
+    pub project: String,
+    pub invitee: String,
+    pub link: String,
+}
+
+impl LamportEvent for InviteEvent {
+    const KIND: u16 = KIND_INVITE;
+    const TYPE: LamportType = LamportType::Invite;
+
+    //version 0 put the project in a p tag, which clients read as a pubkey
+    fn tags(&self) -> Vec<Vec<String>> {
+        vec![
+            vec![TAG_LAMPORT_ID.to_string(), self.lamport_id.clone()],
+            vec!["project".to_string(), self.project.clone()],
+            vec!["Invitee".to_string(), self.invitee.clone()],
+            vec!["link".to_string(), self.link.clone()],
+        ]
+    }
+
+    fn content(&self) -> String {
+        format!("{} Invite {}, Link:{}", self.lamport_id, self.invitee, self.link)
+    }
+
+    fn from_parts(tags: &[Vec<String>], content: &str, version: u32) -> AppResult<Self> {
+        let (project, link) = if version == 0 {
+            (
+                required_tag(tags, "p")?,
+                content.rsplit_once("Link:").map(|(_, l)| l.to_string()).unwrap_or_default(),
+            )
+        } else {
+            (required_tag(tags, "project")?, required_tag(tags, "link")?)
+        };
+
+        Ok(Self {
+            lamport_id: required_tag(tags, TAG_LAMPORT_ID)?,
+            project,
+            invitee: required_tag(tags, "Invitee")?,
+            link,
+        })
+    }
+}
@@ -0,0 +1,296 @@
+//! Typed Lamport event kinds.
+//!
+//! Each kind has a struct that builds its tags and parses them back from a relay event.
+//! Events carry a `lamport_schema` tag with the tag layout version. Events published before
+//! the tag existed are read as version 0.
+
+mod attestation;
+mod bind;
+mod create;
+mod invite;
+mod proposal;
+mod unbind;
+mod vote;
+
+pub use attestation::*;
+pub use bind::*;
+pub use create::*;
+pub use invite::*;
+pub use proposal::*;
+pub use unbind::*;
+pub use vote::*;
+
+use super::{LamportBinding, LamportType};
+use crate::common::error::{AppError, AppResult};
+use nostr_sdk::prelude::{Event, Kind, PublicKey};
+
+pub const SCHEMA_VERSION: u32 = 1;
+
+pub const KIND_CREATE: u16 = 2321;
+pub const KIND_BIND: u16 = 2322;
+pub const KIND_INVITE: u16 = 2323;
+pub const KIND_ATTEST: u16 = 2324;
+pub const KIND_REVOKE: u16 = 2325;
+pub const KIND_UNBIND: u16 = 2326;
+pub const KIND_PROPOSAL: u16 = 2327;
+pub const KIND_VOTE: u16 = 2328;
+//votes were published as text notes before the schema was versioned
+pub const LEGACY_KIND_VOTE: u16 = 1;
+
+pub const TAG_SCHEMA: &str = "lamport_schema";
+pub const TAG_TYPE: &str = "lamport_type";
+//misspelled type tag of version 0 events
+pub const TAG_LEGACY_TYPE: &str = "lmport_type";
+pub const TAG_LAMPORT_ID: &str = "LamportID";
+pub const TAG_SUBJECT: &str = "Subject";
+pub const TAG_ADDRESS: &str = "Address";
+pub const TAG_SIG: &str = "sig";
+
+/// A Lamport event kind with a fixed tag layout.
+pub trait LamportEvent: Sized {
+    const KIND: u16;
+    const TYPE: LamportType;
+    //kinds version 0 events of this type were published under
+    const LEGACY_KINDS: &'static [u16] = &[];
+
+    //payload tags, the type and schema tags are added by build
+    fn tags(&self) -> Vec<Vec<String>>;
+
+    fn content(&self) -> String;
+
+    fn from_parts(tags: &[Vec<String>], content: &str, version: u32) -> AppResult<Self>;
+
+    fn build(&self, pubkey: PublicKey) -> LamportBinding {
+        let mut tags = self.tags();
+        tags.push(vec![TAG_TYPE.to_string(), format!("{:?}", Self::TYPE)]);
+        tags.push(vec![TAG_SCHEMA.to_string(), SCHEMA_VERSION.to_string()]);
+
+        LamportBinding {
+            pubkey,
+            kind: Kind::from(Self::KIND),
+            tags,
+            content: self.content(),
+            lamport_type: Some(Self::TYPE),
+        }
+    }
+
+    fn parse(kind: Kind, tags: &[Vec<String>], content: &str) -> AppResult<Self> {
+        let version = schema_version(tags)?;
+        let kind = kind.as_u16();
+        if kind != Self::KIND && !(version == 0 && Self::LEGACY_KINDS.contains(&kind)) {
+            return Err(AppError::InvalidLamportType);
+        }
+        if let Some(lamport_type) = lamport_type(tags)? {
+            if lamport_type != Self::TYPE {
+                return Err(AppError::InvalidLamportType);
+            }
+        }
+
+        Self::from_parts(tags, content, version)
+    }
+
+    fn from_binding(binding: &LamportBinding) -> AppResult<Self> {
+        Self::parse(binding.kind, &binding.tags, binding.content.as_str())
+    }
+
+    fn from_event(event: &Event) -> AppResult<Self> {
+        Self::parse(event.kind, &event_tags(event), event.content.as_str())
+    }
+}
+
+pub fn event_tags(event: &Event) -> Vec<Vec<String>> {
+    event.tags.iter().map(|t| t.as_slice().to_vec()).collect()
+}
+
+//value of the first tag named name
+pub fn tag<'a>(tags: &'a [Vec<String>], name: &str) -> Option<&'a str> {
+    tag_values(tags, name).and_then(|v| v.first()).map(|v| v.as_str())
+}
+
+//all values of the first tag named name
+pub fn tag_values<'a>(tags: &'a [Vec<String>], name: &str) -> Option<&'a [String]> {
+    tags.iter()
+        .find(|t| t.first().map(|n| n == name).unwrap_or(false))
+        .map(|t| &t[1..])
+}
+
+pub fn required_tag(tags: &[Vec<String>], name: &str) -> AppResult<String> {
+    tag(tags, name)
+        .map(|v| v.to_string())
+        .ok_or_else(|| AppError::InvalidNostrEvent(format!("missing {} tag", name)))
+}
+
+pub fn schema_version(tags: &[Vec<String>]) -> AppResult<u32> {
+    let version = match tag(tags, TAG_SCHEMA) {
+        Some(v) => v
+            .parse::<u32>()
+            .map_err(|_| AppError::InvalidNostrEvent(format!("bad schema version {}", v)))?,
+        None => 0,
+    };
+    if version > SCHEMA_VERSION {
+        return Err(AppError::InvalidNostrEvent(format!("unsupported schema version {}", version)));
+    }
+
+    Ok(version)
+}
+
+pub fn lamport_type(tags: &[Vec<String>]) -> AppResult<Option<LamportType>> {
+    tag(tags, TAG_TYPE)
+        .or_else(|| tag(tags, TAG_LEGACY_TYPE))
+        .map(|t| t.parse::<LamportType>())
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nostr_sdk::prelude::{Keys, UnsignedEvent};
+
+    //build, sign and parse back through a real event
+    fn round_trip<T: LamportEvent>(payload: &T, keys: &Keys) -> T {
+        let binding = payload.build(keys.public_key());
+        let unsigned: UnsignedEvent = binding.try_into().unwrap();
+        let event = unsigned.sign_with_keys(keys).unwrap();
+        assert_eq!(tag(&event_tags(&event), TAG_SCHEMA), Some("1"));
+
+        T::from_event(&event).unwrap()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let keys = Keys::generate();
+
+        let create = CreateEvent {
+            lamport_id: "1001".to_string(),
+            provider: "twitter".to_string(),
+            account: "alice".to_string(),
+        };
+        assert_eq!(round_trip(&create, &keys), create);
+
+        let bind = BindEvent {
+            lamport_id: "1001".to_string(),
+            address: "0xabc".to_string(),
+            sig: "0xsig".to_string(),
+        };
+        assert_eq!(round_trip(&bind, &keys), bind);
+
+        let invite = InviteEvent {
+            lamport_id: "1001".to_string(),
+            project: "acl".to_string(),
+            invitee: "bob@example.com".to_string(),
+            link: "https://example.com/invite/abc".to_string(),
+        };
+        assert_eq!(round_trip(&invite, &keys), invite);
+
+        let attest = AttestEvent {
+            attester: "1001".to_string(),
+            subject: "1002".to_string(),
+            address: "0xabc".to_string(),
+            sig: "0xsig".to_string(),
+        };
+        assert_eq!(round_trip(&attest, &keys), attest);
+
+        let revoke = RevokeEvent {
+            attester: "1001".to_string(),
+            subject: "1002".to_string(),
+            revoked_by: "1001".to_string(),
+        };
+        assert_eq!(round_trip(&revoke, &keys), revoke);
+
+        let unbind = UnbindEvent {
+            lamport_id: "1001".to_string(),
+            provider: "twitter".to_string(),
+            account: "alice".to_string(),
+            reason: "unbind".to_string(),
+        };
+        assert_eq!(round_trip(&unbind, &keys), unbind);
+
+        let proposal = ProposalEvent {
+            lamport_id: "1001".to_string(),
+            proposal_id: "p1".to_string(),
+            group_id: "g1".to_string(),
+            title: "Fund the docs".to_string(),
+            options: vec!["yes".to_string(), "no, later".to_string()],
+            start_time: "2025-03-01T00:00:00+00:00".to_string(),
+            end_time: "2025-03-08T00:00:00+00:00".to_string(),
+        };
+        assert_eq!(round_trip(&proposal, &keys), proposal);
+
+        let vote = VoteEvent {
+            lamport_id: "1001".to_string(),
+            vote_id: "v1".to_string(),
+            proposal_id: "p1".to_string(),
+            choice: "yes".to_string(),
+            title: "Fund the docs".to_string(),
+            options: vec!["yes".to_string(), "no, later".to_string()],
+            start_time: "2025-03-01T00:00:00+00:00".to_string(),
+            end_time: "2025-03-08T00:00:00+00:00".to_string(),
+            sig: "".to_string(),
+        };
+        assert_eq!(round_trip(&vote, &keys), vote);
+    }
+
+    #[test]
+    fn test_parse_rejects_other_kinds() {
+        let keys = Keys::generate();
+        let bind = BindEvent {
+            lamport_id: "1001".to_string(),
+            address: "0xabc".to_string(),
+            sig: "".to_string(),
+        }
+        .build(keys.public_key());
+
+        assert!(matches!(CreateEvent::from_binding(&bind), Err(AppError::InvalidLamportType)));
+
+        let mut future = bind.clone();
+        future.tags.retain(|t| t[0] != TAG_SCHEMA);
+        future.tags.push(vec![TAG_SCHEMA.to_string(), "99".to_string()]);
+        assert!(matches!(BindEvent::from_binding(&future), Err(AppError::InvalidNostrEvent(_))));
+    }
+
+    #[test]
+    fn test_parse_version_0() {
+        let pubkey = Keys::generate().public_key();
+        let tags = |tags: &[[&str; 2]]| -> Vec<Vec<String>> {
+            tags.iter().map(|t| t.iter().map(|v| v.to_string()).collect()).collect()
+        };
+
+        let invite = InviteEvent::parse(
+            Kind::from(KIND_INVITE),
+            &tags(&[
+                ["LamportID", "1001"],
+                ["p", "acl"],
+                ["Invitee", "bob@example.com"],
+                ["lmport_type", "Invite"],
+                ["i", "invite"],
+            ]),
+            "1001 Invite bob@example.com, Link:https://example.com/invite/abc",
+        )
+        .unwrap();
+        assert_eq!(invite.project, "acl");
+        assert_eq!(invite.link, "https://example.com/invite/abc");
+
+        let vote = VoteEvent::parse(
+            Kind::from(LEGACY_KIND_VOTE),
+            &tags(&[
+                ["LamportID", "1001"],
+                ["vote_id", "v1"],
+                ["title", "Fund the docs"],
+                ["content", "1001 vote for Fund the docs"],
+                ["start_time", "2025-03-01"],
+                ["end_time", "2025-03-08"],
+                ["options", "yes,no"],
+                ["sig", ""],
+            ]),
+            "",
+        )
+        .unwrap();
+        assert_eq!(vote.options, vec!["yes".to_string(), "no".to_string()]);
+        assert_eq!(vote.proposal_id, "");
+
+        //kind 1 is only accepted from unversioned events
+        let mut versioned = vote.build(pubkey);
+        versioned.kind = Kind::from(LEGACY_KIND_VOTE);
+        assert!(VoteEvent::from_binding(&versioned).is_err());
+    }
+}
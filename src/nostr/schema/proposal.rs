@@ -0,0 +1,50 @@
+use super::*;
+
+/// Kind 2327, a proposal opened in a group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProposalEvent {
+    pub lamport_id: String,
+    pub proposal_id: String,
+    pub group_id: String,
+    pub title: String,
+    pub options: Vec<String>,
+    pub start_time: String,
+    pub end_time: String,
+}
+
+impl LamportEvent for ProposalEvent {
+    const KIND: u16 = KIND_PROPOSAL;
+    const TYPE: LamportType = LamportType::Proposal;
+
+    //options are the values of one tag so they may contain commas
+    fn tags(&self) -> Vec<Vec<String>> {
+        let mut options = vec!["options".to_string()];
+        options.extend(self.options.iter().cloned());
+
+        vec![
+            vec![TAG_LAMPORT_ID.to_string(), self.lamport_id.clone()],
+            vec!["proposal_id".to_string(), self.proposal_id.clone()],
+            vec!["group_id".to_string(), self.group_id.clone()],
+            vec!["title".to_string(), self.title.clone()],
+            options,
+            vec!["start_time".to_string(), self.start_time.clone()],
+            vec!["end_time".to_string(), self.end_time.clone()],
+        ]
+    }
+
+    fn content(&self) -> String {
+        format!("{} Proposal {}, Title:{}", self.lamport_id, self.proposal_id, self.title)
+    }
+
+    fn from_parts(tags: &[Vec<String>], _content: &str, _version: u32) -> AppResult<Self> {
+        Ok(Self {
+            lamport_id: required_tag(tags, TAG_LAMPORT_ID)?,
+            proposal_id: required_tag(tags, "proposal_id")?,
+            group_id: required_tag(tags, "group_id")?,
+            title: required_tag(tags, "title")?,
+            options: tag_values(tags, "options").map(|v| v.to_vec()).unwrap_or_default(),
+            start_time: required_tag(tags, "start_time")?,
+            end_time: required_tag(tags, "end_time")?,
+        })
+    }
+}
@@ -0,0 +1,39 @@
+use super::*;
+
+/// Kind 2326, retracts the kind 2321 binding of an account. The account may have moved to
+/// another Lamport ID.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnbindEvent {
+    pub lamport_id: String,
+    pub provider: String,
+    pub account: String,
+    pub reason: String,
+}
+
+impl LamportEvent for UnbindEvent {
+    const KIND: u16 = KIND_UNBIND;
+    const TYPE: LamportType = LamportType::Unbind;
+
+    fn tags(&self) -> Vec<Vec<String>> {
+        vec![
+            vec![TAG_LAMPORT_ID.to_string(), self.lamport_id.clone()],
+            vec!["provider".to_string(), self.provider.clone()],
+            vec!["account".to_string(), self.account.clone()],
+            vec!["k".to_string(), KIND_CREATE.to_string()],
+            vec!["reason".to_string(), self.reason.clone()],
+        ]
+    }
+
+    fn content(&self) -> String {
+        format!("LamportID:{} unbind {} account:{}", self.lamport_id, self.provider, self.account)
+    }
+
+    fn from_parts(tags: &[Vec<String>], _content: &str, _version: u32) -> AppResult<Self> {
+        Ok(Self {
+            lamport_id: required_tag(tags, TAG_LAMPORT_ID)?,
+            provider: required_tag(tags, "provider")?,
+            account: required_tag(tags, "account")?,
+            reason: tag(tags, "reason").unwrap_or_default().to_string(),
+        })
+    }
+}
@@ -0,0 +1,71 @@
+use super::*;
+
+/// Kind 2328, a vote cast on a proposal. Version 0 votes were kind 1 text notes without the
+/// proposal id and choice.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VoteEvent {
+    pub lamport_id: String,
+    pub vote_id: String,
+    pub proposal_id: String,
+    pub choice: String,
+    pub title: String,
+    pub options: Vec<String>,
+    pub start_time: String,
+    pub end_time: String,
+    pub sig: String,
+}
+
+impl LamportEvent for VoteEvent {
+    const KIND: u16 = KIND_VOTE;
+    const TYPE: LamportType = LamportType::Vote;
+    const LEGACY_KINDS: &'static [u16] = &[LEGACY_KIND_VOTE];
+
+    fn tags(&self) -> Vec<Vec<String>> {
+        let mut options = vec!["options".to_string()];
+        options.extend(self.options.iter().cloned());
+
+        vec![
+            vec![TAG_LAMPORT_ID.to_string(), self.lamport_id.clone()],
+            vec!["vote_id".to_string(), self.vote_id.clone()],
+            vec!["proposal_id".to_string(), self.proposal_id.clone()],
+            vec!["choice".to_string(), self.choice.clone()],
+            vec!["title".to_string(), self.title.clone()],
+            options,
+            vec!["start_time".to_string(), self.start_time.clone()],
+            vec!["end_time".to_string(), self.end_time.clone()],
+            vec![TAG_SIG.to_string(), self.sig.clone()],
+        ]
+    }
+
+    fn content(&self) -> String {
+        format!("{} Vote {}, Title:{}", self.lamport_id, self.vote_id, self.title)
+    }
+
+    //version 0 joined the options with commas
+    fn from_parts(tags: &[Vec<String>], _content: &str, version: u32) -> AppResult<Self> {
+        let (proposal_id, choice, options) = if version == 0 {
+            let options = tag(tags, "options")
+                .map(|o| o.split(',').map(|v| v.to_string()).collect())
+                .unwrap_or_default();
+            (String::new(), String::new(), options)
+        } else {
+            (
+                required_tag(tags, "proposal_id")?,
+                required_tag(tags, "choice")?,
+                tag_values(tags, "options").map(|v| v.to_vec()).unwrap_or_default(),
+            )
+        };
+
+        Ok(Self {
+            lamport_id: required_tag(tags, TAG_LAMPORT_ID)?,
+            vote_id: required_tag(tags, "vote_id")?,
+            proposal_id,
+            choice,
+            title: tag(tags, "title").unwrap_or_default().to_string(),
+            options,
+            start_time: tag(tags, "start_time").unwrap_or_default().to_string(),
+            end_time: tag(tags, "end_time").unwrap_or_default().to_string(),
+            sig: tag(tags, TAG_SIG).unwrap_or_default().to_string(),
+        })
+    }
+}
@@ -0,0 +1,249 @@
+use super::admin_message::*;
+use crate::{
+    app::SharedState,
+    common::{
+        error::{AppError, AppResult},
+        rbac::{Permission, SanctionKind},
+    },
+    server::{auth::auth_service::RedisClient, middlewares::AuthClaims},
+};
+use axum::{debug_handler, extract::Json as EJson, extract::Path, extract::Query, extract::State, Json};
+
+#[debug_handler]
+pub async fn grant_role(
+    State(state): State<SharedState>,
+    AuthClaims(claim): AuthClaims,
+    EJson(RoleRequest { lamport_id, role }): EJson<RoleRequest>,
+) -> AppResult<Json<serde_json::Value>> {
+    let granted = state.store.grant_role(lamport_id.as_str(), role, claim.sub.as_str()).await?;
+
+    Ok(Json(serde_json::json!({
+        "result": RoleInfo::from(granted)
+    })))
+}
+
+#[debug_handler]
+pub async fn revoke_role(
+    State(state): State<SharedState>,
+    AuthClaims(claim): AuthClaims,
+    EJson(RoleRequest { lamport_id, role }): EJson<RoleRequest>,
+) -> AppResult<Json<serde_json::Value>> {
+    let revoked = state.store.revoke_role(lamport_id.as_str(), role, claim.sub.as_str()).await?;
+
+    Ok(Json(serde_json::json!({
+        "result": RoleInfo::from(revoked)
+    })))
+}
+
+#[debug_handler]
+pub async fn get_roles(
+    State(state): State<SharedState>,
+    Query(GetRolesRequest { role }): Query<GetRolesRequest>,
+) -> AppResult<Json<serde_json::Value>> {
+    let roles = state.store.get_role_assignments(role).await?;
+
+    Ok(Json(serde_json::json!({
+        "result": {
+            "count": roles.len(),
+            "roles": roles.into_iter().map(RoleInfo::from).collect::<Vec<RoleInfo>>()
+        }
+    })))
+}
+
+#[debug_handler]
+pub async fn get_user_roles(
+    State(state): State<SharedState>,
+    Path(lamport_id): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    let roles = state.store.get_roles_by_uid(lamport_id.as_str()).await?;
+
+    Ok(Json(serde_json::json!({
+        "result": {
+            "lamport_id": lamport_id,
+            //admins from config hold every permission without a granted role
+            "bootstrap_admin": state.config.admin.lamport_ids.contains(&lamport_id),
+            "roles": roles
+        }
+    })))
+}
+
+//admins can not be sanctioned, a moderator would otherwise be able to lock them out
+async fn check_sanction_target(state: &SharedState, operator: &str, lamport_id: &str) -> AppResult<()> {
+    if operator == lamport_id {
+        return Err(AppError::InputValidateError("can not sanction yourself".into()));
+    }
+
+    if state
+        .store
+        .has_permission(lamport_id, Permission::ManageRoles, &state.config.admin.lamport_ids)
+        .await?
+    {
+        return Err(AppError::Forbidden("admins can not be sanctioned".into()));
+    }
+
+    Ok(())
+}
+
+#[debug_handler]
+pub async fn ban_user(
+    State(state): State<SharedState>,
+    AuthClaims(claim): AuthClaims,
+    Path(lamport_id): Path<String>,
+    EJson(BanRequest { reason }): EJson<BanRequest>,
+) -> AppResult<Json<serde_json::Value>> {
+    check_sanction_target(&state, claim.sub.as_str(), lamport_id.as_str()).await?;
+
+    let sanction = state
+        .store
+        .sanction_user(lamport_id.as_str(), SanctionKind::Ban, reason.as_str(), None, claim.sub.as_str())
+        .await?;
+    let revoked = RedisClient::from(state.redis.clone()).revoke_all_sessions(lamport_id.as_str()).await?;
+
+    Ok(Json(serde_json::json!({
+        "result": {
+            "sanction": SanctionInfo::from(sanction),
+            "revoked_sessions": revoked
+        }
+    })))
+}
+
+#[debug_handler]
+pub async fn suspend_user(
+    State(state): State<SharedState>,
+    AuthClaims(claim): AuthClaims,
+    Path(lamport_id): Path<String>,
+    EJson(SuspendRequest { reason, days }): EJson<SuspendRequest>,
+) -> AppResult<Json<serde_json::Value>> {
+    if days <= 0 {
+        return Err(AppError::InputValidateError("days must be positive".into()));
+    }
+    check_sanction_target(&state, claim.sub.as_str(), lamport_id.as_str()).await?;
+
+    let expires_at = chrono::Utc::now() + chrono::Duration::days(days);
+    let sanction = state
+        .store
+        .sanction_user(lamport_id.as_str(), SanctionKind::Suspend, reason.as_str(), Some(expires_at), claim.sub.as_str())
+        .await?;
+    let revoked = RedisClient::from(state.redis.clone()).revoke_all_sessions(lamport_id.as_str()).await?;
+
+    Ok(Json(serde_json::json!({
+        "result": {
+            "sanction": SanctionInfo::from(sanction),
+            "revoked_sessions": revoked
+        }
+    })))
+}
+
+#[debug_handler]
+pub async fn lift_sanction(
+    State(state): State<SharedState>,
+    AuthClaims(claim): AuthClaims,
+    Path(lamport_id): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    let sanction = state.store.lift_sanction(lamport_id.as_str(), claim.sub.as_str()).await?;
+
+    Ok(Json(serde_json::json!({
+        "result": SanctionInfo::from(sanction)
+    })))
+}
+
+#[debug_handler]
+pub async fn get_user_score(
+    State(state): State<SharedState>,
+    Path(lamport_id): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    let signals = state.store.get_sybil_signals(lamport_id.as_str()).await?;
+
+    Ok(Json(serde_json::json!({
+        "result": {
+            "lamport_id": lamport_id,
+            "score": signals.score(),
+            "signals": signals
+        }
+    })))
+}
+
+#[debug_handler]
+pub async fn get_sanctions(
+    State(state): State<SharedState>,
+    Query(PageRequest { offset, limit }): Query<PageRequest>,
+) -> AppResult<Json<serde_json::Value>> {
+    let sanctions = state.store.get_sanctions(offset, limit).await?;
+
+    Ok(Json(serde_json::json!({
+        "result": {
+            "count": sanctions.len(),
+            "sanctions": sanctions.into_iter().map(SanctionInfo::from).collect::<Vec<SanctionInfo>>()
+        }
+    })))
+}
+
+#[debug_handler]
+pub async fn hide_proposal(
+    State(state): State<SharedState>,
+    AuthClaims(claim): AuthClaims,
+    Path(proposal_id): Path<String>,
+    EJson(HideProposalRequest { reason }): EJson<HideProposalRequest>,
+) -> AppResult<Json<serde_json::Value>> {
+    let proposal = state
+        .store
+        .set_proposal_hidden(proposal_id.as_str(), true, reason.as_deref(), claim.sub.as_str())
+        .await?;
+
+    Ok(Json(serde_json::json!({
+        "result": {
+            "proposal_id": proposal.proposal_id,
+            "hidden": proposal.hidden
+        }
+    })))
+}
+
+#[debug_handler]
+pub async fn unhide_proposal(
+    State(state): State<SharedState>,
+    AuthClaims(claim): AuthClaims,
+    Path(proposal_id): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    let proposal = state
+        .store
+        .set_proposal_hidden(proposal_id.as_str(), false, None, claim.sub.as_str())
+        .await?;
+
+    Ok(Json(serde_json::json!({
+        "result": {
+            "proposal_id": proposal.proposal_id,
+            "hidden": proposal.hidden
+        }
+    })))
+}
+
+#[debug_handler]
+pub async fn get_audit_logs(
+    State(state): State<SharedState>,
+    Query(PageRequest { offset, limit }): Query<PageRequest>,
+) -> AppResult<Json<serde_json::Value>> {
+    let logs = state.store.get_admin_audit_logs(offset, limit).await?;
+
+    Ok(Json(serde_json::json!({
+        "result": {
+            "count": logs.len(),
+            "logs": logs.into_iter().map(AdminAuditInfo::from).collect::<Vec<AdminAuditInfo>>()
+        }
+    })))
+}
+
+//events that some write relay has not accepted yet
+#[debug_handler]
+pub async fn get_undelivered_nostr_events(
+    State(state): State<SharedState>,
+    Query(PageRequest { offset, limit }): Query<PageRequest>,
+) -> AppResult<Json<serde_json::Value>> {
+    let events = state.store.get_undelivered_events(offset, limit).await?;
+
+    Ok(Json(serde_json::json!({
+        "result": {
+            "count": events.len(),
+            "events": events.into_iter().map(UndeliveredEventInfo::from).collect::<Vec<UndeliveredEventInfo>>()
+        }
+    })))
+}
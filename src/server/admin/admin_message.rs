@@ -0,0 +1,149 @@
+use crate::{
+    common::{consts, rbac::Role},
+    database::entities::{admin_audit, nostr_deliveries, nostr_events, user_roles, user_sanctions},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize)]
+pub struct RoleRequest {
+    pub lamport_id: String,
+    pub role: Role,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GetRolesRequest {
+    pub role: Option<Role>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BanRequest {
+    pub reason: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SuspendRequest {
+    pub reason: String,
+    pub days: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HideProposalRequest {
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PageRequest {
+    pub offset: i64,
+    pub limit: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RoleInfo {
+    pub lamport_id: String,
+    pub role: String,
+    pub granted_by: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<user_roles::Model> for RoleInfo {
+    fn from(model: user_roles::Model) -> Self {
+        Self {
+            lamport_id: model.lamport_id,
+            role: model.role,
+            granted_by: model.granted_by,
+            created_at: model.created_at.into(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SanctionInfo {
+    pub lamport_id: String,
+    pub kind: String,
+    pub reason: String,
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_by: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<user_sanctions::Model> for SanctionInfo {
+    fn from(model: user_sanctions::Model) -> Self {
+        Self {
+            lamport_id: model.lamport_id,
+            kind: model.kind,
+            reason: model.reason,
+            expires_at: model.expires_at.map(|t| t.into()),
+            created_by: model.created_by,
+            created_at: model.created_at.into(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AdminAuditInfo {
+    pub operator: String,
+    pub action: String,
+    pub target: String,
+    pub detail: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<admin_audit::Model> for AdminAuditInfo {
+    fn from(model: admin_audit::Model) -> Self {
+        Self {
+            operator: model.operator,
+            action: model.action,
+            target: model.target,
+            detail: model.detail,
+            created_at: model.created_at.into(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeliveryInfo {
+    pub relay: String,
+    pub status: String,
+    pub error: Option<String>,
+    pub attempts: i32,
+    //none once the relay has been given up on
+    pub next_retry_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<nostr_deliveries::Model> for DeliveryInfo {
+    fn from(model: nostr_deliveries::Model) -> Self {
+        Self {
+            relay: model.relay,
+            status: model.status,
+            error: model.error,
+            attempts: model.attempts,
+            next_retry_at: model.next_retry_at.map(|t| t.into()),
+            updated_at: model.updated_at.into(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UndeliveredEventInfo {
+    pub event_id: String,
+    pub kind: i32,
+    //unpublished when no relay took the event, partial otherwise
+    pub status: String,
+    pub deliveries: Vec<DeliveryInfo>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<(nostr_events::Model, Vec<nostr_deliveries::Model>)> for UndeliveredEventInfo {
+    fn from((event, deliveries): (nostr_events::Model, Vec<nostr_deliveries::Model>)) -> Self {
+        let published = deliveries.iter().any(|d| d.status == consts::NOSTR_DELIVERY_OK);
+
+        Self {
+            event_id: event.event_id,
+            kind: event.kind,
+            status: if published { "partial" } else { "unpublished" }.to_string(),
+            deliveries: deliveries.into_iter().map(DeliveryInfo::from).collect(),
+            created_at: event.created_at.into(),
+        }
+    }
+}
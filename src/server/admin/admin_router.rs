@@ -0,0 +1,49 @@
+use super::admin_handler::*;
+use crate::app::SharedState;
+use crate::common::rbac::Permission;
+use crate::server::middlewares::{self, PermissionGuard};
+use axum::{middleware, routing::{get, post}, Router};
+
+pub fn admin_router(state: SharedState) -> Router<SharedState> {
+    let roles = Router::new()
+        .route("/roles", post(grant_role).get(get_roles))
+        .route("/roles/revoke", post(revoke_role))
+        .route("/roles/:lamport_id", get(get_user_roles))
+        .layer(middleware::from_fn_with_state(
+            PermissionGuard::new(state.clone(), Permission::ManageRoles),
+            middlewares::permission_middleware,
+        ));
+
+    let users = Router::new()
+        .route("/users/:lamport_id/ban", post(ban_user))
+        .route("/users/:lamport_id/suspend", post(suspend_user))
+        .route("/users/:lamport_id/lift", post(lift_sanction))
+        .route("/users/:lamport_id/score", get(get_user_score))
+        .route("/sanctions", get(get_sanctions))
+        .layer(middleware::from_fn_with_state(
+            PermissionGuard::new(state.clone(), Permission::ManageUsers),
+            middlewares::permission_middleware,
+        ));
+
+    let proposals = Router::new()
+        .route("/proposals/:proposal_id/hide", post(hide_proposal))
+        .route("/proposals/:proposal_id/unhide", post(unhide_proposal))
+        .layer(middleware::from_fn_with_state(
+            PermissionGuard::new(state.clone(), Permission::ModerateProposals),
+            middlewares::permission_middleware,
+        ));
+
+    let audit = Router::new()
+        .route("/audit", get(get_audit_logs))
+        .route("/nostr/deliveries", get(get_undelivered_nostr_events))
+        .layer(middleware::from_fn_with_state(
+            PermissionGuard::new(state, Permission::ViewAudit),
+            middlewares::permission_middleware,
+        ));
+
+    Router::new()
+        .merge(roles)
+        .merge(users)
+        .merge(proposals)
+        .merge(audit)
+}
@@ -0,0 +1,5 @@
+mod admin_handler;
+mod admin_message;
+mod admin_router;
+
+pub use admin_router::admin_router;
@@ -0,0 +1,181 @@
+use super::attestation_message::*;
+use crate::{
+    app::SharedState,
+    common::{
+        consts,
+        error::{AppError, AppResult},
+        rbac::Permission,
+    },
+    database::entities::users,
+    helpers::{eip1271::verify_hash_signature, eip191::hash_message},
+    nostr,
+    server::{auth::auth_service::RedisClient, events::events_message::Event, middlewares::AuthClaims, notification::Notification},
+};
+use axum::{debug_handler, extract::Path, extract::State, Json};
+
+//recompute the subject and the accounts it vouched for, and put the flips on their timelines
+async fn apply_verification(state: &SharedState, subject: &str) -> AppResult<Vec<users::Model>> {
+    let policy = state
+        .store
+        .verification_policy(&state.config.verification, &state.config.admin.lamport_ids)
+        .await?;
+    let flipped = state.store.reevaluate_verification(subject, &policy).await?;
+
+    for user in flipped.iter() {
+        tracing::info!("verification of {} changed to {}", user.lamport_id, user.verified);
+
+        let e = Event {
+            event_id: uuid::Uuid::new_v4().to_string(),
+            lamport_id: user.lamport_id.clone(),
+            event_type: consts::EVENT_TYPE_VERIFICATION.to_string(),
+            content: if user.verified {
+                "Verified by attestations".to_string()
+            } else {
+                "Verification withdrawn".to_string()
+            },
+            created_at: chrono::Utc::now(),
+        };
+        state.queue.add_queue_req_ex(consts::EVENT_TOPIC, e).await?;
+    }
+
+    Ok(flipped)
+}
+
+// attest that the owner of an address is a distinct person
+#[debug_handler]
+pub async fn attest(
+    State(state): State<SharedState>,
+    AuthClaims(claim): AuthClaims,
+    Json(AttestRequest { data, sig }): Json<AttestRequest>,
+) -> AppResult<Json<serde_json::Value>> {
+    if data.attester != claim.sub {
+        return Err(AppError::InputValidateError("lamport id mismatch".into()));
+    }
+
+    let attester = state.store.get_user_by_uid(claim.sub.as_str()).await?;
+    let policy = state
+        .store
+        .verification_policy(&state.config.verification, &state.config.admin.lamport_ids)
+        .await?;
+    if !attester.verified && !policy.is_trusted(&attester.lamport_id) {
+        return Err(AppError::Forbidden("only verified users can attest".into()));
+    }
+
+    if cfg!(not(debug_assertions)) {
+        let verified = verify_hash_signature(state.sig_checker.as_deref(), hash_message(&data)?, &sig, &attester.address).await?;
+        if !verified {
+            return Err(AppError::InvalidSignature);
+        }
+        tracing::info!("signature verified success");
+
+        RedisClient::from(state.redis.clone())
+            .consume_nonce(attester.address.as_str(), data.nonce.as_str())
+            .await?;
+    }
+
+    let subject = state.store.get_user_by_address(data.subject.as_str()).await?;
+    if subject.lamport_id == attester.lamport_id {
+        return Err(AppError::InputValidateError("can not attest yourself".into()));
+    }
+
+    let attestation = state
+        .store
+        .create_attestation(attester.lamport_id.as_str(), subject.lamport_id.as_str(), data.subject.as_str(), sig.as_str())
+        .await?;
+    apply_verification(&state, subject.lamport_id.as_str()).await?;
+
+    let nostr_event_id = state
+        .publish_nostr(
+            nostr::LamportBinding::new_kind2324(
+                state.nclient.get_pub_key(),
+                attestation.attester.as_str(),
+                attestation.subject.as_str(),
+                attestation.subject_address.as_str(),
+                attestation.sig.as_str(),
+            ),
+            Some((consts::NOSTR_OBJECT_ATTESTATION, attestation.id.to_string().as_str())),
+        )
+        .await?;
+
+    state.queue.add_queue_req_ex(consts::NOTIFICATION_TOPIC, Notification::Attested {
+        lamport_id: attestation.subject.clone(),
+        attester: attestation.attester.clone(),
+    }).await?;
+
+    let subject = state.store.get_user_by_uid(attestation.subject.as_str()).await?;
+
+    Ok(Json(serde_json::json!({
+        "result": {
+            "attestation": AttestationInfo::from(attestation),
+            "subject_verified": subject.verified
+        },
+        "nostr_event_id": nostr_event_id
+    })))
+}
+
+// revoke an attestation, by its attester or a user manager
+#[debug_handler]
+pub async fn revoke_attestation(
+    State(state): State<SharedState>,
+    AuthClaims(claim): AuthClaims,
+    Path(id): Path<i32>,
+) -> AppResult<Json<serde_json::Value>> {
+    let attestation = state.store.get_attestation(id).await?;
+
+    if attestation.attester != claim.sub
+        && !state
+            .store
+            .has_permission(&claim.sub, Permission::ManageUsers, &state.config.admin.lamport_ids)
+            .await?
+    {
+        return Err(AppError::Forbidden("only the attester can revoke an attestation".into()));
+    }
+
+    let revoked = state.store.revoke_attestation(id, claim.sub.as_str()).await?;
+    let flipped = apply_verification(&state, revoked.subject.as_str()).await?;
+
+    let nostr_event_id = state
+        .publish_nostr(
+            nostr::LamportBinding::new_kind2325(
+                state.nclient.get_pub_key(),
+                revoked.attester.as_str(),
+                revoked.subject.as_str(),
+                claim.sub.as_str(),
+            ),
+            Some((consts::NOSTR_OBJECT_ATTESTATION, revoked.id.to_string().as_str())),
+        )
+        .await?;
+
+    Ok(Json(serde_json::json!({
+        "result": {
+            "attestation": AttestationInfo::from(revoked),
+            "unverified": flipped
+                .into_iter()
+                .filter(|u| !u.verified)
+                .map(|u| u.lamport_id)
+                .collect::<Vec<String>>()
+        },
+        "nostr_event_id": nostr_event_id
+    })))
+}
+
+// attestations received and given by a lamport id
+#[debug_handler]
+pub async fn get_attestations(
+    State(state): State<SharedState>,
+    Path(lamport_id): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    let user = state.store.get_user_by_uid(lamport_id.as_str()).await?;
+    let received = state.store.get_attestations_by_subject(lamport_id.as_str()).await?;
+    let given = state.store.get_attestations_by_attester(lamport_id.as_str()).await?;
+
+    Ok(Json(serde_json::json!({
+        "result": {
+            "lamport_id": user.lamport_id,
+            "verified": user.verified,
+            "verified_by": user.verified_by,
+            "received": received.into_iter().map(AttestationInfo::from).collect::<Vec<AttestationInfo>>(),
+            "given": given.into_iter().map(AttestationInfo::from).collect::<Vec<AttestationInfo>>()
+        }
+    })))
+}
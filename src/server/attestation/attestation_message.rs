@@ -0,0 +1,43 @@
+use crate::database::entities::attestations;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttestRequest {
+    pub data: AttestationData,
+    pub sig: String,
+}
+
+//signed by the attester's primary address, nonce from /api/v1/auth/nonce/:address
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttestationData {
+    pub attester: String,
+    pub subject: String,
+    pub nonce: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AttestationInfo {
+    pub id: i32,
+    pub attester: String,
+    pub subject: String,
+    pub subject_address: String,
+    pub revoked: bool,
+    pub revoked_by: Option<String>,
+    pub revoked_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<attestations::Model> for AttestationInfo {
+    fn from(model: attestations::Model) -> Self {
+        Self {
+            id: model.id,
+            attester: model.attester,
+            subject: model.subject,
+            subject_address: model.subject_address,
+            revoked: model.revoked,
+            revoked_by: model.revoked_by,
+            revoked_at: model.revoked_at.map(|t| t.into()),
+            created_at: model.created_at.into(),
+        }
+    }
+}
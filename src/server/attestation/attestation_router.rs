@@ -0,0 +1,15 @@
+use super::attestation_handler::*;
+use crate::app::SharedState;
+use crate::server::middlewares;
+use axum::{middleware, routing::{get, post}, Router};
+
+pub fn attestation_router(state: SharedState) -> Router<SharedState> {
+    Router::new()
+        .route("/", post(attest))
+        .route("/:id/revoke", post(revoke_attestation))
+        .layer(middleware::from_fn_with_state(
+            state,
+            middlewares::auth_middleware,
+        ))
+        .route("/users/:lamport_id", get(get_attestations))
+}
@@ -0,0 +1,5 @@
+mod attestation_handler;
+mod attestation_message;
+mod attestation_router;
+
+pub use attestation_router::attestation_router;
@@ -1,9 +1,11 @@
 use super::auth_message::*;
 use super::auth_service::*;
+use super::session_service::{refresh_session, start_session};
 use crate::{
     app::SharedState,
     common::error::{AppError, AppResult},
-    server::user::*,
+    database::entities::users,
+    server::{events::events_message::Event, middlewares::{AuthClaims, ClientInfo}, user::*},
     common::consts,
 };
 use axum::{
@@ -11,119 +13,65 @@ use axum::{
     extract::{Query, State, Path},
     Json,
 };
-//use oauth2::RedirectUrl;
-use reqwest::Client;
+use oauth2::{CsrfToken, PkceCodeChallenge, Scope};
 
+//an X login resolves by the stable X id, through the bound account first and the id it signed up with then
+async fn find_x_user(state: &SharedState, x_id: &str) -> AppResult<Option<users::Model>> {
+    if let Some(binding) = state
+        .store
+        .get_social_binding_by_external_id(consts::PROVIDER_TWITTER, x_id)
+        .await?
+    {
+        return Ok(Some(state.store.get_user_by_uid(binding.user_id.as_str()).await?));
+    }
+
+    state.store.get_user_by_x_id(x_id).await
+}
 
 #[debug_handler]
 pub async fn auth_token(
     State(state): State<SharedState>,
+    client_info: ClientInfo,
     Json(params): Json<OAuthParams>,
 ) -> AppResult<Json<serde_json::Value>> {
     tracing::info!("[auth_token] get params: {:?}", params);
 
     params.validate_items()?;
 
-    //let client = state
-    //    .oauth
-    //    .clone()
-    //    .set_redirect_uri(RedirectUrl::new(params.clone().redirect_uri.unwrap().clone())?);
-    //    //.set_redirect_uri(RedirectUrl::new(state.config.auth.redirect_url.clone())?);
-
     let csrf_state = params.clone()
         .state
         .ok_or(AppError::InputValidateError("Invild state".into()))?;
 
     let redis_client = RedisClient::from(state.redis.clone());
-    if let Ok(token) = redis_client.get_csrf_token(csrf_state.as_str()).await {
-        tracing::info!("got csrf token: {:?} by key: {:?}", token, csrf_state);
-    } else {
-        tracing::error!("got csrf token err: wrong state:{:?} ", csrf_state);
-        //return Err(AppError::InputValidateError(
-        //    "csrf token verification error".into(),
-        //));
-    }
-
-    let client = Client::new();
-
-    let token_params = [
-        ("code", params.clone().code.unwrap()),
-        ("grant_type", "authorization_code".into()),
-        ("client_id", state.config.auth.client_id.clone()),
-        ("redirect_uri", params.clone().redirect_uri.unwrap()),
-        ("code_verifier", "challenge".into()),
-    ];
-
-    tracing::info!("[auth_token] exchange code params: {:?}", token_params);
-
-
-    let token_response= client
-        .post("https://api.x.com/2/oauth2/token")
-        .header("Content-Type", "application/x-www-form-urlencoded")
-        .form(&token_params)
-        .send()
-        .await
-        .map_err(|_e| AppError::RequestError("failed to exchange code".to_string()))?;
-
-    if !token_response.status().is_success() {
-        let error_message = token_response
-            .text()
-            .await
-            .unwrap_or_else(|_| "Failed to read error response".to_string());
-
-        return Err(AppError::RequestError(format!(
-                    "Failed to get token. Status: Error: {}", 
-                    error_message
-        )));
-    }
+    let code_verifier = redis_client.consume_oauth_state(csrf_state.as_str()).await?;
 
-    let token: ExchangeTokenRespose = token_response
-        .json()
-        .await
-        .map_err(|e| AppError::CustomError(e.to_string() + "Failed to parse user info"))?;
+    let token = state
+        .twitter
+        .exchange_code(
+            params.code.as_deref().unwrap_or_default(),
+            params.redirect_uri.as_deref().unwrap_or_default(),
+            code_verifier.as_str(),
+        )
+        .await?;
 
-    tracing::info!("[auth_token] exchange code get: {:?}", token);
+    tracing::info!("[auth_token] exchange code, scope: {:?}", token.scope);
 
-    let access_token = token.access_token;
-
-    tracing::info!("[auth_token] Access Token: {:?}", access_token);
-
-    let client = Client::new();
-
-    let user_info_response = client
-        .get("https://api.x.com/2/users/me")
-        .bearer_auth(&access_token)
-        .query(&[("user.fields", "profile_image_url")])
-        .send()
-        .await
-        .map_err(|_e| AppError::RequestError("failed to get user info".to_string()))?;
-
-    tracing::info!("[auth_token] get user info response: {:?}", user_info_response);
-
-    if !user_info_response.status().is_success() {
-        return Err(AppError::RequestError(
-            "non user info in response".to_string(),
-        ));
-    }
-
-    let user_info: OauthUserInfo = user_info_response
-        .json()
-        .await
-        .map_err(|e| AppError::CustomError(e.to_string() + "Failed to parse user info"))?;
+    let user_info = OauthUserInfo {
+        data: state.twitter.get_me(token.access_token.as_str()).await?,
+    };
 
     tracing::info!("[auth_token] get user info: {:?}", user_info);
 
-    let created_user = if state
-        .store
-        .is_user_exists_by_username(user_info.data.username.as_ref())
-        .await?
-    {
-        state
-            .store
-            .get_user_by_username(user_info.data.username.as_ref())
-            .await?
+    let created_user = if let Some(user) = find_x_user(&state, user_info.data.id.as_str()).await? {
+        user
     } else {
-        let user: User = User::from(user_info.clone());
+        //the handle may belong to another lamport id, the account then starts without one
+        let mut x_user = user_info.clone();
+        if state.store.is_username_taken(x_user.data.username.as_str(), None).await? {
+            x_user.data.username = String::new();
+        }
+
+        let user: User = User::from(x_user).add_register_ip(client_info.ip.as_str());
         tracing::info!("[auth_token] create user: {:?}", user);
 
         //points
@@ -136,10 +84,9 @@ pub async fn auth_token(
             Ok(u) => u,
             Err(AppError::UserExisted(_)) => {
                 tracing::info!("user has already existed, log in");
-                state
-                    .store
-                    .get_user_by_username(user_info.data.username.as_ref())
+                find_x_user(&state, user_info.data.id.as_str())
                     .await?
+                    .ok_or(AppError::UserExisted("X account has already signed up".into()))?
             }
             Err(e) => return Err(e),
         };
@@ -171,20 +118,14 @@ pub async fn auth_token(
         created_user
     };
 
-    let secret = state.jwt_handler.clone();
-    let token: String =
-        secret.create_token(&created_user.lamport_id, &created_user.name, &created_user.user_name);
+    let tokens = start_session(&redis_client, &state.jwt_handler, &state.store, &created_user, &client_info, None).await?;
 
-    tracing::info!("[auth_token] jwt token: {:?}", token);
-
-    //redis_client
-    //    .del_csrf_token(csrf_state.as_str())
-    //    .await
-    //    .unwrap();
+    tracing::info!("[auth_token] jwt token: {:?}", tokens.access_token);
 
     Ok(Json(serde_json::json!({
         "result": {
-            "access_token": token,
+            "access_token": tokens.access_token,
+            "refresh_token": tokens.refresh_token,
             "user_info": UserResponse::from(created_user)
         }
     })))
@@ -194,30 +135,53 @@ pub async fn auth_token(
 pub async fn callback_handler(
     State(state): State<SharedState>,
     Query(params): Query<OAuthCallbackParams>,
-    //Query(params): Query<HashMap<String, String>>
 ) -> Json<serde_json::Value> {
     tracing::info!("auth params: {:?}", params);
 
     Json(serde_json::json!({
         "result": {
             "code": params.code,
-            "state": "authorization_code",
+            "state": params.state,
             "redirect_uri": state.config.auth.redirect_url.clone()
         }
     }))
 }
 
+//start an X login or binding, the client sends the user to authorize_url and posts code and state back
 #[debug_handler]
-pub async fn get_csrf_token(
+pub async fn authorize(
     State(state): State<SharedState>,
 ) -> AppResult<Json<serde_json::Value>> {
+    let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+    let code_challenge = pkce_challenge.as_str().to_string();
+
+    let (authorize_url, csrf_token) = state
+        .oauth
+        .authorize_url(CsrfToken::new_random)
+        .add_scopes(
+            state
+                .config
+                .twitter
+                .scope
+                .split_whitespace()
+                .map(|s| Scope::new(s.to_string())),
+        )
+        .set_pkce_challenge(pkce_challenge)
+        .url();
+
     let redis_client = RedisClient::from(state.redis.clone());
-    let token = redis_client.cache_csrf_token().await.unwrap();
-    tracing::info!("gen csrf token: {:?}", token);
+    redis_client
+        .cache_oauth_state(csrf_token.secret(), pkce_verifier.secret())
+        .await?;
+    tracing::info!("gen oauth state: {:?}", csrf_token.secret());
 
     Ok(Json(serde_json::json!({
         "result": {
-            "csrf_token": token
+            "csrf_token": csrf_token.secret(),
+            "state": csrf_token.secret(),
+            "code_challenge": code_challenge,
+            "code_challenge_method": "S256",
+            "authorize_url": authorize_url.to_string()
         }
     })))
 }
@@ -238,3 +202,98 @@ pub async fn get_nonce(
     })))
 }
 
+
+//exchange a refresh token for a new token pair, the old refresh token stops working
+#[debug_handler]
+pub async fn refresh_token(
+    State(state): State<SharedState>,
+    client: ClientInfo,
+    Json(req): Json<RefreshRequest>,
+) -> AppResult<Json<serde_json::Value>> {
+    let redis_client = RedisClient::from(state.redis.clone());
+    let (tokens, user) = refresh_session(&redis_client, &state.jwt_handler, &state.store, &req.refresh_token, &client).await?;
+
+    Ok(Json(serde_json::json!({
+        "result": {
+            "access_token": tokens.access_token,
+            "refresh_token": tokens.refresh_token,
+            "user_info": UserResponse::from(user)
+        }
+    })))
+}
+
+//confirm an email address with the token of its verification link, no login needed
+#[debug_handler]
+pub async fn verify_email(
+    State(state): State<SharedState>,
+    Json(req): Json<VerifyEmailRequest>,
+) -> AppResult<Json<serde_json::Value>> {
+    let verification = RedisClient::from(state.redis.clone())
+        .consume_email_verification(req.token.as_str())
+        .await?;
+
+    let user = state
+        .store
+        .verify_user_email(verification.lamport_id.as_str(), verification.email.as_str())
+        .await?
+        .ok_or(AppError::InputValidateError("email has changed since the link was sent".into()))?;
+
+    let e = Event {
+        event_id: uuid::Uuid::new_v4().to_string(),
+        lamport_id: user.lamport_id.clone(),
+        event_type: consts::EVENT_TYPE_PROFILE.to_string(),
+        content: "Verified email".to_string(),
+        created_at: chrono::Utc::now(),
+    };
+    state.queue.add_queue_req_ex(consts::EVENT_TOPIC, e).await?;
+
+    Ok(Json(serde_json::json!({
+        "result": UserResponse::from(user)
+    })))
+}
+
+#[debug_handler]
+pub async fn logout(
+    State(state): State<SharedState>,
+    AuthClaims(claim): AuthClaims,
+) -> AppResult<Json<serde_json::Value>> {
+    if let Some(sid) = claim.sid.as_deref() {
+        RedisClient::from(state.redis.clone()).revoke_session(&claim.sub, sid).await?;
+    }
+
+    Ok(Json(serde_json::json!({
+        "result": "OK"
+    })))
+}
+
+//log out all devices of the caller
+#[debug_handler]
+pub async fn logout_all(
+    State(state): State<SharedState>,
+    AuthClaims(claim): AuthClaims,
+) -> AppResult<Json<serde_json::Value>> {
+    let revoked = RedisClient::from(state.redis.clone()).revoke_all_sessions(&claim.sub).await?;
+
+    Ok(Json(serde_json::json!({
+        "result": {
+            "revoked": revoked
+        }
+    })))
+}
+
+#[debug_handler]
+pub async fn get_sessions(
+    State(state): State<SharedState>,
+    AuthClaims(claim): AuthClaims,
+) -> AppResult<Json<serde_json::Value>> {
+    let sessions: Vec<SessionInfo> = RedisClient::from(state.redis.clone())
+        .list_sessions(&claim.sub)
+        .await?
+        .into_iter()
+        .map(|s| SessionInfo::new(s, claim.sid.as_deref()))
+        .collect();
+
+    Ok(Json(serde_json::json!({
+        "result": sessions
+    })))
+}
@@ -1,3 +1,4 @@
+use super::session_service::Session;
 use crate::common::error::{AppError, AppResult};
 use serde::{Deserialize, Serialize};
 use std::convert::From;
@@ -6,7 +7,6 @@ use validator::Validate;
 #[derive(Deserialize, Debug)]
 pub struct OAuthCallbackParams {
     pub code: String,
-    #[allow(unused)]
     pub state: String,
 }
 
@@ -64,3 +64,37 @@ pub struct ExchangeTokenRespose {
     pub scope: String,
 }
 
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
+
+//token from the link sent by /api/v1/user/email/verify
+#[derive(Deserialize, Debug, Clone)]
+pub struct VerifyEmailRequest {
+    pub token: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SessionInfo {
+    pub sid: String,
+    pub device: String,
+    pub ip: String,
+    pub created_at: i64,
+    pub last_used_at: i64,
+    pub current: bool,
+}
+
+impl SessionInfo {
+    pub fn new(session: Session, current_sid: Option<&str>) -> Self {
+        Self {
+            current: current_sid == Some(session.sid.as_str()),
+            sid: session.sid,
+            device: session.device,
+            ip: session.ip,
+            created_at: session.created_at,
+            last_used_at: session.last_used_at,
+        }
+    }
+}
@@ -1,15 +1,27 @@
 use super::auth_handler::*;
 use crate::app::SharedState;
+use crate::server::middlewares;
 use axum::{
+    middleware,
     routing::{get, post},
     Router,
 };
 
 pub fn auth_router(state: SharedState) -> Router<SharedState> {
     Router::new()
+        .route("/logout", post(logout))
+        .route("/logout/all", post(logout_all))
+        .route("/sessions", get(get_sessions))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            middlewares::auth_middleware,
+        ))
+        .route("/refresh", post(refresh_token))
+        .route("/email/verify", post(verify_email))
         .route("/callback", get(callback_handler))
         .route("/token", post(auth_token))
-        .route("/csrf_token", get(get_csrf_token))
+        .route("/authorize", get(authorize))
+        .route("/csrf_token", get(authorize))
         .route("/nonce/:address", get(get_nonce))
         .with_state(state.clone())
 }
@@ -1,8 +1,69 @@
-use oauth2::CsrfToken;
+use crate::common::{
+    consts,
+    error::{AppError, AppResult},
+};
+use rand::{distributions::Alphanumeric, Rng};
 use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
 
-pub fn gen_csrf_token() -> String {
-    CsrfToken::new_random().secret().to_string()
+fn oauth_state_key(state: &str) -> String {
+    format!("oauth_state:{}", state)
+}
+
+fn pending_binding_key(state: &str) -> String {
+    format!("pending_binding:{}", state)
+}
+
+fn email_verification_key(token: &str) -> String {
+    format!("email_verification:{}", token)
+}
+
+fn email_user_cooldown_key(lamport_id: &str) -> String {
+    format!("email_cooldown:user:{}", lamport_id)
+}
+
+fn email_address_cooldown_key(email: &str) -> String {
+    format!("email_cooldown:address:{}", email.to_lowercase())
+}
+
+//hex addresses are case insensitive, a nonce is kept under the lowercase form whichever form asked for it
+fn nonce_key(address: &str) -> String {
+    address.to_lowercase()
+}
+
+fn nostr_challenge_key(lamport_id: &str) -> String {
+    format!("nostr_challenge:{}", lamport_id)
+}
+
+fn closing_notified_key(proposal_id: &str) -> String {
+    format!("proposal_closing_notified:{}", proposal_id)
+}
+
+/// An email address waiting for its owner to open the verification link.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailVerification {
+    pub lamport_id: String,
+    pub email: String,
+}
+
+/// A binding started by a Lamport ID, waiting for the provider callback or the email link.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingBinding {
+    pub provider: String,
+    pub lamport_id: String,
+    //pkce verifier of an oauth provider
+    pub verifier: Option<String>,
+    //address the magic link was sent to
+    pub email: Option<String>,
+}
+
+//EIP-4361 only allows alphanumeric nonces
+pub fn gen_nonce() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(32)
+        .map(char::from)
+        .collect()
 }
 
 #[derive(Clone)]
@@ -22,46 +83,160 @@ impl RedisClient {
         Ok(Self(client))
     }
 
-    pub async fn cache_csrf_token(&self) -> Result<String, redis::RedisError> {
-        let token: String = gen_csrf_token();
+    pub(super) async fn conn(&self) -> Result<redis::aio::MultiplexedConnection, redis::RedisError> {
+        self.0.get_multiplexed_async_connection().await
+    }
 
-        let mut conn = self.0.get_multiplexed_async_connection().await?;
-        let _: () = conn.set_ex(&token, "lamport_id", 600).await?;
+    //keep the pkce verifier of a login until its callback, keyed by the state parameter
+    pub async fn cache_oauth_state(&self, state: &str, verifier: &str) -> Result<(), redis::RedisError> {
+        let mut conn = self.conn().await?;
+        let _: () = conn
+            .set_ex(oauth_state_key(state), verifier, consts::OAUTH_STATE_EXPIRATION)
+            .await?;
 
-        Ok(token)
+        Ok(())
     }
-    pub async fn get_csrf_token(&self, token: &str) -> Result<String, redis::RedisError> {
-        let mut conn = self.0.get_multiplexed_async_connection().await?;
-        let cached_token: Option<String> = conn.get(token).await?;
 
-        match cached_token {
-            Some(val) => Ok(val),
-            None => Err(redis::RedisError::from((
-                redis::ErrorKind::ResponseError,
-                "Token not found",
-            ))),
+    //take the verifier issued with state, a state is only accepted once
+    pub async fn consume_oauth_state(&self, state: &str) -> AppResult<String> {
+        let mut conn = self.conn().await?;
+        let verifier: Option<String> = conn.get_del(oauth_state_key(state)).await?;
+
+        verifier.ok_or_else(|| {
+            tracing::error!("got oauth state err: unknown or used state:{:?} ", state);
+            AppError::InputValidateError("state is invalid or has been used".into())
+        })
+    }
+
+    //state is the oauth state parameter or the token of an email link
+    pub async fn cache_pending_binding(
+        &self,
+        state: &str,
+        pending: &PendingBinding,
+        expiration: u64,
+    ) -> AppResult<()> {
+        let mut conn = self.conn().await?;
+        let _: () = conn
+            .set_ex(pending_binding_key(state), serde_json::to_string(pending)?, expiration)
+            .await?;
+
+        Ok(())
+    }
+
+    //take the binding started with state, it is only accepted once
+    pub async fn consume_pending_binding(&self, state: &str) -> AppResult<PendingBinding> {
+        let mut conn = self.conn().await?;
+        let pending: Option<String> = conn.get_del(pending_binding_key(state)).await?;
+
+        match pending {
+            Some(p) => Ok(serde_json::from_str(&p)?),
+            None => Err(AppError::InputValidateError("state is invalid or has been used".into())),
         }
     }
 
-    pub async fn del_csrf_token(&self, token: &str) -> Result<(), redis::RedisError> {
-        let mut conn = self.0.get_multiplexed_async_connection().await?;
-        let _: () = conn.del(token).await?;
+    pub async fn cache_email_verification(
+        &self,
+        token: &str,
+        verification: &EmailVerification,
+        expiration: u64,
+    ) -> AppResult<()> {
+        let mut conn = self.conn().await?;
+        let _: () = conn
+            .set_ex(email_verification_key(token), serde_json::to_string(verification)?, expiration)
+            .await?;
 
         Ok(())
     }
 
+    //take the verification of token, a link works once
+    pub async fn consume_email_verification(&self, token: &str) -> AppResult<EmailVerification> {
+        let mut conn = self.conn().await?;
+        let verification: Option<String> = conn.get_del(email_verification_key(token)).await?;
+
+        match verification {
+            Some(v) => Ok(serde_json::from_str(&v)?),
+            None => Err(AppError::InputValidateError("token is invalid, expired or has been used".into())),
+        }
+    }
+
+    //starts the cooldowns of a lamport id and an address before mailing, errors while either is running
+    pub async fn claim_email_cooldown(&self, lamport_id: &str, email: &str) -> AppResult<()> {
+        let mut conn = self.conn().await?;
+        let user_key = email_user_cooldown_key(lamport_id);
+        let user: Option<String> = redis::cmd("SET")
+            .arg(user_key.as_str())
+            .arg(1)
+            .arg("NX")
+            .arg("EX")
+            .arg(consts::EMAIL_USER_COOLDOWN)
+            .query_async(&mut conn)
+            .await?;
+        if user.is_none() {
+            return Err(AppError::TooManyRequests("wait before sending another email".into()));
+        }
+
+        let address: Option<String> = redis::cmd("SET")
+            .arg(email_address_cooldown_key(email))
+            .arg(1)
+            .arg("NX")
+            .arg("EX")
+            .arg(consts::EMAIL_ADDRESS_COOLDOWN)
+            .query_async(&mut conn)
+            .await?;
+        if address.is_none() {
+            //nothing was sent, the lamport id may still mail another address
+            let _: () = conn.del(user_key).await?;
+            return Err(AppError::TooManyRequests(format!("wait before sending another email to {}", email)));
+        }
+
+        Ok(())
+    }
+
+    //a new challenge for lamport_id to sign with its nostr key, replaces any earlier one
+    pub async fn cache_nostr_challenge(&self, lamport_id: &str) -> AppResult<String> {
+        let challenge = gen_nonce();
+        let mut conn = self.conn().await?;
+        let _: () = conn
+            .set_ex(nostr_challenge_key(lamport_id), challenge.as_str(), consts::NOSTR_CHALLENGE_EXPIRATION)
+            .await?;
+
+        Ok(challenge)
+    }
+
+    pub async fn consume_nostr_challenge(&self, lamport_id: &str) -> AppResult<String> {
+        let mut conn = self.conn().await?;
+        let challenge: Option<String> = conn.get_del(nostr_challenge_key(lamport_id)).await?;
+
+        challenge.ok_or(AppError::InputValidateError("challenge is invalid, expired or has been used".into()))
+    }
+
+    //true the first time it is called for a proposal, so its closing notice goes out once
+    pub async fn mark_proposal_closing_notified(&self, proposal_id: &str, expiration: u64) -> AppResult<bool> {
+        let mut conn = self.conn().await?;
+        let set: Option<String> = redis::cmd("SET")
+            .arg(closing_notified_key(proposal_id))
+            .arg(1)
+            .arg("NX")
+            .arg("EX")
+            .arg(expiration)
+            .query_async(&mut conn)
+            .await?;
+
+        Ok(set.is_some())
+    }
+
     pub async fn cache_nonce(&self, address: &str) -> Result<String, redis::RedisError> {
-        let token: String = gen_csrf_token();
+        let token: String = gen_nonce();
 
         let mut conn = self.0.get_multiplexed_async_connection().await?;
-        let _: () = conn.set_ex(address, token.as_str(), 600).await?;
+        let _: () = conn.set_ex(nonce_key(address), token.as_str(), 600).await?;
 
         Ok(token)
     }
 
     pub async fn get_nonce(&self, address: &str) -> Result<String, redis::RedisError> {
         let mut conn = self.0.get_multiplexed_async_connection().await?;
-        let cached_token: Option<String> = conn.get(address).await?;
+        let cached_token: Option<String> = conn.get(nonce_key(address)).await?;
 
         match cached_token {
             Some(val) => Ok(val),
@@ -74,9 +249,183 @@ impl RedisClient {
 
     pub async fn del_nonce(&self, address: &str) -> Result<(), redis::RedisError> {
         let mut conn = self.0.get_multiplexed_async_connection().await?;
-        let _: () = conn.del(address).await?;
+        let _: () = conn.del(nonce_key(address)).await?;
+
+        Ok(())
+    }
+
+    //check the nonce issued for address and delete it so it can not be replayed
+    pub async fn consume_nonce(&self, address: &str, nonce: &str) -> AppResult<()> {
+        match self.get_nonce(address).await {
+            Ok(cached) => {
+                if cached != nonce {
+                    return Err(AppError::InputValidateError("nonce verification error".into()));
+                }
+            }
+            Err(_) => {
+                tracing::error!("got nonce err: wrong address:{:?} ", address);
+                return Err(AppError::InputValidateError("nonce is not existing".into()));
+            }
+        }
+
+        if let Err(e) = self.del_nonce(address).await {
+            tracing::error!("delete nonce err: {:?}", e);
+        }
 
         Ok(())
     }
 
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oauth2::{PkceCodeChallenge, PkceCodeVerifier};
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+    };
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+
+    //keys with the second they expire at, now is moved by the test instead of waiting
+    #[derive(Default)]
+    struct FakeRedis {
+        now: u64,
+        keys: HashMap<String, (String, u64)>,
+    }
+
+    //just enough of RESP for SETEX, SET NX EX, GETDEL and DEL, anything else (CLIENT SETINFO on connect) answers OK
+    async fn fake_redis(redis: Arc<Mutex<FakeRedis>>) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("redis://{}/", listener.local_addr().unwrap());
+
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                let redis = redis.clone();
+                tokio::spawn(async move {
+                    let (reader, mut writer) = socket.into_split();
+                    let mut reader = BufReader::new(reader);
+                    let mut line = String::new();
+                    while reader.read_line(&mut line).await.unwrap_or(0) > 0 {
+                        let n: usize = line.trim()[1..].parse().unwrap();
+                        let mut args = Vec::new();
+                        for _ in 0..n {
+                            line.clear();
+                            reader.read_line(&mut line).await.unwrap();
+                            let mut arg = vec![0; line.trim()[1..].parse::<usize>().unwrap() + 2];
+                            reader.read_exact(&mut arg).await.unwrap();
+                            args.push(String::from_utf8_lossy(&arg[..arg.len() - 2]).to_string());
+                        }
+                        line.clear();
+
+                        let reply = {
+                            let mut redis = redis.lock().unwrap();
+                            let now = redis.now;
+                            match args[0].to_uppercase().as_str() {
+                                "SETEX" => {
+                                    let until = now + args[2].parse::<u64>().unwrap();
+                                    redis.keys.insert(args[1].clone(), (args[3].clone(), until));
+                                    "+OK\r\n".to_string()
+                                }
+                                "SET" => {
+                                    let until = now + args[5].parse::<u64>().unwrap();
+                                    match redis.keys.get(&args[1]) {
+                                        Some((_, expires)) if *expires > now => "$-1\r\n".to_string(),
+                                        _ => {
+                                            redis.keys.insert(args[1].clone(), (args[2].clone(), until));
+                                            "+OK\r\n".to_string()
+                                        }
+                                    }
+                                }
+                                "DEL" => format!(":{}\r\n", redis.keys.remove(&args[1]).map_or(0, |_| 1)),
+                                "GET" => match redis.keys.get(&args[1]) {
+                                    Some((value, until)) if *until > now => format!("${}\r\n{}\r\n", value.len(), value),
+                                    _ => "$-1\r\n".to_string(),
+                                },
+                                "GETDEL" => match redis.keys.remove(&args[1]) {
+                                    Some((value, until)) if until > now => format!("${}\r\n{}\r\n", value.len(), value),
+                                    _ => "$-1\r\n".to_string(),
+                                },
+                                _ => "+OK\r\n".to_string(),
+                            }
+                        };
+                        writer.write_all(reply.as_bytes()).await.unwrap();
+                    }
+                });
+            }
+        });
+
+        url
+    }
+
+    #[tokio::test]
+    async fn test_oauth_state_flow() {
+        let redis = Arc::new(Mutex::new(FakeRedis::default()));
+        let client = RedisClient::new(fake_redis(redis.clone()).await.as_str()).await.unwrap();
+
+        let (challenge, verifier) = PkceCodeChallenge::new_random_sha256();
+        client.cache_oauth_state("state-1", verifier.secret()).await.unwrap();
+        let (_, other_verifier) = PkceCodeChallenge::new_random_sha256();
+        client.cache_oauth_state("state-2", other_verifier.secret()).await.unwrap();
+
+        //the verifier kept for a state answers the challenge sent to X with it, not the one of another state
+        let consumed = client.consume_oauth_state("state-1").await.unwrap();
+        let answered = PkceCodeChallenge::from_code_verifier_sha256(&PkceCodeVerifier::new(consumed));
+        assert_eq!(answered.as_str(), challenge.as_str());
+        let other = client.consume_oauth_state("state-2").await.unwrap();
+        let mismatched = PkceCodeChallenge::from_code_verifier_sha256(&PkceCodeVerifier::new(other));
+        assert_ne!(mismatched.as_str(), challenge.as_str());
+
+        //a state is accepted once
+        assert!(client.consume_oauth_state("state-1").await.is_err());
+        assert!(client.consume_oauth_state("unknown").await.is_err());
+
+        //and only until it expires
+        client.cache_oauth_state("state-3", verifier.secret()).await.unwrap();
+        redis.lock().unwrap().now += consts::OAUTH_STATE_EXPIRATION;
+        assert!(client.consume_oauth_state("state-3").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_email_cooldown() {
+        let redis = Arc::new(Mutex::new(FakeRedis::default()));
+        let client = RedisClient::new(fake_redis(redis.clone()).await.as_str()).await.unwrap();
+
+        client.claim_email_cooldown("1001", "alice@example.com").await.unwrap();
+        //the same lamport id waits, whatever the address
+        assert!(matches!(
+            client.claim_email_cooldown("1001", "bob@example.com").await,
+            Err(AppError::TooManyRequests(_))
+        ));
+        //the same address waits, whoever asks
+        assert!(matches!(
+            client.claim_email_cooldown("1002", "Alice@Example.com").await,
+            Err(AppError::TooManyRequests(_))
+        ));
+        //and the refused lamport id is not held back by it
+        client.claim_email_cooldown("1002", "carol@example.com").await.unwrap();
+
+        redis.lock().unwrap().now += consts::EMAIL_USER_COOLDOWN;
+        client.claim_email_cooldown("1001", "bob@example.com").await.unwrap();
+        assert!(client.claim_email_cooldown("1003", "alice@example.com").await.is_err());
+        redis.lock().unwrap().now += consts::EMAIL_ADDRESS_COOLDOWN;
+        client.claim_email_cooldown("1003", "alice@example.com").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_nonce_address_case() {
+        let redis = Arc::new(Mutex::new(FakeRedis::default()));
+        let client = RedisClient::new(fake_redis(redis.clone()).await.as_str()).await.unwrap();
+
+        //asked for with the checksummed address, consumed with the lowercase one
+        let nonce = client.cache_nonce("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed").await.unwrap();
+        client
+            .consume_nonce("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed", nonce.as_str())
+            .await
+            .unwrap();
+        assert!(client
+            .consume_nonce("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed", nonce.as_str())
+            .await
+            .is_err());
+    }
+}
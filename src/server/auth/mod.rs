@@ -2,6 +2,8 @@ mod auth_handler;
 pub mod auth_message;
 mod auth_router;
 pub mod auth_service;
+pub mod session_service;
+pub mod twitter_client;
 
 pub use auth_message::OauthUserInfo;
 pub use auth_router::auth_router;
@@ -0,0 +1,259 @@
+use super::auth_service::RedisClient;
+use crate::{
+    common::error::{AppError, AppResult},
+    database::{entities::users, Storage},
+    server::middlewares::{jwt::jwt_handler::JwtHandler, ClientInfo},
+};
+use rand::{distributions::Alphanumeric, Rng};
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+const SESSION_PREFIX: &str = "session:";
+const USER_SESSIONS_PREFIX: &str = "sessions:";
+
+/// A login of one device, the refresh token secret is only kept as a hash.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub sid: String,
+    pub lamport_id: String,
+    pub device: String,
+    pub ip: String,
+    pub refresh_hash: String,
+    //wallet and chain of a Sign-In with Ethereum login, copied into refreshed tokens
+    pub address: Option<String>,
+    pub chain_id: Option<u64>,
+    pub created_at: i64,
+    pub last_used_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenPair {
+    pub access_token: String,
+    pub refresh_token: String,
+}
+
+fn random_secret(length: usize) -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(length)
+        .map(char::from)
+        .collect()
+}
+
+fn hash_secret(secret: &str) -> String {
+    hex::encode(Sha256::digest(secret.as_bytes()))
+}
+
+fn session_key(sid: &str) -> String {
+    format!("{}{}", SESSION_PREFIX, sid)
+}
+
+fn user_sessions_key(lamport_id: &str) -> String {
+    format!("{}{}", USER_SESSIONS_PREFIX, lamport_id)
+}
+
+//replaces the session only while it still holds the refresh hash the caller checked
+const SWAP_SESSION_SCRIPT: &str = r#"
+local current = redis.call('GET', KEYS[1])
+if not current or cjson.decode(current).refresh_hash ~= ARGV[1] then
+    return 0
+end
+redis.call('SET', KEYS[1], ARGV[2], 'EX', ARGV[4])
+redis.call('SADD', KEYS[2], ARGV[3])
+redis.call('EXPIRE', KEYS[2], ARGV[4])
+return 1
+"#;
+
+//refresh tokens are `<sid>.<secret>`
+fn split_refresh_token(token: &str) -> AppResult<(&str, &str)> {
+    token
+        .split_once('.')
+        .ok_or_else(|| AppError::Unauthorized("invalid refresh token".into()))
+}
+
+//banned and suspended lamport ids can not log in or refresh
+async fn ensure_not_sanctioned(store: &Storage, lamport_id: &str) -> AppResult<()> {
+    if let Some(sanction) = store.get_active_sanction(lamport_id).await? {
+        return Err(AppError::Forbidden(format!("account is under {}: {}", sanction.kind, sanction.reason)));
+    }
+
+    Ok(())
+}
+
+fn access_token(jwt: &JwtHandler, session: &Session, user: &users::Model) -> AppResult<String> {
+    match (&session.address, session.chain_id) {
+        (Some(address), Some(chain_id)) => {
+            jwt.create_wallet_token(&user.lamport_id, &user.name, &user.user_name, &session.sid, address, chain_id)
+        }
+        _ => jwt.create_token(&user.lamport_id, &user.name, &user.user_name, &session.sid),
+    }
+}
+
+impl RedisClient {
+    async fn save_session(&self, session: &Session, ttl: u64) -> AppResult<()> {
+        let mut conn = self.conn().await?;
+        let _: () = conn
+            .set_ex(session_key(&session.sid), serde_json::to_string(session)?, ttl)
+            .await?;
+
+        let index = user_sessions_key(&session.lamport_id);
+        let _: () = conn.sadd(&index, &session.sid).await?;
+        let _: () = conn.expire(&index, ttl as i64).await?;
+
+        Ok(())
+    }
+
+    //false when another refresh rotated the session first
+    async fn swap_session(&self, session: &Session, expected_hash: &str, ttl: u64) -> AppResult<bool> {
+        let mut conn = self.conn().await?;
+        let swapped: i32 = redis::Script::new(SWAP_SESSION_SCRIPT)
+            .key(session_key(&session.sid))
+            .key(user_sessions_key(&session.lamport_id))
+            .arg(expected_hash)
+            .arg(serde_json::to_string(session)?)
+            .arg(&session.sid)
+            .arg(ttl)
+            .invoke_async(&mut conn)
+            .await?;
+
+        Ok(swapped == 1)
+    }
+
+    pub async fn get_session(&self, sid: &str) -> AppResult<Option<Session>> {
+        let mut conn = self.conn().await?;
+        let cached: Option<String> = conn.get(session_key(sid)).await?;
+
+        Ok(match cached {
+            Some(v) => Some(serde_json::from_str(&v)?),
+            None => None,
+        })
+    }
+
+    pub async fn is_session_active(&self, sid: &str) -> AppResult<bool> {
+        let mut conn = self.conn().await?;
+        Ok(conn.exists(session_key(sid)).await?)
+    }
+
+    pub async fn revoke_session(&self, lamport_id: &str, sid: &str) -> AppResult<()> {
+        let mut conn = self.conn().await?;
+        let _: () = conn.del(session_key(sid)).await?;
+        let _: () = conn.srem(user_sessions_key(lamport_id), sid).await?;
+
+        Ok(())
+    }
+
+    pub async fn revoke_all_sessions(&self, lamport_id: &str) -> AppResult<u64> {
+        let mut conn = self.conn().await?;
+        let index = user_sessions_key(lamport_id);
+        let sids: Vec<String> = conn.smembers(&index).await?;
+
+        for sid in sids.iter() {
+            let _: () = conn.del(session_key(sid)).await?;
+        }
+        let _: () = conn.del(&index).await?;
+
+        Ok(sids.len() as u64)
+    }
+
+    //active sessions of lamport_id, expired ones are dropped from the index
+    pub async fn list_sessions(&self, lamport_id: &str) -> AppResult<Vec<Session>> {
+        let mut conn = self.conn().await?;
+        let index = user_sessions_key(lamport_id);
+        let sids: Vec<String> = conn.smembers(&index).await?;
+
+        let mut sessions = Vec::new();
+        for sid in sids.iter() {
+            match self.get_session(sid).await? {
+                Some(s) => sessions.push(s),
+                None => {
+                    let _: () = conn.srem(&index, sid).await?;
+                }
+            }
+        }
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.last_used_at));
+
+        Ok(sessions)
+    }
+}
+
+//create a session for a fresh login and issue its first token pair
+pub async fn start_session(
+    redis: &RedisClient,
+    jwt: &JwtHandler,
+    store: &Storage,
+    user: &users::Model,
+    client: &ClientInfo,
+    wallet: Option<(&str, u64)>,
+) -> AppResult<TokenPair> {
+    ensure_not_sanctioned(store, &user.lamport_id).await?;
+
+    let secret = random_secret(48);
+    let now = chrono::Utc::now().timestamp();
+
+    let session = Session {
+        sid: uuid::Uuid::new_v4().to_string(),
+        lamport_id: user.lamport_id.clone(),
+        device: client.device.clone(),
+        ip: client.ip.clone(),
+        refresh_hash: hash_secret(&secret),
+        address: wallet.map(|(a, _)| a.to_string()),
+        chain_id: wallet.map(|(_, c)| c),
+        created_at: now,
+        last_used_at: now,
+    };
+    let access_token = access_token(jwt, &session, user)?;
+    redis.save_session(&session, jwt.refresh_ttl()).await?;
+
+    Ok(TokenPair {
+        access_token,
+        refresh_token: format!("{}.{}", session.sid, secret),
+    })
+}
+
+//rotate the refresh token, presenting an already rotated token revokes the session
+pub async fn refresh_session(
+    redis: &RedisClient,
+    jwt: &JwtHandler,
+    store: &Storage,
+    refresh_token: &str,
+    client: &ClientInfo,
+) -> AppResult<(TokenPair, users::Model)> {
+    let (sid, secret) = split_refresh_token(refresh_token)?;
+
+    let mut session = redis
+        .get_session(sid)
+        .await?
+        .ok_or_else(|| AppError::Unauthorized("session has been revoked or expired".into()))?;
+
+    let presented = hash_secret(secret);
+    if session.refresh_hash != presented {
+        tracing::warn!("refresh token reuse on session {}, revoking it", sid);
+        redis.revoke_session(&session.lamport_id, sid).await?;
+        return Err(AppError::Unauthorized("refresh token has been used".into()));
+    }
+
+    let user = store.get_user_by_uid(&session.lamport_id).await?;
+    ensure_not_sanctioned(store, &user.lamport_id).await?;
+
+    let secret = random_secret(48);
+    session.refresh_hash = hash_secret(&secret);
+    session.device = client.device.clone();
+    session.ip = client.ip.clone();
+    session.last_used_at = chrono::Utc::now().timestamp();
+    let access_token = access_token(jwt, &session, &user)?;
+    //a concurrent refresh with the same token won, so the token was used twice
+    if !redis.swap_session(&session, presented.as_str(), jwt.refresh_ttl()).await? {
+        tracing::warn!("concurrent refresh token reuse on session {}, revoking it", sid);
+        redis.revoke_session(&session.lamport_id, sid).await?;
+        return Err(AppError::Unauthorized("refresh token has been used".into()));
+    }
+
+    Ok((
+        TokenPair {
+            access_token,
+            refresh_token: format!("{}.{}", session.sid, secret),
+        },
+        user,
+    ))
+}
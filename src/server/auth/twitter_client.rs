@@ -0,0 +1,305 @@
+use super::auth_message::{ExchangeTokenRespose, OauthUserInfo, UserInfo};
+use crate::{
+    common::{
+        config::{AuthConfig, TwitterConfig},
+        consts,
+        error::{AppError, AppResult},
+    },
+    database::{entities::social_bindings, services::binding::BindingTokens, DbTxn, Storage},
+    helpers::token_cipher::{TokenCipher, SEALED_PREFIX},
+};
+use reqwest::{Client, RequestBuilder, StatusCode};
+
+/// Talks to the X OAuth2 and users API, the endpoints come from the `twitter` config section.
+#[derive(Clone)]
+pub struct TwitterClient {
+    http: Client,
+    config: TwitterConfig,
+    client_id: String,
+    client_secret: String,
+    cipher: TokenCipher,
+}
+
+/// What re-checking a binding against X found.
+#[derive(Debug, Clone)]
+pub enum BindingStatus {
+    Valid(UserInfo),
+    //the token works but belongs to another X account
+    Mismatch(UserInfo),
+    //the account is gone or the user revoked our access
+    Unavailable(String),
+}
+
+impl TwitterClient {
+    pub fn new(auth: &AuthConfig, config: &TwitterConfig) -> AppResult<Self> {
+        Ok(Self {
+            http: Client::new(),
+            config: config.clone(),
+            client_id: auth.client_id.clone(),
+            client_secret: auth.client_secret.clone(),
+            cipher: TokenCipher::new(config.token_secret.as_str())?,
+        })
+    }
+
+    //confidential clients authenticate with basic auth, public clients only send client_id
+    fn with_client_auth(&self, req: RequestBuilder) -> RequestBuilder {
+        if self.client_secret.is_empty() {
+            req
+        } else {
+            req.basic_auth(&self.client_id, Some(&self.client_secret))
+        }
+    }
+
+    async fn token_request(&self, params: &[(&str, &str)]) -> AppResult<ExchangeTokenRespose> {
+        let response = self
+            .with_client_auth(self.http.post(self.config.token_url.as_str()))
+            .form(params)
+            .send()
+            .await
+            .map_err(|_e| AppError::RequestError("failed to request token".to_string()))?;
+
+        if !response.status().is_success() {
+            let error_message = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Failed to read error response".to_string());
+
+            return Err(AppError::RequestError(format!(
+                "Failed to get token. Status: Error: {}",
+                error_message
+            )));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| AppError::CustomError(e.to_string() + "Failed to parse token"))
+    }
+
+    pub async fn exchange_code(
+        &self,
+        code: &str,
+        redirect_uri: &str,
+        code_verifier: &str,
+    ) -> AppResult<ExchangeTokenRespose> {
+        self.token_request(&[
+            ("code", code),
+            ("grant_type", "authorization_code"),
+            ("client_id", self.client_id.as_str()),
+            ("redirect_uri", redirect_uri),
+            ("code_verifier", code_verifier),
+        ])
+        .await
+    }
+
+    pub async fn refresh_token(&self, refresh_token: &str) -> AppResult<ExchangeTokenRespose> {
+        self.token_request(&[
+            ("refresh_token", refresh_token),
+            ("grant_type", "refresh_token"),
+            ("client_id", self.client_id.as_str()),
+        ])
+        .await
+    }
+
+    async fn fetch_me(&self, access_token: &str) -> AppResult<Result<UserInfo, StatusCode>> {
+        let response = self
+            .http
+            .get(self.config.userinfo_url.as_str())
+            .bearer_auth(access_token)
+            .query(&[("user.fields", "profile_image_url")])
+            .send()
+            .await
+            .map_err(|_e| AppError::RequestError("failed to get user info".to_string()))?;
+
+        if !response.status().is_success() {
+            return Ok(Err(response.status()));
+        }
+
+        let user_info: OauthUserInfo = response
+            .json()
+            .await
+            .map_err(|e| AppError::CustomError(e.to_string() + "Failed to parse user info"))?;
+
+        Ok(Ok(user_info.data))
+    }
+
+    pub async fn get_me(&self, access_token: &str) -> AppResult<UserInfo> {
+        self.fetch_me(access_token)
+            .await?
+            .map_err(|_| AppError::RequestError("non user info in response".to_string()))
+    }
+
+    pub async fn revoke_token(&self, token: &str, token_type_hint: &str) -> AppResult<()> {
+        let response = self
+            .with_client_auth(self.http.post(self.config.revoke_url.as_str()))
+            .form(&[
+                ("token", token),
+                ("token_type_hint", token_type_hint),
+                ("client_id", self.client_id.as_str()),
+            ])
+            .send()
+            .await
+            .map_err(|_e| AppError::RequestError("failed to revoke token".to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::RequestError(format!(
+                "Failed to revoke token. Status: {}",
+                response.status()
+            )));
+        }
+
+        Ok(())
+    }
+
+    //tokens of a new binding, encrypted for storage
+    pub fn seal(&self, token: &ExchangeTokenRespose) -> AppResult<BindingTokens> {
+        Ok(BindingTokens {
+            access_token: self.cipher.encrypt(token.access_token.as_str())?,
+            refresh_token: Some(self.cipher.encrypt(token.refresh_token.as_str())?),
+            token_type: token.token_type.clone(),
+            scope: token.scope.clone(),
+            expires_at: Some(Self::expires_at(token)),
+        })
+    }
+
+    fn expires_at(token: &ExchangeTokenRespose) -> chrono::DateTime<chrono::Utc> {
+        chrono::Utc::now() + chrono::Duration::seconds(token.expires_in)
+    }
+
+    fn stored_token(&self, token: Option<&String>) -> AppResult<String> {
+        match token {
+            Some(t) => self.cipher.decrypt(t),
+            None => Err(AppError::CustomError("binding has no stored token".into())),
+        }
+    }
+
+    fn is_expiring(&self, binding: &social_bindings::Model) -> bool {
+        let margin = chrono::Duration::seconds(self.config.refresh_margin);
+        match binding.expires_at {
+            Some(t) => t.with_timezone(&chrono::Utc) - margin < chrono::Utc::now(),
+            None => true,
+        }
+    }
+
+    //refresh the tokens of a binding and store them encrypted, X rotates the refresh token on every refresh
+    //so the row stays locked until the new one is stored and a refresh that waited reuses it
+    pub async fn refresh_binding(
+        &self,
+        store: &Storage,
+        binding: social_bindings::Model,
+    ) -> AppResult<social_bindings::Model> {
+        let txn = DbTxn::new(store.conn.as_ref()).await?;
+        let binding = store
+            .lock_social_binding(&txn, binding.id)
+            .await?
+            .ok_or(AppError::CustomError("binding has been released".into()))?;
+
+        if !self.is_expiring(&binding) {
+            txn.commit_transaction().await?;
+            return Ok(binding);
+        }
+
+        let refresh_token = self.stored_token(binding.refresh_token.as_ref())?;
+        let token = self.refresh_token(refresh_token.as_str()).await?;
+
+        let binding = store
+            .update_binding_tokens(
+                &txn,
+                binding,
+                self.cipher.encrypt(token.access_token.as_str())?,
+                Some(self.cipher.encrypt(token.refresh_token.as_str())?),
+                Some(Self::expires_at(&token)),
+            )
+            .await?;
+        txn.commit_transaction().await?;
+
+        Ok(binding)
+    }
+
+    //access token of a binding, refreshed first when it is about to expire
+    pub async fn access_token(&self, store: &Storage, binding: social_bindings::Model) -> AppResult<String> {
+        let binding = if self.is_expiring(&binding) {
+            self.refresh_binding(store, binding).await?
+        } else {
+            binding
+        };
+
+        self.stored_token(binding.access_token.as_ref())
+    }
+
+    //one-shot migration of tokens stored before encryption, returns how many bindings were sealed
+    pub async fn seal_plaintext_tokens(&self, store: &Storage) -> AppResult<usize> {
+        let mut sealed = 0;
+
+        for binding in store.get_unsealed_bindings(consts::PROVIDER_TWITTER, SEALED_PREFIX).await? {
+            let txn = DbTxn::new(store.conn.as_ref()).await?;
+            let Some(binding) = store.lock_social_binding(&txn, binding.id).await? else {
+                continue;
+            };
+
+            let seal = |token: Option<&String>| -> AppResult<Option<String>> {
+                match token {
+                    Some(t) if TokenCipher::is_sealed(t) => Ok(Some(t.clone())),
+                    Some(t) => Ok(Some(self.cipher.encrypt(t)?)),
+                    None => Ok(None),
+                }
+            };
+            let Some(access_token) = seal(binding.access_token.as_ref())? else {
+                continue;
+            };
+            let refresh_token = seal(binding.refresh_token.as_ref())?;
+            let expires_at = binding.expires_at.map(|t| t.with_timezone(&chrono::Utc));
+
+            store
+                .update_binding_tokens(&txn, binding, access_token, refresh_token, expires_at)
+                .await?;
+            txn.commit_transaction().await?;
+            sealed += 1;
+        }
+
+        Ok(sealed)
+    }
+
+    //check the bound account still exists and is the one bound
+    pub async fn verify_binding(&self, store: &Storage, binding: social_bindings::Model) -> AppResult<BindingStatus> {
+        let x_id = binding.external_id.clone();
+        let access_token = match self.access_token(store, binding).await {
+            Ok(t) => t,
+            Err(AppError::RequestError(e)) => return Ok(BindingStatus::Unavailable(e)),
+            Err(e) => return Err(e),
+        };
+
+        Ok(match self.fetch_me(access_token.as_str()).await? {
+            Ok(info) if info.id == x_id => BindingStatus::Valid(info),
+            Ok(info) => BindingStatus::Mismatch(info),
+            Err(status) => BindingStatus::Unavailable(format!("users/me answered {}", status)),
+        })
+    }
+
+    //revoke the grant of a binding, the refresh token first since revoking it ends the whole grant
+    pub async fn revoke_binding(&self, binding: &social_bindings::Model) -> AppResult<()> {
+        if binding.refresh_token.is_some() {
+            let refresh_token = self.stored_token(binding.refresh_token.as_ref())?;
+            self.revoke_token(refresh_token.as_str(), "refresh_token").await?;
+        }
+
+        let access_token = self.stored_token(binding.access_token.as_ref())?;
+        self.revoke_token(access_token.as_str(), "access_token").await
+    }
+
+    //refresh every binding that expires within the margin, returns how many were refreshed
+    pub async fn refresh_expiring_bindings(&self, store: &Storage) -> AppResult<usize> {
+        let before = chrono::Utc::now() + chrono::Duration::seconds(self.config.refresh_margin);
+        let mut refreshed = 0;
+
+        for binding in store.get_expiring_bindings(consts::PROVIDER_TWITTER, before).await? {
+            let user_id = binding.user_id.clone();
+            match self.refresh_binding(store, binding).await {
+                Ok(_) => refreshed += 1,
+                Err(e) => tracing::error!("refresh twitter token of {} err: {:?}", user_id, e),
+            }
+        }
+
+        Ok(refreshed)
+    }
+}
@@ -0,0 +1,432 @@
+use super::binding_message::*;
+use super::provider::{twitter_account, SocialAccount, SocialProvider};
+use crate::{
+    app::SharedState,
+    common::{config::RewardPolicy, consts, error::{AppError, AppResult}},
+    database::{
+        entities::{binding_releases, social_bindings},
+        services::binding::NewSocialBinding,
+    },
+    nostr,
+    server::{
+        auth::{auth_message::OAuthParams, auth_service::RedisClient, twitter_client::BindingStatus},
+        events::events_message::Event,
+        middlewares::AuthClaims,
+    },
+};
+use axum::{
+    debug_handler,
+    extract::{Path, State},
+    Json,
+};
+use sha2::{Digest, Sha256};
+
+//email addresses are never published, a hash salted with the server key stands in for them
+fn published_account(state: &SharedState, provider: &str, account: &str) -> String {
+    if provider != consts::PROVIDER_EMAIL {
+        return account.to_string();
+    }
+
+    let mut hasher = Sha256::new();
+    hasher.update(state.nclient.signer.secret_key().as_secret_bytes());
+    hasher.update(account.to_lowercase().as_bytes());
+    format!("sha256:{}", hex::encode(hasher.finalize()))
+}
+
+//after an unbind or rebind the lamport id and the account both wait before binding provider again
+async fn check_cooldown(
+    state: &SharedState,
+    lamport_id: &str,
+    provider: &str,
+    external_id: Option<&str>,
+) -> AppResult<()> {
+    let mut releases = vec![state.store.get_last_release_by_user_id(lamport_id, provider).await?];
+    if let Some(external_id) = external_id {
+        releases.push(state.store.get_last_release_by_external_id(provider, external_id).await?);
+    }
+
+    let cooldown = chrono::Duration::hours(state.config.binding.cooldown_hours);
+    for release in releases.into_iter().flatten() {
+        let until = release.released_at.with_timezone(&chrono::Utc) + cooldown;
+        if until > chrono::Utc::now() {
+            return Err(AppError::Forbidden(format!(
+                "{} binding is cooling down until {}",
+                provider,
+                until.to_rfc3339()
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+//detach a binding from its lamport id, the reward is handled by the policy and the binding retracted
+async fn release_binding(state: &SharedState, binding: social_bindings::Model, reason: &str) -> AppResult<String> {
+    //a grant X already dropped can not be revoked again, that must not keep the binding alive
+    revoke_grant(state, &binding).await;
+
+    let binding_id = binding.id;
+    let released = state.store.release_social_binding(binding, reason).await?;
+    on_released(state, binding_id, &released, reason).await
+}
+
+async fn revoke_grant(state: &SharedState, binding: &social_bindings::Model) {
+    if binding.provider == consts::PROVIDER_TWITTER && binding.access_token.is_some() {
+        if let Err(e) = state.twitter.revoke_binding(binding).await {
+            tracing::error!("[release_binding] revoke token of {} err: {:?}", binding.user_id, e);
+        }
+    }
+}
+
+//side effects of a release once it is stored, returns the nostr event id of the retraction
+async fn on_released(
+    state: &SharedState,
+    binding_id: i32,
+    released: &binding_releases::Model,
+    reason: &str,
+) -> AppResult<String> {
+    let lamport_id = released.user_id.as_str();
+    let provider = released.provider.as_str();
+
+    //claw back once, a later binding of the provider is not rewarded again
+    if state.config.binding.reward_policy == RewardPolicy::Clawback
+        && state.store.has_points(lamport_id, consts::POINTS_BINDING, provider).await?
+        && !state.store.has_points(lamport_id, consts::POINTS_BINDING_CLAWBACK, provider).await?
+    {
+        state
+            .store
+            .award_points(lamport_id.to_string(), consts::POINTS_BINDING_CLAWBACK, -consts::POINTS_BINDING_VALUE, provider)
+            .await?;
+    }
+
+    let e = Event {
+        event_id: uuid::Uuid::new_v4().to_string(),
+        lamport_id: lamport_id.to_string(),
+        event_type: consts::EVENT_TYPE_BINDING.to_string(),
+        content: format!("Unbound {}", provider),
+        created_at: chrono::Utc::now(),
+    };
+    state.queue.add_queue_req_ex(consts::EVENT_TOPIC, e).await?;
+
+    state
+        .publish_nostr(
+            nostr::LamportBinding::new_kind2326(
+                state.nclient.get_pub_key(),
+                lamport_id,
+                provider,
+                published_account(state, provider, released.user_name.as_str()).as_str(),
+                reason,
+            ),
+            Some((consts::NOSTR_OBJECT_BINDING, binding_id.to_string().as_str())),
+        )
+        .await
+}
+
+//consume the state of a binding the caller started and let the provider prove the account
+async fn prove_account(
+    state: &SharedState,
+    lamport_id: &str,
+    provider: &dyn SocialProvider,
+    req: &CompleteBindingRequest,
+) -> AppResult<SocialAccount> {
+    let pending = RedisClient::from(state.redis.clone())
+        .consume_pending_binding(req.state.as_str())
+        .await?;
+    if pending.provider != provider.name() || pending.lamport_id != lamport_id {
+        return Err(AppError::InputValidateError("state was issued for another binding".into()));
+    }
+
+    provider.complete(pending, req).await
+}
+
+fn new_binding(lamport_id: &str, provider: &str, account: SocialAccount) -> NewSocialBinding {
+    NewSocialBinding {
+        user_id: lamport_id.to_string(),
+        provider: provider.to_string(),
+        external_id: account.external_id,
+        name: account.name,
+        user_name: account.user_name,
+        image_url: account.image_url,
+        tokens: account.tokens,
+    }
+}
+
+//store a proven account, reward the first binding of each provider and publish it, returns the nostr event id too
+async fn save_binding(
+    state: &SharedState,
+    lamport_id: &str,
+    provider: &str,
+    account: SocialAccount,
+) -> AppResult<(social_bindings::Model, String)> {
+    let binding = state
+        .store
+        .create_social_binding(new_binding(lamport_id, provider, account))
+        .await?;
+
+    let nostr_event_id = on_bound(state, &binding).await?;
+    Ok((binding, nostr_event_id))
+}
+
+//side effects of a binding once it is stored, returns the nostr event id
+async fn on_bound(state: &SharedState, binding: &social_bindings::Model) -> AppResult<String> {
+    let lamport_id = binding.user_id.as_str();
+    let provider = binding.provider.as_str();
+
+    //award point, once per provider however often it is rebound
+    if !state.store.has_points(lamport_id, consts::POINTS_BINDING, provider).await? {
+        state
+            .store
+            .award_points(lamport_id.to_string(), consts::POINTS_BINDING, consts::POINTS_BINDING_VALUE, provider)
+            .await?;
+    }
+
+    //consume energy
+    state
+        .store
+        .create_energy(lamport_id.to_string(), consts::ENERGY_BINDING, consts::ENERGY_BINDING_VALUE)
+        .await?;
+
+    let e = Event {
+        event_id: uuid::Uuid::new_v4().to_string(),
+        lamport_id: lamport_id.to_string(),
+        event_type: consts::EVENT_TYPE_BINDING.to_string(),
+        content: format!("Bound {}", provider),
+        created_at: chrono::Utc::now(),
+    };
+    state.queue.add_queue_req_ex(consts::EVENT_TOPIC, e).await?;
+
+    state
+        .publish_nostr(
+            nostr::LamportBinding::new_kind2321(
+                state.nclient.get_pub_key(),
+                lamport_id,
+                provider,
+                published_account(state, provider, binding.user_name.as_str()).as_str(),
+            ),
+            Some((consts::NOSTR_OBJECT_BINDING, binding.id.to_string().as_str())),
+        )
+        .await
+}
+
+// post binding_account, binds twitter with a state from /api/v1/auth/authorize
+#[debug_handler]
+pub async fn binding_account(
+    State(state): State<SharedState>,
+    AuthClaims(claim): AuthClaims,
+    Json(params): Json<OAuthParams>,
+) -> AppResult<Json<serde_json::Value>> {
+    if let Ok(t) = state.store.get_social_binding(claim.sub.as_str(), consts::PROVIDER_TWITTER).await {
+        return Ok(Json(serde_json::json!({
+            "result": {
+                "twitter_info": BindingTwitterResponse::from(t)
+            }
+        })));
+    }
+
+    tracing::info!("[binding_account] get params: {:?}", params);
+
+    params.validate_items()?;
+
+    let code_verifier = RedisClient::from(state.redis.clone())
+        .consume_oauth_state(params.state.as_deref().unwrap_or_default())
+        .await?;
+
+    let account = twitter_account(
+        &state.twitter,
+        params.code.as_deref().unwrap_or_default(),
+        params.redirect_uri.as_deref().unwrap_or_default(),
+        code_verifier.as_str(),
+    )
+    .await?;
+
+    tracing::info!("[binding_account] get twitter account: {:?}", account.user_name);
+
+    check_cooldown(&state, claim.sub.as_str(), consts::PROVIDER_TWITTER, Some(account.external_id.as_str())).await?;
+
+    let (created_binding, nostr_event_id) =
+        save_binding(&state, claim.sub.as_str(), consts::PROVIDER_TWITTER, account).await?;
+
+    Ok(Json(serde_json::json!({
+        "result": {
+            "twitter_info": BindingTwitterResponse::from(created_binding)
+        },
+        "nostr_event_id": nostr_event_id
+    })))
+}
+
+// start binding an account of provider
+#[debug_handler]
+pub async fn begin_binding(
+    State(state): State<SharedState>,
+    AuthClaims(claim): AuthClaims,
+    Path(provider): Path<String>,
+    Json(req): Json<BeginBindingRequest>,
+) -> AppResult<Json<serde_json::Value>> {
+    let provider = state.social.get(provider.as_str())?;
+
+    if state.store.get_social_binding(claim.sub.as_str(), provider.name()).await.is_ok() {
+        return Err(AppError::ConflictError(format!("{} account has already been bound", provider.name())));
+    }
+    check_cooldown(&state, claim.sub.as_str(), provider.name(), None).await?;
+
+    let redis_client = RedisClient::from(state.redis.clone());
+    let result = provider.begin(&redis_client, claim.sub.as_str(), &req).await?;
+
+    Ok(Json(serde_json::json!({
+        "result": result
+    })))
+}
+
+// finish a binding started by begin_binding
+#[debug_handler]
+pub async fn complete_binding(
+    State(state): State<SharedState>,
+    AuthClaims(claim): AuthClaims,
+    Path(provider): Path<String>,
+    Json(req): Json<CompleteBindingRequest>,
+) -> AppResult<Json<serde_json::Value>> {
+    let provider = state.social.get(provider.as_str())?;
+
+    let account = prove_account(&state, claim.sub.as_str(), provider.as_ref(), &req).await?;
+    check_cooldown(&state, claim.sub.as_str(), provider.name(), Some(account.external_id.as_str())).await?;
+
+    let (binding, nostr_event_id) = save_binding(&state, claim.sub.as_str(), provider.name(), account).await?;
+
+    Ok(Json(serde_json::json!({
+        "result": BindingInfo::from(binding),
+        "nostr_event_id": nostr_event_id
+    })))
+}
+
+// move an account bound to another lamport id to the caller, proven like complete_binding
+#[debug_handler]
+pub async fn rebind(
+    State(state): State<SharedState>,
+    AuthClaims(claim): AuthClaims,
+    Path(provider): Path<String>,
+    Json(req): Json<CompleteBindingRequest>,
+) -> AppResult<Json<serde_json::Value>> {
+    let provider = state.social.get(provider.as_str())?;
+
+    let account = prove_account(&state, claim.sub.as_str(), provider.as_ref(), &req).await?;
+    check_cooldown(&state, claim.sub.as_str(), provider.name(), Some(account.external_id.as_str())).await?;
+
+    //the caller's own binding of provider blocks the move before the other lamport id loses anything
+    if state.store.get_social_binding(claim.sub.as_str(), provider.name()).await.is_ok() {
+        return Err(AppError::ConflictError(format!("{} account has already been bound", provider.name())));
+    }
+
+    let previous = state
+        .store
+        .get_social_binding_by_external_id(provider.name(), account.external_id.as_str())
+        .await?;
+    let (binding, moved_from, nostr_event_id) = match previous {
+        Some(p) => {
+            let from = p.user_id.clone();
+            let new = new_binding(claim.sub.as_str(), provider.name(), account);
+            let (released, binding) = state
+                .store
+                .rebind_social_binding(p.clone(), consts::BINDING_RELEASE_REBIND, new)
+                .await?;
+
+            //the move is stored, the old grant and the side effects of both halves follow
+            revoke_grant(&state, &p).await;
+            on_released(&state, p.id, &released, consts::BINDING_RELEASE_REBIND).await?;
+            let nostr_event_id = on_bound(&state, &binding).await?;
+            (binding, Some(from), nostr_event_id)
+        }
+        None => {
+            let (binding, nostr_event_id) = save_binding(&state, claim.sub.as_str(), provider.name(), account).await?;
+            (binding, None, nostr_event_id)
+        }
+    };
+
+    Ok(Json(serde_json::json!({
+        "result": {
+            "binding": BindingInfo::from(binding),
+            "moved_from": moved_from
+        },
+        "nostr_event_id": nostr_event_id
+    })))
+}
+
+// get_user_bindings
+#[debug_handler]
+pub async fn get_user_bindings(
+    State(state): State<SharedState>,
+    AuthClaims(claim): AuthClaims,
+) -> AppResult<Json<serde_json::Value>> {
+    let bindings = state.store.get_social_bindings_by_user_id(claim.sub.as_str()).await?;
+
+    let twitter_info = match bindings.iter().find(|b| b.provider == consts::PROVIDER_TWITTER) {
+        Some(t) => serde_json::json!(BindingTwitterResponse::from(t.clone())),
+        None => serde_json::json!({}),
+    };
+
+    Ok(Json(serde_json::json!({
+        "result": {
+            "twitter_info": twitter_info,
+            "bindings": bindings.into_iter().map(BindingInfo::from).collect::<Vec<_>>(),
+            "providers": state.social.names()
+        }
+    })))
+}
+
+// check the bound account still exists and is the one that was bound, only twitter keeps a token to ask with
+#[debug_handler]
+pub async fn verify_binding(
+    State(state): State<SharedState>,
+    AuthClaims(claim): AuthClaims,
+    Path(provider): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    if provider != consts::PROVIDER_TWITTER {
+        return Err(AppError::InputValidateError(format!("{} bindings can not be verified", provider)));
+    }
+
+    let binding = state.store.get_social_binding(claim.sub.as_str(), consts::PROVIDER_TWITTER).await?;
+    let x_id = binding.external_id.clone();
+
+    let result = match state.twitter.verify_binding(&state.store, binding).await? {
+        BindingStatus::Valid(info) => serde_json::json!({
+            "valid": true,
+            "x_id": info.id,
+            "user_name": info.username
+        }),
+        BindingStatus::Mismatch(info) => serde_json::json!({
+            "valid": false,
+            "x_id": x_id,
+            "reason": format!("token belongs to another account {}", info.id)
+        }),
+        BindingStatus::Unavailable(reason) => serde_json::json!({
+            "valid": false,
+            "x_id": x_id,
+            "reason": reason
+        }),
+    };
+
+    Ok(Json(serde_json::json!({
+        "result": result
+    })))
+}
+
+// unbind an account, a stored twitter token is revoked at X before the binding is released
+#[debug_handler]
+pub async fn unbind(
+    State(state): State<SharedState>,
+    AuthClaims(claim): AuthClaims,
+    Path(provider): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    let binding = state.store.get_social_binding(claim.sub.as_str(), provider.as_str()).await?;
+    let user_name = binding.user_name.clone();
+
+    let nostr_event_id = release_binding(&state, binding, consts::BINDING_RELEASE_UNBIND).await?;
+
+    Ok(Json(serde_json::json!({
+        "result": {
+            "status": "success",
+            "provider": provider,
+            "user_name": user_name
+        },
+        "nostr_event_id": nostr_event_id
+    })))
+}
@@ -0,0 +1,58 @@
+use crate::database::entities::social_bindings;
+use serde::{Deserialize, Serialize};
+
+//body of /:provider/start, oauth providers take {}
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct BeginBindingRequest {
+    pub email: Option<String>,
+}
+
+//state returned by /:provider/start, or the token of an email link
+#[derive(Debug, Clone, Deserialize)]
+pub struct CompleteBindingRequest {
+    pub state: String,
+    pub code: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BindingInfo {
+    pub provider: String,
+    pub external_id: String,
+    pub name: String,
+    pub user_name: String,
+    pub image_url: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<social_bindings::Model> for BindingInfo {
+    fn from(binding: social_bindings::Model) -> Self {
+        Self {
+            provider: binding.provider,
+            external_id: binding.external_id,
+            name: binding.name,
+            user_name: binding.user_name,
+            image_url: binding.image_url,
+            created_at: binding.created_at.into(),
+        }
+    }
+}
+
+//struct for get binding twitter and impl from social_bindings::Model
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BindingTwitterResponse {
+    pub x_id: String,
+    pub name: String,
+    pub user_name: String,
+    pub image_url: String,
+}
+
+impl From<social_bindings::Model> for BindingTwitterResponse {
+    fn from(binding: social_bindings::Model) -> Self {
+        Self {
+            x_id: binding.external_id,
+            name: binding.name,
+            user_name: binding.user_name,
+            image_url: binding.image_url.unwrap_or_default(),
+        }
+    }
+}
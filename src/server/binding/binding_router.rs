@@ -0,0 +1,18 @@
+use super::binding_handler::*;
+use crate::app::SharedState;
+use crate::server::middlewares;
+use axum::{middleware, routing::{get, post}, Router};
+
+pub fn binding_router(state: SharedState) -> Router<SharedState> {
+    Router::new()
+        .route("/", post(binding_account).get(get_user_bindings))
+        .route("/:provider", post(complete_binding))
+        .route("/:provider/start", post(begin_binding))
+        .route("/:provider/verify", get(verify_binding))
+        .route("/:provider/remove", post(unbind))
+        .route("/:provider/rebind", post(rebind))
+        .layer(middleware::from_fn_with_state(
+            state,
+            middlewares::auth_middleware,
+        ))
+}
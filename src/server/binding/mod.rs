@@ -0,0 +1,6 @@
+mod binding_handler;
+mod binding_message;
+mod binding_router;
+pub mod provider;
+
+pub use binding_router::binding_router;
@@ -0,0 +1,415 @@
+use super::binding_message::{BeginBindingRequest, CompleteBindingRequest};
+use crate::{
+    common::{
+        config::{Config, EmailBindingConfig, OAuthProviderConfig},
+        consts,
+        error::{AppError, AppResult},
+    },
+    database::services::binding::BindingTokens,
+    helpers::mailer::Mailer,
+    server::auth::{
+        auth_service::{PendingBinding, RedisClient},
+        twitter_client::TwitterClient,
+    },
+};
+use async_trait::async_trait;
+use oauth2::{
+    basic::BasicClient, AuthUrl, ClientId, ClientSecret, CsrfToken, PkceCodeChallenge, RedirectUrl, Scope,
+    TokenUrl,
+};
+use rand::{distributions::Alphanumeric, Rng};
+use serde_json::Value;
+use std::{collections::HashMap, sync::Arc};
+use validator::ValidateEmail;
+
+/// An account a provider vouched for, ready to be bound.
+#[derive(Debug, Clone)]
+pub struct SocialAccount {
+    pub external_id: String,
+    pub name: String,
+    pub user_name: String,
+    pub image_url: Option<String>,
+    //only for providers whose api is called after binding
+    pub tokens: Option<BindingTokens>,
+}
+
+/// A kind of account that can be bound to a Lamport ID.
+///
+/// Binding takes two steps: `begin` stores a `PendingBinding` under a state and tells the
+/// client where to prove the account, `complete` turns the consumed `PendingBinding` and the
+/// client's answer into the proven account.
+#[async_trait]
+pub trait SocialProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    async fn begin(&self, redis: &RedisClient, lamport_id: &str, req: &BeginBindingRequest) -> AppResult<Value>;
+
+    async fn complete(&self, pending: PendingBinding, req: &CompleteBindingRequest) -> AppResult<SocialAccount>;
+}
+
+fn oauth_client(config: &OAuthProviderConfig) -> AppResult<BasicClient> {
+    Ok(BasicClient::new(
+        ClientId::new(config.client_id.clone()),
+        Some(ClientSecret::new(config.client_secret.clone())),
+        AuthUrl::new(config.authorize_url.clone())?,
+        Some(TokenUrl::new(config.token_url.clone())?),
+    )
+    .set_redirect_uri(RedirectUrl::new(config.redirect_url.clone())?))
+}
+
+//authorize url with a fresh state and pkce challenge, the verifier waits in redis for the callback
+async fn begin_oauth(
+    redis: &RedisClient,
+    provider: &str,
+    lamport_id: &str,
+    oauth: &BasicClient,
+    scope: &str,
+) -> AppResult<Value> {
+    let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
+
+    let (authorize_url, csrf_token) = oauth
+        .authorize_url(CsrfToken::new_random)
+        .add_scopes(scope.split_whitespace().map(|s| Scope::new(s.to_string())))
+        .set_pkce_challenge(pkce_challenge)
+        .url();
+
+    let pending = PendingBinding {
+        provider: provider.to_string(),
+        lamport_id: lamport_id.to_string(),
+        verifier: Some(pkce_verifier.secret().to_string()),
+        email: None,
+    };
+    redis
+        .cache_pending_binding(csrf_token.secret(), &pending, consts::OAUTH_STATE_EXPIRATION)
+        .await?;
+
+    Ok(serde_json::json!({
+        "state": csrf_token.secret(),
+        "authorize_url": authorize_url.to_string()
+    }))
+}
+
+fn required<'a>(value: Option<&'a String>, what: &str) -> AppResult<&'a str> {
+    value
+        .map(|v| v.as_str())
+        .ok_or(AppError::InputValidateError(format!("{} not found", what)))
+}
+
+//exchange a twitter code and fetch the account, the tokens are sealed for storage
+pub async fn twitter_account(
+    client: &TwitterClient,
+    code: &str,
+    redirect_uri: &str,
+    code_verifier: &str,
+) -> AppResult<SocialAccount> {
+    let token = client.exchange_code(code, redirect_uri, code_verifier).await?;
+    let info = client.get_me(token.access_token.as_str()).await?;
+
+    Ok(SocialAccount {
+        external_id: info.id,
+        name: info.name,
+        user_name: info.username,
+        image_url: Some(info.profile_image_url),
+        tokens: Some(client.seal(&token)?),
+    })
+}
+
+pub struct TwitterProvider {
+    client: TwitterClient,
+    oauth: BasicClient,
+    redirect_url: String,
+    scope: String,
+}
+
+#[async_trait]
+impl SocialProvider for TwitterProvider {
+    fn name(&self) -> &'static str {
+        consts::PROVIDER_TWITTER
+    }
+
+    async fn begin(&self, redis: &RedisClient, lamport_id: &str, _req: &BeginBindingRequest) -> AppResult<Value> {
+        begin_oauth(redis, self.name(), lamport_id, &self.oauth, &self.scope).await
+    }
+
+    async fn complete(&self, pending: PendingBinding, req: &CompleteBindingRequest) -> AppResult<SocialAccount> {
+        twitter_account(
+            &self.client,
+            required(req.code.as_ref(), "code")?,
+            &self.redirect_url,
+            required(pending.verifier.as_ref(), "verifier")?,
+        )
+        .await
+    }
+}
+
+//string or number field of a json profile
+fn profile_field(profile: &Value, key: &str) -> Option<String> {
+    match profile.get(key)? {
+        Value::String(s) if !s.is_empty() => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+fn github_account(profile: &Value) -> Option<SocialAccount> {
+    let login = profile_field(profile, "login")?;
+
+    Some(SocialAccount {
+        external_id: profile_field(profile, "id")?,
+        name: profile_field(profile, "name").unwrap_or_else(|| login.clone()),
+        user_name: login,
+        image_url: profile_field(profile, "avatar_url"),
+        tokens: None,
+    })
+}
+
+fn discord_account(profile: &Value) -> Option<SocialAccount> {
+    let id = profile_field(profile, "id")?;
+    let username = profile_field(profile, "username")?;
+
+    Some(SocialAccount {
+        name: profile_field(profile, "global_name").unwrap_or_else(|| username.clone()),
+        image_url: profile_field(profile, "avatar")
+            .map(|hash| format!("https://cdn.discordapp.com/avatars/{}/{}.png", id, hash)),
+        external_id: id,
+        user_name: username,
+        tokens: None,
+    })
+}
+
+/// A plain oauth2 provider, the account only has to be proven once so no token is kept.
+pub struct OAuthProvider {
+    name: &'static str,
+    config: OAuthProviderConfig,
+    oauth: BasicClient,
+    http: reqwest::Client,
+    account: fn(&Value) -> Option<SocialAccount>,
+}
+
+impl OAuthProvider {
+    fn new(
+        name: &'static str,
+        config: &OAuthProviderConfig,
+        account: fn(&Value) -> Option<SocialAccount>,
+    ) -> AppResult<Self> {
+        let http = reqwest::Client::builder()
+            //the github api rejects requests without one
+            .user_agent("lamport-id")
+            .build()
+            .map_err(|e| AppError::CustomError(e.to_string()))?;
+
+        Ok(Self {
+            name,
+            config: config.clone(),
+            oauth: oauth_client(config)?,
+            http,
+            account,
+        })
+    }
+
+    async fn exchange_code(&self, code: &str, code_verifier: &str) -> AppResult<String> {
+        let response = self
+            .http
+            .post(self.config.token_url.as_str())
+            .header("Accept", "application/json")
+            .form(&[
+                ("code", code),
+                ("grant_type", "authorization_code"),
+                ("client_id", self.config.client_id.as_str()),
+                ("client_secret", self.config.client_secret.as_str()),
+                ("redirect_uri", self.config.redirect_url.as_str()),
+                ("code_verifier", code_verifier),
+            ])
+            .send()
+            .await
+            .map_err(|_e| AppError::RequestError("failed to exchange code".to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::RequestError(format!(
+                "Failed to get {} token. Status: {}",
+                self.name,
+                response.status()
+            )));
+        }
+
+        //github answers errors with 200 and no access_token
+        let token: Value = response
+            .json()
+            .await
+            .map_err(|e| AppError::CustomError(e.to_string() + "Failed to parse token"))?;
+
+        profile_field(&token, "access_token").ok_or(AppError::RequestError(format!(
+            "Failed to get {} token: {}",
+            self.name,
+            profile_field(&token, "error").unwrap_or_default()
+        )))
+    }
+}
+
+#[async_trait]
+impl SocialProvider for OAuthProvider {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    async fn begin(&self, redis: &RedisClient, lamport_id: &str, _req: &BeginBindingRequest) -> AppResult<Value> {
+        begin_oauth(redis, self.name, lamport_id, &self.oauth, &self.config.scope).await
+    }
+
+    async fn complete(&self, pending: PendingBinding, req: &CompleteBindingRequest) -> AppResult<SocialAccount> {
+        let access_token = self
+            .exchange_code(
+                required(req.code.as_ref(), "code")?,
+                required(pending.verifier.as_ref(), "verifier")?,
+            )
+            .await?;
+
+        let response = self
+            .http
+            .get(self.config.userinfo_url.as_str())
+            .header("Accept", "application/json")
+            .bearer_auth(&access_token)
+            .send()
+            .await
+            .map_err(|_e| AppError::RequestError("failed to get user info".to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::RequestError("non user info in response".to_string()));
+        }
+
+        let profile: Value = response
+            .json()
+            .await
+            .map_err(|e| AppError::CustomError(e.to_string() + "Failed to parse user info"))?;
+
+        (self.account)(&profile).ok_or(AppError::RequestError(format!("incomplete {} profile", self.name)))
+    }
+}
+
+/// Proves an email address with a single-use link sent to it.
+pub struct EmailProvider {
+    config: EmailBindingConfig,
+    mailer: Arc<dyn Mailer>,
+}
+
+#[async_trait]
+impl SocialProvider for EmailProvider {
+    fn name(&self) -> &'static str {
+        consts::PROVIDER_EMAIL
+    }
+
+    async fn begin(&self, redis: &RedisClient, lamport_id: &str, req: &BeginBindingRequest) -> AppResult<Value> {
+        let email = required(req.email.as_ref(), "email")?.trim().to_lowercase();
+        if !email.validate_email() {
+            return Err(AppError::InputValidateError("invalid email".into()));
+        }
+        redis.claim_email_cooldown(lamport_id, &email).await?;
+
+        let token: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(48)
+            .map(char::from)
+            .collect();
+
+        let pending = PendingBinding {
+            provider: self.name().to_string(),
+            lamport_id: lamport_id.to_string(),
+            verifier: None,
+            email: Some(email.clone()),
+        };
+        redis.cache_pending_binding(&token, &pending, self.config.expiration).await?;
+
+        let mut link = url::Url::parse(&self.config.link_url)?;
+        link.query_pairs_mut().append_pair("token", &token);
+
+        self.mailer
+            .send(
+                &email,
+                "Confirm your email for Lamport ID",
+                &format!(
+                    "Open this link to bind {} to Lamport ID {}:\n\n{}\n\nThe link expires in {} minutes.",
+                    email,
+                    lamport_id,
+                    link,
+                    self.config.expiration / 60
+                ),
+            )
+            .await?;
+
+        Ok(serde_json::json!({
+            "sent_to": email,
+            "expires_in": self.config.expiration
+        }))
+    }
+
+    async fn complete(&self, pending: PendingBinding, _req: &CompleteBindingRequest) -> AppResult<SocialAccount> {
+        let email = required(pending.email.as_ref(), "email")?.to_string();
+
+        Ok(SocialAccount {
+            external_id: email.clone(),
+            name: email.clone(),
+            user_name: email,
+            image_url: None,
+            tokens: None,
+        })
+    }
+}
+
+/// The providers enabled in config, twitter is always there.
+#[derive(Clone)]
+pub struct SocialProviders(HashMap<&'static str, Arc<dyn SocialProvider>>);
+
+impl SocialProviders {
+    pub fn new(config: &Config, twitter: TwitterClient, oauth: BasicClient, mailer: Arc<dyn Mailer>) -> AppResult<Self> {
+        let mut providers: HashMap<&'static str, Arc<dyn SocialProvider>> = HashMap::new();
+
+        providers.insert(
+            consts::PROVIDER_TWITTER,
+            Arc::new(TwitterProvider {
+                client: twitter,
+                oauth,
+                redirect_url: config.auth.redirect_url.clone(),
+                scope: config.twitter.scope.clone(),
+            }),
+        );
+
+        if let Some(github) = config.social.github.as_ref() {
+            providers.insert(
+                consts::PROVIDER_GITHUB,
+                Arc::new(OAuthProvider::new(consts::PROVIDER_GITHUB, github, github_account)?),
+            );
+        }
+
+        if let Some(discord) = config.social.discord.as_ref() {
+            providers.insert(
+                consts::PROVIDER_DISCORD,
+                Arc::new(OAuthProvider::new(consts::PROVIDER_DISCORD, discord, discord_account)?),
+            );
+        }
+
+        if let Some(email) = config.social.email.as_ref() {
+            providers.insert(
+                consts::PROVIDER_EMAIL,
+                Arc::new(EmailProvider {
+                    config: email.clone(),
+                    mailer,
+                }),
+            );
+        }
+
+        Ok(Self(providers))
+    }
+
+    pub fn get(&self, name: &str) -> AppResult<Arc<dyn SocialProvider>> {
+        self.0
+            .get(name)
+            .cloned()
+            .ok_or(AppError::InputValidateError(format!("unknown provider {}", name)))
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        let mut names: Vec<&'static str> = self.0.keys().copied().collect();
+        names.sort();
+        names
+    }
+}
@@ -1,5 +1,5 @@
 use super::events_message::*;
-use crate::{app::SharedState, common::error::AppResult, server::middlewares::AuthToken};
+use crate::{app::SharedState, common::error::AppResult, server::middlewares::AuthClaims};
 use axum::{debug_handler, extract::State, extract::Path, extract::Query, Json};
 use std::convert::Into;
 
@@ -13,7 +13,7 @@ pub struct GetEventListRequest {
 #[debug_handler]
 pub async fn get_events(
     State(state): State<SharedState>,
-    AuthToken(user): AuthToken,
+    AuthClaims(_claim): AuthClaims,
     Query(GetEventListRequest { offset, limit }): Query<GetEventListRequest>,
     Path(lamport_id): Path<String>,
 ) -> AppResult<Json<serde_json::Value>> {
@@ -28,8 +28,8 @@ pub async fn get_events(
                 Event {
                     event_id: event.event_id,
                     lamport_id: event.lamport_id,
+                    content: public_content(event.etype.as_str(), event.content.as_str()),
                     event_type: event.etype,
-                    content: event.content,
                     created_at: event.created_at.into(),
                 }
             }).collect::<Vec<_>>()
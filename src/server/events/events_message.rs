@@ -1,5 +1,20 @@
+use crate::common::consts;
 use serde::{Deserialize, Serialize};
 
+//binding entries written before they stopped naming the account or address only show the provider
+pub fn public_content(event_type: &str, content: &str) -> String {
+    if event_type != consts::EVENT_TYPE_BINDING {
+        return content.to_string();
+    }
+    if content.starts_with("Linked address ") {
+        return "Linked an address".to_string();
+    }
+    match content.split(' ').collect::<Vec<_>>().as_slice() {
+        [verb @ ("Bound" | "Unbound"), provider, _, ..] => format!("{} {}", verb, provider),
+        _ => content.to_string(),
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Event {
     pub event_id: String,
@@ -8,3 +23,18 @@ pub struct Event {
     pub content: String,
     pub created_at: chrono::DateTime<chrono::Utc>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_public_content() {
+        let binding = consts::EVENT_TYPE_BINDING;
+        assert_eq!(public_content(binding, "Bound twitter alice"), "Bound twitter");
+        assert_eq!(public_content(binding, "Unbound email alice@example.com"), "Unbound email");
+        assert_eq!(public_content(binding, "Bound github"), "Bound github");
+        assert_eq!(public_content(binding, "Linked address 0xabc"), "Linked an address");
+        assert_eq!(public_content(consts::EVENT_TYPE_VOTE, "Bound to win"), "Bound to win");
+    }
+}
@@ -1,5 +1,5 @@
 use super::group_message::*;
-use crate::{app::SharedState, common::error::AppResult, server::middlewares::AuthToken};
+use crate::{app::SharedState, common::error::AppResult, server::middlewares::AuthClaims};
 use axum::{debug_handler, extract::Json as EJson, extract::State, extract::Query, Json};
 use std::convert::Into;
 
@@ -7,7 +7,7 @@ use std::convert::Into;
 #[debug_handler]
 pub async fn create_group(
     State(state): State<SharedState>,
-    AuthToken(user): AuthToken,
+    AuthClaims(claim): AuthClaims,
     EJson(CreateGroupRequest {
         name,
         logo,
@@ -16,9 +16,6 @@ pub async fn create_group(
         twitter,
     }): EJson<CreateGroupRequest>,
 ) -> AppResult<Json<serde_json::Value>> {
-    let client = state.jwt_handler.clone();
-    let claim = client.decode_token(user).unwrap();
-
     let new_group = state.store.create_group(name, logo, description, website, twitter, claim.sub).await?;
     let group_info = GroupInfo::from(new_group);
 
@@ -31,7 +28,7 @@ pub async fn create_group(
 #[debug_handler]
 pub async fn get_group_list(
     State(state): State<SharedState>,
-    //AuthToken(user): AuthToken,
+    //AuthClaims(claim): AuthClaims,
     Query(GetGroupListRequest { offset, limit }): Query<GetGroupListRequest>,
 ) -> AppResult<Json<serde_json::Value>> {
     let groups = state.store.get_group_list(offset, limit).await?;
@@ -0,0 +1,107 @@
+use super::lamport_id_message::*;
+use crate::{app::SharedState, common::error::AppResult, server::middlewares::AuthClaims};
+use axum::{debug_handler, extract::Json as EJson, extract::Path, extract::Query, extract::State, Json};
+
+#[debug_handler]
+pub async fn reserve_range(
+    State(state): State<SharedState>,
+    AuthClaims(claim): AuthClaims,
+    EJson(ReserveRangeRequest { start, end, reason }): EJson<ReserveRangeRequest>,
+) -> AppResult<Json<serde_json::Value>> {
+    let reservation = state
+        .store
+        .reserve_lamport_id_range(start, end, reason.as_str(), claim.sub.as_str())
+        .await?;
+
+    Ok(Json(serde_json::json!({
+        "result": ReservationInfo::from(reservation)
+    })))
+}
+
+#[debug_handler]
+pub async fn release_range(
+    State(state): State<SharedState>,
+    AuthClaims(claim): AuthClaims,
+    Path(id): Path<i32>,
+) -> AppResult<Json<serde_json::Value>> {
+    let reservation = state.store.release_lamport_id_range(id, claim.sub.as_str()).await?;
+
+    Ok(Json(serde_json::json!({
+        "result": ReservationInfo::from(reservation)
+    })))
+}
+
+#[debug_handler]
+pub async fn get_reservations(
+    State(state): State<SharedState>,
+) -> AppResult<Json<serde_json::Value>> {
+    let reservations = state.store.get_lamport_id_reservations().await?;
+
+    Ok(Json(serde_json::json!({
+        "result": {
+            "count": reservations.len(),
+            "reservations": reservations.into_iter().map(ReservationInfo::from).collect::<Vec<ReservationInfo>>()
+        }
+    })))
+}
+
+#[debug_handler]
+pub async fn assign_lamport_id(
+    State(state): State<SharedState>,
+    AuthClaims(claim): AuthClaims,
+    EJson(AssignLamportIdRequest { lamport_id, address, reason }): EJson<AssignLamportIdRequest>,
+) -> AppResult<Json<serde_json::Value>> {
+    let assignment = state
+        .store
+        .assign_lamport_id(lamport_id, address.as_str(), reason.as_str(), claim.sub.as_str())
+        .await?;
+
+    Ok(Json(serde_json::json!({
+        "result": AssignmentInfo::from(assignment)
+    })))
+}
+
+#[debug_handler]
+pub async fn release_assignment(
+    State(state): State<SharedState>,
+    AuthClaims(claim): AuthClaims,
+    Path(lamport_id): Path<i64>,
+) -> AppResult<Json<serde_json::Value>> {
+    let assignment = state
+        .store
+        .release_lamport_id_assignment(lamport_id, claim.sub.as_str())
+        .await?;
+
+    Ok(Json(serde_json::json!({
+        "result": AssignmentInfo::from(assignment)
+    })))
+}
+
+#[debug_handler]
+pub async fn get_assignments(
+    State(state): State<SharedState>,
+) -> AppResult<Json<serde_json::Value>> {
+    let assignments = state.store.get_lamport_id_assignments().await?;
+
+    Ok(Json(serde_json::json!({
+        "result": {
+            "count": assignments.len(),
+            "assignments": assignments.into_iter().map(AssignmentInfo::from).collect::<Vec<AssignmentInfo>>()
+        }
+    })))
+}
+
+#[debug_handler]
+pub async fn get_audit_logs(
+    State(state): State<SharedState>,
+    Query(GetAuditLogsRequest { offset, limit }): Query<GetAuditLogsRequest>,
+) -> AppResult<Json<serde_json::Value>> {
+    let logs = state.store.get_lamport_id_audit_logs(offset, limit).await?;
+
+    Ok(Json(serde_json::json!({
+        "result": {
+            "count": logs.len(),
+            "logs": logs.into_iter().map(AuditInfo::from).collect::<Vec<AuditInfo>>()
+        }
+    })))
+}
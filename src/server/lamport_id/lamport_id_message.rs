@@ -0,0 +1,93 @@
+use crate::database::entities::{lamport_id_assignments, lamport_id_audit, lamport_id_reservations};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize)]
+pub struct ReserveRangeRequest {
+    pub start: i64,
+    pub end: i64,
+    pub reason: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AssignLamportIdRequest {
+    pub lamport_id: i64,
+    pub address: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GetAuditLogsRequest {
+    pub offset: i64,
+    pub limit: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReservationInfo {
+    pub id: i32,
+    pub start: i64,
+    pub end: i64,
+    pub reason: String,
+    pub created_by: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<lamport_id_reservations::Model> for ReservationInfo {
+    fn from(model: lamport_id_reservations::Model) -> Self {
+        Self {
+            id: model.id,
+            start: model.start_value,
+            end: model.end_value,
+            reason: model.reason,
+            created_by: model.created_by,
+            created_at: model.created_at.into(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AssignmentInfo {
+    pub lamport_id: i64,
+    pub address: String,
+    pub reason: String,
+    pub assigned_by: String,
+    pub claimed: bool,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<lamport_id_assignments::Model> for AssignmentInfo {
+    fn from(model: lamport_id_assignments::Model) -> Self {
+        Self {
+            lamport_id: model.lamport_value,
+            address: model.address,
+            reason: model.reason,
+            assigned_by: model.assigned_by,
+            claimed: model.claimed,
+            created_at: model.created_at.into(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuditInfo {
+    pub action: String,
+    pub start: i64,
+    pub end: i64,
+    pub address: Option<String>,
+    pub reason: Option<String>,
+    pub operator: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<lamport_id_audit::Model> for AuditInfo {
+    fn from(model: lamport_id_audit::Model) -> Self {
+        Self {
+            action: model.action,
+            start: model.start_value,
+            end: model.end_value,
+            address: model.address,
+            reason: model.reason,
+            operator: model.operator,
+            created_at: model.created_at.into(),
+        }
+    }
+}
@@ -0,0 +1,18 @@
+use super::lamport_id_handler::*;
+use crate::app::SharedState;
+use crate::common::rbac::Permission;
+use crate::server::middlewares::{self, PermissionGuard};
+use axum::{middleware, routing::{get, post}, Router};
+
+pub fn lamport_id_router(state: SharedState) -> Router<SharedState> {
+    Router::new()
+        .route("/reservations", post(reserve_range).get(get_reservations))
+        .route("/reservations/:id/release", post(release_range))
+        .route("/assignments", post(assign_lamport_id).get(get_assignments))
+        .route("/assignments/:lamport_id/release", post(release_assignment))
+        .route("/audit", get(get_audit_logs))
+        .layer(middleware::from_fn_with_state(
+            PermissionGuard::new(state, Permission::ManageLamportIds),
+            middlewares::permission_middleware,
+        ))
+}
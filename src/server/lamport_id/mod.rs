@@ -0,0 +1,5 @@
+mod lamport_id_handler;
+mod lamport_id_message;
+mod lamport_id_router;
+
+pub use lamport_id_router::lamport_id_router;
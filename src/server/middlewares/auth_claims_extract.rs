@@ -0,0 +1,91 @@
+//! Extractors that turn the `Authorization` header into validated claims.
+//!
+//! # Overview
+//! - `AuthClaims` decodes the bearer token with the application's `JwtHandler` and
+//!   checks that its login session has not been revoked.
+//! - `AuthUser` additionally loads the caller's `users::Model`.
+//! - Failures are returned as `AppError`, so the JSON error body tells an expired
+//!   token (`token expired`) apart from a missing, forged or revoked one.
+//!
+//! # Example
+//! ```ignore
+//! async fn protected_route(AuthClaims(claim): AuthClaims) -> String {
+//!     format!("Your lamport id is: {}", claim.sub)
+//! }
+//! ```
+
+use super::{jwt::jwt_handler::Claims, AuthToken};
+use crate::{
+    app::SharedState,
+    common::error::AppError,
+    database::entities::users,
+    server::auth::auth_service::RedisClient,
+};
+use axum::{
+    async_trait,
+    extract::{FromRef, FromRequestParts},
+    http::request::Parts,
+};
+use jsonwebtoken::errors::ErrorKind;
+
+/// Claims of a valid access token whose session is still active.
+pub struct AuthClaims(pub Claims);
+
+/// The authenticated caller together with its user record.
+pub struct AuthUser {
+    pub claims: Claims,
+    pub user: users::Model,
+}
+
+//generic over the router state so layers holding more than `SharedState` can use it too
+#[async_trait]
+impl<S> FromRequestParts<S> for AuthClaims
+where
+    SharedState: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let state = SharedState::from_ref(state);
+        let AuthToken(token) = AuthToken::from_request_parts(parts, &state)
+            .await
+            .map_err(|_| AppError::Unauthorized("missing bearer token".into()))?;
+
+        let claim = state.jwt_handler.decode_token(token).map_err(|e| match e.kind() {
+            ErrorKind::ExpiredSignature => AppError::TokenExpired,
+            _ => AppError::Unauthorized("invalid token".into()),
+        })?;
+
+        // Tokens issued before sessions were introduced carry no sid and could not be revoked.
+        let sid = claim
+            .sid
+            .as_deref()
+            .ok_or_else(|| AppError::Unauthorized("token has no session, log in again".into()))?;
+        if !RedisClient::from(state.redis.clone()).is_session_active(sid).await? {
+            return Err(AppError::Unauthorized("session has been revoked".into()));
+        }
+
+        Ok(AuthClaims(claim))
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for AuthUser
+where
+    SharedState: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let AuthClaims(claims) = AuthClaims::from_request_parts(parts, state).await?;
+        let state = SharedState::from_ref(state);
+        let user = state.store.get_user_by_uid(&claims.sub).await?;
+        if user.deleted_at.is_some() {
+            return Err(AppError::Unauthorized("account has been deleted".into()));
+        }
+
+        Ok(AuthUser { claims, user })
+    }
+}
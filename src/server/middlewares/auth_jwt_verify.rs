@@ -5,10 +5,11 @@
 //! - Tokens are decoded and verified using the application's shared JWT handler.
 //! - On successful validation, the request is passed to the next handler in the processing pipeline.
 //! - If the token is invalid or expired, an appropriate HTTP status code is returned.
+//! - Tokens of revoked sessions are rejected as well, see `AuthClaims`.
 //!
 //! # Key Components
 //! - **`auth_middleware`**: The primary middleware function that performs the authentication.
-//! - **`AuthToken`**: A custom extractor for parsing JWT tokens from requests.
+//! - **`AuthClaims`**: A custom extractor that decodes and validates JWT tokens from requests.
 //! - **`SharedState`**: Application-wide shared state, including the JWT handler.
 //!
 //! # Usage
@@ -16,7 +17,7 @@
 //! 2. Ensure the shared state (`SharedState`) contains a properly configured JWT handler.
 //!
 //! # Example
-//! ```rust
+//! ```ignore
 //! use axum::{Router, routing::get, middleware};
 //! use crate::auth_middleware::auth_middleware;
 //!
@@ -29,46 +30,27 @@
 //! }
 //! ```
 
-use super::AuthToken;
-use crate::app::SharedState;
-use axum::{
-    body::Body, extract::State, http::Request, http::StatusCode, middleware::Next,
-    response::Response,
-};
-use jsonwebtoken::errors::ErrorKind;
+use super::AuthClaims;
+use axum::{body::Body, http::Request, middleware::Next, response::Response};
 use tracing;
 
 /// Middleware for authenticating requests using JWT tokens.
 ///
 /// # Arguments
-/// - `State(state)`: Shared application state, which contains the JWT handler.
-/// - `AuthToken(user)`: Extracted JWT token from the `Authorization` header of the request.
+/// - `AuthClaims(claim)`: Claims of the validated JWT token from the `Authorization` header.
 /// - `req`: The incoming HTTP request.
 /// - `next`: The next middleware or handler in the processing pipeline.
 ///
 /// # Returns
 /// - On successful authentication, forwards the request to the next handler and returns its response.
-/// - On authentication failure, returns an appropriate `StatusCode` error response.
+/// - On authentication failure, the `AuthClaims` rejection (`401` with an expired/invalid error body) is returned.
 pub async fn auth_middleware(
-    State(state): State<SharedState>,
-    AuthToken(user): AuthToken,
+    AuthClaims(claim): AuthClaims,
     req: Request<Body>,
     next: Next,
-) -> Result<Response, StatusCode> {
-    let client = state.jwt_handler.clone();
-
-    // Decode and verify the provided JWT token.
-    match client.decode_token(user) {
-        Ok(claim) => tracing::info!("Authenticate pass, extract claim: {:?}", claim),
-        Err(e) => {
-            if e.kind().eq(&ErrorKind::ExpiredSignature) {
-                return Err(StatusCode::UNAUTHORIZED);
-            } else {
-                return Err(StatusCode::UNAUTHORIZED);
-            }
-        }
-    }
+) -> Response {
+    tracing::info!("Authenticate pass, extract claim: {:?}", claim);
 
     // If authentication succeeds, pass the request to the next handler in the pipeline.
-    Ok(next.run(req).await)
+    next.run(req).await
 }
@@ -16,7 +16,7 @@
 //! 2. Use `AuthToken` in your handler functions to access and validate JWT tokens.
 //!
 //! # Example
-//! ```rust
+//! ```ignore
 //! use axum::{Router, routing::get, extract::Extension};
 //! use crate::auth::AuthToken;
 //! use http::StatusCode;
@@ -31,7 +31,7 @@
 //!
 //! # Notes
 //! - This module assumes the `Authorization` header follows the `Bearer <token>` format.
-//! - Ensure proper error handling for cases where tokens are invalid or expired.
+//! - The token is not decoded here, use `AuthClaims` / `AuthUser` to get validated claims.
 
 use axum::{
     async_trait,
@@ -66,7 +66,7 @@ where
     ///
     /// # Example
     /// Given the following HTTP header:
-    /// ```
+    /// ```text
     /// Authorization: Bearer <token>
     /// ```
     /// This method will extract `<token>` and encapsulate it in an `AuthToken`.
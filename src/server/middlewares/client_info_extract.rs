@@ -0,0 +1,94 @@
+//! Extracts the device and address of the caller, recorded on login sessions.
+//!
+//! The client address is the socket peer. When the peer is one of `server.trusted_proxies`
+//! (the OpenResty in front of the service), the right-most `X-Forwarded-For` entry that is
+//! not a trusted proxy is used instead, since entries to its left are whatever the client sent.
+
+use crate::app::SharedState;
+use axum::{
+    async_trait,
+    extract::{ConnectInfo, FromRef, FromRequestParts},
+    http::{request::Parts, HeaderMap, StatusCode},
+};
+use std::net::{IpAddr, SocketAddr};
+
+const UNKNOWN: &str = "unknown";
+
+/// Device (`User-Agent`) and IP of the request.
+#[derive(Debug, Clone)]
+pub struct ClientInfo {
+    pub device: String,
+    pub ip: String,
+}
+
+fn header_value(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+//forwarding headers are only believed when they were added by a trusted proxy
+fn client_ip(peer: Option<IpAddr>, headers: &HeaderMap, trusted_proxies: &[IpAddr]) -> String {
+    let Some(peer) = peer else {
+        return UNKNOWN.to_string();
+    };
+    if !trusted_proxies.contains(&peer) {
+        return peer.to_string();
+    }
+
+    let forwarded = header_value(headers, "x-forwarded-for").and_then(|v| {
+        v.rsplit(',')
+            .map(|hop| hop.trim().parse::<IpAddr>().ok())
+            .find(|hop| !matches!(hop, Some(ip) if trusted_proxies.contains(ip)))
+            .flatten()
+    });
+
+    forwarded
+        .or_else(|| header_value(headers, "x-real-ip").and_then(|v| v.parse().ok()))
+        .unwrap_or(peer)
+        .to_string()
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for ClientInfo
+where
+    SharedState: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let state = SharedState::from_ref(state);
+        let device = header_value(&parts.headers, "user-agent").unwrap_or_else(|| UNKNOWN.to_string());
+
+        let peer = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip());
+        let ip = client_ip(peer, &parts.headers, &state.config.server.trusted_proxies);
+
+        Ok(ClientInfo { device, ip })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_ip() {
+        let proxy: IpAddr = "10.0.0.2".parse().unwrap();
+        let client: IpAddr = "203.0.113.7".parse().unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", "198.51.100.1, 203.0.113.7, 10.0.0.2".parse().unwrap());
+
+        //the spoofed left-most entry is skipped, the trusted hops are peeled from the right
+        assert_eq!(client_ip(Some(proxy), &headers, &[proxy]), "203.0.113.7");
+        //a client talking to the service directly can not forge its address
+        assert_eq!(client_ip(Some(client), &headers, &[proxy]), "203.0.113.7");
+        assert_eq!(client_ip(Some(client), &headers, &[]), "203.0.113.7");
+        assert_eq!(client_ip(None, &headers, &[proxy]), UNKNOWN);
+    }
+}
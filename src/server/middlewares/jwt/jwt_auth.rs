@@ -1,10 +1,10 @@
-///
-///         let auth = jwt_auth::Authorization {
-///             jwt_handler: share_state.0.read().await.jwt_handler.clone(),
-///         };
-///
-/// .layer(ValidateRequestHeaderLayer::custom(auth));
-///
+//
+//         let auth = jwt_auth::Authorization {
+//             jwt_handler: share_state.0.read().await.jwt_handler.clone(),
+//         };
+//
+// .layer(ValidateRequestHeaderLayer::custom(auth));
+//
 use super::jwt_handler::JwtHandler;
 use axum::body::Body;
 use axum::extract::Request;
@@ -20,6 +20,7 @@ pub struct Authorization {
 
 impl<B> ValidateRequest<B> for Authorization {
     type ResponseBody = Body;
+    #[allow(clippy::result_large_err)]
     fn validate(&mut self, request: &mut Request<B>) -> Result<(), Response<Self::ResponseBody>> {
         request
             .headers()
@@ -30,7 +31,7 @@ impl<B> ValidateRequest<B> for Authorization {
                     token
                         .to_string()
                         .strip_prefix("Bearer ")
-                        .unwrap_or(&token)
+                        .unwrap_or(token)
                         .to_string(),
                 )
             })
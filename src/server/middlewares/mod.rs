@@ -1,11 +1,17 @@
+mod auth_claims_extract;
 mod auth_jwt_verify;
 mod auth_token_extract;
+mod client_info_extract;
 mod handle_error;
 pub mod jwt;
 mod eip191_verify;
+mod permission_verify;
 
 pub use auth_jwt_verify::auth_middleware;
 pub use eip191_verify::eip191_middleware;
+pub use auth_claims_extract::{AuthClaims, AuthUser};
 pub use auth_token_extract::AuthToken;
+pub use client_info_extract::ClientInfo;
 pub use handle_error::handle_error;
+pub use permission_verify::{permission_middleware, PermissionGuard};
 pub use jwt::*;
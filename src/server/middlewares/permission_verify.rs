@@ -0,0 +1,67 @@
+use super::AuthClaims;
+use crate::{
+    app::SharedState,
+    common::{error::AppError, rbac::Permission},
+};
+use axum::{
+    body::Body,
+    extract::{FromRef, State},
+    http::Request,
+    middleware::Next,
+    response::Response,
+};
+
+/// State of `permission_middleware`: the application state plus the permission a route needs.
+#[derive(Clone)]
+pub struct PermissionGuard {
+    state: SharedState,
+    permission: Permission,
+}
+
+impl PermissionGuard {
+    pub fn new(state: SharedState, permission: Permission) -> Self {
+        Self { state, permission }
+    }
+}
+
+impl FromRef<PermissionGuard> for SharedState {
+    fn from_ref(guard: &PermissionGuard) -> Self {
+        guard.state.clone()
+    }
+}
+
+/// Middleware that only lets callers holding the guard's permission through.
+///
+/// Lamport IDs listed in `admin.lamport_ids` hold every permission, everyone else gets
+/// them from the roles granted in `user_roles`. The token is validated here, so it does
+/// not need to be stacked on `auth_middleware`.
+/// Returns `401` for a missing, expired or invalid token and `403` when the permission is missing.
+///
+/// # Example
+/// ```ignore
+/// Router::new()
+///     .route("/create", post(create_group))
+///     .route_layer(middleware::from_fn_with_state(
+///         PermissionGuard::new(state, Permission::CreateGroup),
+///         middlewares::permission_middleware,
+///     ))
+/// ```
+pub async fn permission_middleware(
+    State(guard): State<PermissionGuard>,
+    AuthClaims(claim): AuthClaims,
+    req: Request<Body>,
+    next: Next,
+) -> Result<Response, AppError> {
+    let allowed = guard
+        .state
+        .store
+        .has_permission(&claim.sub, guard.permission, &guard.state.config.admin.lamport_ids)
+        .await?;
+
+    if !allowed {
+        tracing::error!("permission {:?} denied for lamport id: {}", guard.permission, claim.sub);
+        return Err(AppError::Forbidden(format!("missing permission {:?}", guard.permission)));
+    }
+
+    Ok(next.run(req).await)
+}
@@ -1,15 +1,24 @@
-mod auth;
+mod admin;
+mod attestation;
+pub mod auth;
+pub mod binding;
 mod health;
+mod lamport_id;
 mod message;
 pub mod middlewares;
+pub mod notification;
+mod profile;
+mod relay;
 mod router;
+#[allow(clippy::module_inception)]
 mod server;
 mod user;
 mod webset;
 mod group;
 pub mod proposal;
-mod vote;
+pub mod vote;
 mod users;
 pub mod events;
+mod well_known;
 
 pub use server::http_server_start;
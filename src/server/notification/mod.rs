@@ -0,0 +1,7 @@
+mod notification_handler;
+mod notification_message;
+mod notification_router;
+pub mod notification_service;
+
+pub use notification_message::Notification;
+pub use notification_router::notification_router;
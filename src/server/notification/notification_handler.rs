@@ -0,0 +1,37 @@
+use super::notification_message::NotificationPreferences;
+use crate::{app::SharedState, common::error::AppResult, server::middlewares::AuthClaims};
+use axum::{debug_handler, extract::State, Json};
+
+#[debug_handler]
+pub async fn get_preferences(
+    State(state): State<SharedState>,
+    AuthClaims(claim): AuthClaims,
+) -> AppResult<Json<serde_json::Value>> {
+    let prefs = state.store.get_notification_preferences(claim.sub.as_str()).await?;
+
+    Ok(Json(serde_json::json!({
+        "result": NotificationPreferences::from(prefs)
+    })))
+}
+
+#[debug_handler]
+pub async fn update_preferences(
+    State(state): State<SharedState>,
+    AuthClaims(claim): AuthClaims,
+    Json(req): Json<NotificationPreferences>,
+) -> AppResult<Json<serde_json::Value>> {
+    let prefs = state
+        .store
+        .save_notification_preferences(
+            claim.sub.as_str(),
+            req.new_proposal,
+            req.proposal_closing,
+            req.invited,
+            req.attested,
+        )
+        .await?;
+
+    Ok(Json(serde_json::json!({
+        "result": NotificationPreferences::from(Some(prefs))
+    })))
+}
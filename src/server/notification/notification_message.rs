@@ -0,0 +1,76 @@
+use crate::database::entities::notification_preferences;
+use serde::{Deserialize, Serialize};
+
+/// Queued on `consts::NOTIFICATION_TOPIC`, the worker works out who gets it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Notification {
+    //members of the group except its creator
+    NewProposal {
+        group_id: String,
+        proposal_id: String,
+        title: String,
+        created_by: String,
+    },
+    //members of the group that have not voted yet
+    ProposalClosing {
+        group_id: String,
+        proposal_id: String,
+        title: String,
+        end_time: chrono::DateTime<chrono::Utc>,
+    },
+    //the inviter, when an account registers with its invite code
+    Invited {
+        lamport_id: String,
+        invitee: String,
+    },
+    Attested {
+        lamport_id: String,
+        attester: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationPreferences {
+    pub new_proposal: bool,
+    pub proposal_closing: bool,
+    pub invited: bool,
+    pub attested: bool,
+}
+
+//everything is on until the user saves preferences
+impl Default for NotificationPreferences {
+    fn default() -> Self {
+        Self {
+            new_proposal: true,
+            proposal_closing: true,
+            invited: true,
+            attested: true,
+        }
+    }
+}
+
+impl From<Option<notification_preferences::Model>> for NotificationPreferences {
+    fn from(prefs: Option<notification_preferences::Model>) -> Self {
+        match prefs {
+            Some(p) => Self {
+                new_proposal: p.new_proposal,
+                proposal_closing: p.proposal_closing,
+                invited: p.invited,
+                attested: p.attested,
+            },
+            None => Self::default(),
+        }
+    }
+}
+
+impl NotificationPreferences {
+    pub fn allows(&self, notification: &Notification) -> bool {
+        match notification {
+            Notification::NewProposal { .. } => self.new_proposal,
+            Notification::ProposalClosing { .. } => self.proposal_closing,
+            Notification::Invited { .. } => self.invited,
+            Notification::Attested { .. } => self.attested,
+        }
+    }
+}
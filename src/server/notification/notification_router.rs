@@ -0,0 +1,13 @@
+use super::notification_handler::*;
+use crate::app::SharedState;
+use crate::server::middlewares;
+use axum::{middleware, routing::get, Router};
+
+pub fn notification_router(state: SharedState) -> Router<SharedState> {
+    Router::new()
+        .route("/", get(get_preferences).post(update_preferences))
+        .layer(middleware::from_fn_with_state(
+            state,
+            middlewares::auth_middleware,
+        ))
+}
@@ -0,0 +1,115 @@
+use super::notification_message::{Notification, NotificationPreferences};
+use crate::{
+    common::{consts, error::AppResult},
+    database::Storage,
+    helpers::mailer::Mailer,
+    queue::msg_queue::RedisStreamPool,
+    server::auth::auth_service::RedisClient,
+};
+
+//lamport ids a notification is meant for, before preferences are applied
+async fn recipients(store: &Storage, notification: &Notification) -> AppResult<Vec<String>> {
+    Ok(match notification {
+        Notification::NewProposal { group_id, created_by, .. } => store
+            .get_group_member_ids(group_id)
+            .await?
+            .into_iter()
+            .filter(|m| m != created_by)
+            .collect(),
+        Notification::ProposalClosing { group_id, proposal_id, .. } => {
+            let mut pending = vec![];
+            for member in store.get_group_member_ids(group_id).await? {
+                if !store.is_voted_by_voter_id(member.as_str(), proposal_id).await? {
+                    pending.push(member);
+                }
+            }
+            pending
+        }
+        Notification::Invited { lamport_id, .. } | Notification::Attested { lamport_id, .. } => {
+            vec![lamport_id.clone()]
+        }
+    })
+}
+
+fn render(notification: &Notification) -> (String, String) {
+    match notification {
+        Notification::NewProposal { title, created_by, .. } => (
+            format!("New proposal: {}", title),
+            format!("Lamport ID {} posted \"{}\" in a group you take part in.", created_by, title),
+        ),
+        Notification::ProposalClosing { title, end_time, .. } => (
+            format!("Closing soon: {}", title),
+            format!("Voting on \"{}\" ends at {} and you have not voted yet.", title, end_time),
+        ),
+        Notification::Invited { invitee, .. } => (
+            "Your invite was accepted".to_string(),
+            format!("Lamport ID {} registered with your invite code.", invitee),
+        ),
+        Notification::Attested { attester, .. } => (
+            "You were attested".to_string(),
+            format!("Lamport ID {} attested that you are a real person.", attester),
+        ),
+    }
+}
+
+//mail a notification to every recipient with a verified email that wants it, returns how many were sent
+pub async fn deliver(store: &Storage, mailer: &dyn Mailer, notification: &Notification) -> AppResult<usize> {
+    let (subject, body) = render(notification);
+    let mut sent = 0;
+
+    for user in store
+        .get_users_with_verified_email(recipients(store, notification).await?)
+        .await?
+    {
+        let prefs = NotificationPreferences::from(store.get_notification_preferences(user.lamport_id.as_str()).await?);
+        if !prefs.allows(notification) {
+            continue;
+        }
+
+        match mailer.send(user.email.as_str(), subject.as_str(), body.as_str()).await {
+            Ok(_) => sent += 1,
+            Err(e) => tracing::error!("mail notification to {} err: {:?}", user.lamport_id, e),
+        }
+    }
+
+    Ok(sent)
+}
+
+//queue a closing soon notice for proposals ending within the window, each proposal once
+pub async fn queue_closing_proposals(
+    store: &Storage,
+    redis: &RedisClient,
+    queue: &RedisStreamPool,
+    window_hours: i64,
+) -> AppResult<usize> {
+    let now = chrono::Utc::now();
+    let mut queued = 0;
+
+    for proposal in store
+        .get_proposals_ending_between(now, now + chrono::Duration::hours(window_hours))
+        .await?
+    {
+        //the mark outlives the window so a proposal is not picked up again
+        if !redis
+            .mark_proposal_closing_notified(proposal.proposal_id.as_str(), (window_hours.max(1) * 7200) as u64)
+            .await?
+        {
+            continue;
+        }
+
+        queue
+            .add_queue_req_ex(
+                consts::NOTIFICATION_TOPIC,
+                Notification::ProposalClosing {
+                    group_id: proposal.group_id,
+                    proposal_id: proposal.proposal_id,
+                    title: proposal.title,
+                    end_time: proposal.end_time.into(),
+                },
+            )
+            .await?;
+        queued += 1;
+    }
+
+    Ok(queued)
+}
@@ -0,0 +1,5 @@
+mod profile_handler;
+mod profile_message;
+mod profile_router;
+
+pub use profile_router::profile_router;
@@ -0,0 +1,249 @@
+use super::profile_message::*;
+use crate::{
+    app::SharedState,
+    common::{
+        consts,
+        error::{AppError, AppResult},
+    },
+    database::{entities::users, services::profile::PrivacyOptions},
+    nostr::schema,
+    server::middlewares::AuthClaims,
+};
+use axum::{debug_handler, extract::Path, extract::Query, extract::State, Json};
+
+//the public view of user, filtered by its privacy options
+async fn public_profile(
+    state: &SharedState,
+    user: users::Model,
+    redirected_from: Option<String>,
+) -> AppResult<PublicProfile> {
+    if user.deleted_at.is_some() {
+        return Err(AppError::UserUnExisted(format!("User {} has been deleted", user.lamport_id)));
+    }
+
+    let privacy = state.store.get_privacy_options(user.lamport_id.as_str()).await?;
+    let lamport_id = user.lamport_id.as_str();
+
+    //the primary and linked addresses are published in kind 2322 events, so they are always shown
+    let mut addresses = vec![user.address.clone()];
+    addresses.extend(
+        state
+            .store
+            .get_linked_addresses_by_uid(lamport_id)
+            .await?
+            .into_iter()
+            .map(|linked| linked.address),
+    );
+
+    let bindings = if privacy.show_bindings {
+        Some(
+            state
+                .store
+                .get_social_bindings_by_user_id(lamport_id)
+                .await?
+                .into_iter()
+                .map(PublicBinding::from)
+                .collect(),
+        )
+    } else {
+        None
+    };
+
+    let attestations = if privacy.show_attestations {
+        let given = state
+            .store
+            .get_attestations_by_attester(lamport_id)
+            .await?
+            .into_iter()
+            .filter(|a| !a.revoked)
+            .count() as u64;
+        Some(PublicAttestations {
+            received: state
+                .store
+                .get_attestations_by_subject(lamport_id)
+                .await?
+                .into_iter()
+                .filter(|a| !a.revoked)
+                .map(PublicAttestation::from)
+                .collect(),
+            given,
+        })
+    } else {
+        None
+    };
+
+    let points = if privacy.show_points {
+        let points = state.store.get_user_points(lamport_id).await?;
+        Some(PublicPoints {
+            points,
+            rank: state.store.get_points_rank(points).await?,
+        })
+    } else {
+        None
+    };
+
+    let groups = if privacy.show_groups {
+        Some(
+            state
+                .store
+                .get_groups_by_member(lamport_id)
+                .await?
+                .into_iter()
+                .map(PublicGroup::from)
+                .collect(),
+        )
+    } else {
+        None
+    };
+
+    let events = if privacy.show_events {
+        Some(
+            state
+                .store
+                .get_recent_events_by_lamport_id(lamport_id, consts::PROFILE_EVENTS_LIMIT)
+                .await?
+                .into_iter()
+                //binding and address link entries stay private with the bindings
+                .filter(|e| privacy.show_bindings || e.etype != consts::EVENT_TYPE_BINDING)
+                .map(PublicEvent::from)
+                .collect(),
+        )
+    } else {
+        None
+    };
+
+    Ok(PublicProfile {
+        lamport_id: user.lamport_id,
+        user_name: user.user_name,
+        name: user.name,
+        image: user.image,
+        bio: user.bio,
+        verified: user.verified,
+        nostr_pubkey: user.nostr_pubkey,
+        created_at: user.created_at.map(|t| t.into()),
+        redirected_from,
+        addresses,
+        bindings,
+        attestations,
+        points,
+        groups,
+        events,
+    })
+}
+
+#[debug_handler]
+pub async fn get_profile_by_lamport_id(
+    State(state): State<SharedState>,
+    Path(key): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    let user = state.store.get_user_by_uid(key.as_str()).await?;
+
+    Ok(Json(serde_json::json!({
+        "result": public_profile(&state, user, None).await?
+    })))
+}
+
+// old usernames resolve to the lamport id that held them
+#[debug_handler]
+pub async fn get_profile_by_username(
+    State(state): State<SharedState>,
+    Path(key): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    let (user, redirected_from) = state.store.resolve_username(key.as_str()).await?;
+
+    Ok(Json(serde_json::json!({
+        "result": public_profile(&state, user, redirected_from).await?
+    })))
+}
+
+// primary or linked address
+#[debug_handler]
+pub async fn get_profile_by_address(
+    State(state): State<SharedState>,
+    Path(key): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    let user = state.store.get_user_by_address(key.as_str()).await?;
+
+    Ok(Json(serde_json::json!({
+        "result": public_profile(&state, user, None).await?
+    })))
+}
+
+// hex or npub nostr key
+#[debug_handler]
+pub async fn get_profile_by_nostr_pubkey(
+    State(state): State<SharedState>,
+    Path(key): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    let pubkey = nostr_sdk::PublicKey::parse(key.as_str())
+        .map_err(|_| AppError::InputValidateError("invalid nostr key".into()))?;
+    let user = state.store.get_user_by_nostr_pubkey(pubkey.to_hex().as_str()).await?;
+
+    Ok(Json(serde_json::json!({
+        "result": public_profile(&state, user, None).await?
+    })))
+}
+
+// signed nostr events published for a lamport id, newest first, as relays received them
+#[debug_handler]
+pub async fn get_nostr_events_by_lamport_id(
+    State(state): State<SharedState>,
+    Path(key): Path<String>,
+    Query(GetNostrEventsRequest { offset, limit }): Query<GetNostrEventsRequest>,
+) -> AppResult<Json<serde_json::Value>> {
+    let user = state.store.get_user_by_uid(key.as_str()).await?;
+    if user.deleted_at.is_some() {
+        return Err(AppError::UserUnExisted(format!("User {} has been deleted", user.lamport_id)));
+    }
+
+    //events carrying what the profile hides are left out too
+    let privacy = state.store.get_privacy_options(user.lamport_id.as_str()).await?;
+    let mut hidden_kinds = Vec::new();
+    if !privacy.show_bindings {
+        hidden_kinds.extend([schema::KIND_CREATE as i32, schema::KIND_UNBIND as i32]);
+    }
+    if !privacy.show_attestations {
+        hidden_kinds.extend([schema::KIND_ATTEST as i32, schema::KIND_REVOKE as i32]);
+    }
+
+    let events = state
+        .store
+        .get_nostr_events_by_lamport_id(
+            user.lamport_id.as_str(),
+            &hidden_kinds,
+            offset.max(0),
+            limit.clamp(0, consts::PROFILE_NOSTR_EVENTS_LIMIT),
+        )
+        .await?
+        .into_iter()
+        .map(PublicNostrEvent::try_from)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Json(serde_json::json!({
+        "result": {
+            "count": events.len(),
+            "events": events
+        }
+    })))
+}
+
+#[debug_handler]
+pub async fn get_privacy(
+    State(state): State<SharedState>,
+    AuthClaims(claim): AuthClaims,
+) -> AppResult<Json<serde_json::Value>> {
+    Ok(Json(serde_json::json!({
+        "result": state.store.get_privacy_options(claim.sub.as_str()).await?
+    })))
+}
+
+#[debug_handler]
+pub async fn update_privacy(
+    State(state): State<SharedState>,
+    AuthClaims(claim): AuthClaims,
+    Json(req): Json<PrivacyOptions>,
+) -> AppResult<Json<serde_json::Value>> {
+    Ok(Json(serde_json::json!({
+        "result": state.store.save_privacy_options(claim.sub.as_str(), &req).await?
+    })))
+}
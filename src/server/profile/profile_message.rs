@@ -0,0 +1,148 @@
+use crate::database::entities::{attestations, events, groups, nostr_events, social_bindings};
+use crate::server::events::events_message::public_content;
+use crate::common::consts;
+use serde::{Deserialize, Serialize};
+
+//what anyone can see of a lamport id, sections the user hides are left out
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PublicProfile {
+    pub lamport_id: String,
+    pub user_name: String,
+    pub name: String,
+    pub image: String,
+    pub bio: String,
+    pub verified: bool,
+    //hex, linked by the user so always public
+    pub nostr_pubkey: Option<String>,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+    //the old username the lookup was made with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redirected_from: Option<String>,
+    pub addresses: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bindings: Option<Vec<PublicBinding>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attestations: Option<PublicAttestations>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub points: Option<PublicPoints>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub groups: Option<Vec<PublicGroup>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub events: Option<Vec<PublicEvent>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PublicBinding {
+    pub provider: String,
+    //empty for email bindings, the address is never shown
+    pub user_name: String,
+    pub name: String,
+    pub image_url: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<social_bindings::Model> for PublicBinding {
+    fn from(binding: social_bindings::Model) -> Self {
+        let private = binding.provider == consts::PROVIDER_EMAIL;
+        Self {
+            user_name: if private { String::new() } else { binding.user_name },
+            name: if private { String::new() } else { binding.name },
+            provider: binding.provider,
+            image_url: binding.image_url,
+            created_at: binding.created_at.into(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PublicAttestations {
+    pub received: Vec<PublicAttestation>,
+    pub given: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PublicAttestation {
+    pub attester: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<attestations::Model> for PublicAttestation {
+    fn from(attestation: attestations::Model) -> Self {
+        Self {
+            attester: attestation.attester,
+            created_at: attestation.created_at.into(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PublicPoints {
+    pub points: i64,
+    pub rank: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PublicGroup {
+    pub group_id: String,
+    pub name: String,
+    pub logo: String,
+}
+
+impl From<groups::Model> for PublicGroup {
+    fn from(group: groups::Model) -> Self {
+        Self {
+            group_id: group.group_id,
+            name: group.name,
+            logo: group.logo,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PublicEvent {
+    pub event_id: String,
+    pub event_type: String,
+    pub content: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<events::Model> for PublicEvent {
+    fn from(event: events::Model) -> Self {
+        Self {
+            event_id: event.event_id,
+            content: public_content(event.etype.as_str(), event.content.as_str()),
+            event_type: event.etype,
+            created_at: event.created_at.into(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GetNostrEventsRequest {
+    pub offset: i64,
+    pub limit: i64,
+}
+
+//a signed event as it was sent to relays, with the object it was published for
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PublicNostrEvent {
+    pub event_id: String,
+    pub kind: i32,
+    pub object_type: Option<String>,
+    pub object_id: Option<String>,
+    pub event: serde_json::Value,
+}
+
+impl TryFrom<nostr_events::Model> for PublicNostrEvent {
+    type Error = serde_json::Error;
+
+    fn try_from(event: nostr_events::Model) -> Result<Self, Self::Error> {
+        Ok(Self {
+            event: serde_json::from_str(event.raw.as_str())?,
+            event_id: event.event_id,
+            kind: event.kind,
+            object_type: event.object_type,
+            object_id: event.object_id,
+        })
+    }
+}
@@ -0,0 +1,18 @@
+use super::profile_handler::*;
+use crate::app::SharedState;
+use crate::server::middlewares;
+use axum::{middleware, routing::get, Router};
+
+pub fn profile_router(state: SharedState) -> Router<SharedState> {
+    Router::new()
+        .route("/privacy", get(get_privacy).post(update_privacy))
+        .layer(middleware::from_fn_with_state(
+            state,
+            middlewares::auth_middleware,
+        ))
+        .route("/lamport_id/:key", get(get_profile_by_lamport_id))
+        .route("/lamport_id/:key/nostr", get(get_nostr_events_by_lamport_id))
+        .route("/username/:key", get(get_profile_by_username))
+        .route("/address/:key", get(get_profile_by_address))
+        .route("/nostr/:key", get(get_profile_by_nostr_pubkey))
+}
@@ -3,30 +3,28 @@ use crate::{
     app::SharedState, 
     common::error::{AppResult,AppError},
     server::{
-        middlewares::AuthToken,
-        events::events_message::Event
+        middlewares::AuthClaims,
+        events::events_message::Event,
+        notification::Notification,
     },
     common::consts,
-    helpers::eip191::verify_signature,
+    helpers::eip712::verify_signature_by_type,
+    nostr,
 };
 use axum::{debug_handler, extract::{self,State, Query,Path}, Json};
 
 #[debug_handler]
 pub async fn create_proposal(
     State(state): State<SharedState>,
-    AuthToken(user): AuthToken,
-    extract::Json(CreateProposalRequest{data: payload, sig}): extract::Json<CreateProposalRequest>,
+    AuthClaims(claim): AuthClaims,
+    extract::Json(CreateProposalRequest{data: payload, sig, sig_type}): extract::Json<CreateProposalRequest>,
 ) -> AppResult<Json<serde_json::Value>> {
-    let client = state.jwt_handler.clone();
-    let claim = client.decode_token(user).unwrap();  //TODO address in jwt
-
-
     if cfg!(not(debug_assertions)) {
         //get user address by lamport id
         let user = state.store.get_user_by_uid(claim.sub.as_str()).await?;
         tracing::info!("{:?}", user.address);
 
-        let verified= verify_signature(&payload, &sig, &user.address)?;
+        let verified= verify_signature_by_type(sig_type, &payload, &sig, &user.address, &state.config.eip712, state.sig_checker.as_deref()).await?;
         if !verified {
             tracing::info!("{:?}", verified);
             return Err(AppError::InvalidSignature);
@@ -70,12 +68,42 @@ pub async fn create_proposal(
     if energy < (consts::ENERGY_PROPOSAL_VALUE as i64) {
         return Err(AppError::InputValidateError("energy not enough".into()));
     }
+    //check humanity score
+    let min_score = state.config.scoring.min_proposal_score;
+    if min_score > 0 {
+        state.store.require_humanity_score(claim.sub.as_str(), min_score).await?;
+    }
     //check payload group_id, group_id must be in database
     state.store.get_group_by_groupid(group_id.as_str()).await?;
 
     let new_proposal = state.store.create_proposal(title, description, options, claim.sub.clone(), group_id, start_time, end_time).await?;
     tracing::info!("proposal created: {:?}", new_proposal);
 
+    state.queue.add_queue_req_ex(consts::NOTIFICATION_TOPIC, Notification::NewProposal {
+        group_id: new_proposal.group_id.clone(),
+        proposal_id: new_proposal.proposal_id.clone(),
+        title: new_proposal.title.clone(),
+        created_by: new_proposal.created_by.clone(),
+    }).await?;
+
+    let nostr_event_id = state
+        .publish_nostr(
+            nostr::LamportBinding::new_kind2327(
+                state.nclient.get_pub_key(),
+                nostr::schema::ProposalEvent {
+                    lamport_id: claim.sub.clone(),
+                    proposal_id: new_proposal.proposal_id.clone(),
+                    group_id: new_proposal.group_id.clone(),
+                    title: new_proposal.title.clone(),
+                    options: new_proposal.options.clone(),
+                    start_time: new_proposal.start_time.to_rfc3339(),
+                    end_time: new_proposal.end_time.to_rfc3339(),
+                },
+            ),
+            Some((consts::NOSTR_OBJECT_PROPOSAL, new_proposal.proposal_id.as_str())),
+        )
+        .await?;
+
     let mut proposal_info = ProposalInfo::from(new_proposal);
     proposal_info.ai_comments = "AI: This proposal has great potential and is in line with community goals.".to_string();
 
@@ -106,7 +134,8 @@ pub async fn create_proposal(
     }
 
     Ok(Json(serde_json::json!({
-        "result": proposal_info
+        "result": proposal_info,
+        "nostr_event_id": nostr_event_id
     })))
 }
 
@@ -300,6 +329,10 @@ pub async fn get_proposal_detail(
     let votes_for = state.store.count_votes_by_proposal_id_and_choice(proposal.proposal_id.as_str(), "For").await?;
     let votes_against = state.store.count_votes_by_proposal_id_and_choice(proposal.proposal_id.as_str(), "Against").await?;
     let votes_abstain = state.store.count_votes_by_proposal_id_and_choice(proposal.proposal_id.as_str(), "Abstain").await?;
+    let nostr_event_ids = state
+        .store
+        .get_nostr_event_ids_by_object(consts::NOSTR_OBJECT_PROPOSAL, proposal.proposal_id.as_str())
+        .await?;
 
     Ok(Json(serde_json::json!({
         "result": {
@@ -309,7 +342,8 @@ pub async fn get_proposal_detail(
                 "for": votes_for,
                 "against": votes_against,
                 "abstain": votes_abstain,
-            }
+            },
+            "nostr_event_ids": nostr_event_ids
         }
     })))
 }
@@ -1,11 +1,18 @@
 use serde::{Deserialize, Serialize};
-use crate::{database::entities::proposals,server::proposal::proposal_service::get_proposal_status};
+use crate::{
+    common::error::AppResult,
+    database::entities::proposals,
+    helpers::eip712::{self, SignatureType, TypedMessage},
+    server::proposal::proposal_service::get_proposal_status,
+};
 
 
 #[derive(Deserialize, Serialize, Debug)]
 pub struct CreateProposalRequest {
     pub data: ProposalData,
     pub sig: String,
+    #[serde(default)]
+    pub sig_type: SignatureType,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -21,6 +28,20 @@ pub struct ProposalData {
     //pub nonce: String,
 }
 
+impl TypedMessage for ProposalData {
+    type Typed = eip712::CreateProposal;
+
+    fn to_typed(&self) -> AppResult<Self::Typed> {
+        Ok(eip712::CreateProposal {
+            title: self.title.clone(),
+            description: self.description.clone(),
+            options: self.options.clone(),
+            group_id: self.group_id.clone(),
+            end_time: self.end_time.clone(),
+        })
+    }
+}
+
 // ProposalInfo struct
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ProposalInfo {
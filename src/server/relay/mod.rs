@@ -0,0 +1,4 @@
+mod relay_handler;
+mod relay_router;
+
+pub use relay_router::relay_router;
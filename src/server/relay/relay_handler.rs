@@ -0,0 +1,49 @@
+use crate::app::SharedState;
+use axum::{
+    debug_handler,
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    response::Response,
+};
+use nostr_sdk::JsonUtil;
+use tokio::sync::broadcast::error::RecvError;
+
+// nostr clients connect here, see nostr::relay
+#[debug_handler]
+pub async fn relay_ws(State(state): State<SharedState>, ws: WebSocketUpgrade) -> Response {
+    //longer frames drop the connection before they are buffered
+    ws.max_message_size(state.relay.max_message_size())
+        .on_upgrade(move |socket| serve(state, socket))
+}
+
+//one connection, until the client leaves or a send fails
+async fn serve(state: SharedState, mut socket: WebSocket) {
+    let mut session = state.relay.session();
+
+    loop {
+        let replies = tokio::select! {
+            msg = socket.recv() => match msg {
+                Some(Ok(Message::Text(text))) => state.relay.handle(&state.store, &mut session, text.as_str()).await,
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                //pings are answered by axum
+                Some(Ok(_)) => continue,
+            },
+            event = session.live.recv() => match event {
+                Ok(event) => session.matching(&event),
+                Err(RecvError::Lagged(n)) => {
+                    tracing::warn!("relay connection lagged, {} events dropped", n);
+                    continue;
+                }
+                Err(RecvError::Closed) => return,
+            },
+        };
+
+        for reply in replies {
+            if socket.send(Message::Text(reply.as_json())).await.is_err() {
+                return;
+            }
+        }
+    }
+}
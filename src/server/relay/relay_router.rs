@@ -0,0 +1,7 @@
+use super::relay_handler::relay_ws;
+use crate::app::SharedState;
+use axum::{routing::get, Router};
+
+pub fn relay_router() -> Router<SharedState> {
+    Router::new().route("/", get(relay_ws))
+}
@@ -1,4 +1,4 @@
-use super::{auth::auth_router, health::health_router, user::user_router, webset::index_router, group::group_router, proposal::proposal_router, vote::vote_router, users::users_router, events::events_router};
+use super::{admin::admin_router, attestation::attestation_router, auth::auth_router, binding::binding_router, health::health_router, user::user_router, webset::index_router, group::group_router, proposal::proposal_router, vote::vote_router, users::users_router, events::events_router, lamport_id::lamport_id_router, notification::notification_router, profile::profile_router, relay::relay_router, well_known::well_known_router};
 use crate::{app::SharedState, server::middlewares};
 use axum::{error_handling::HandleErrorLayer, http::Method, Router};
 use std::time::Duration;
@@ -18,17 +18,36 @@ pub fn app_router(state: SharedState) -> Router {
     let proposal_router = proposal_router(state.clone());
     let vote_router = vote_router(state.clone());
     let events_router = events_router(state.clone());
+    let lamport_id_router = lamport_id_router(state.clone());
+    let admin_router = admin_router(state.clone());
+    let attestation_router = attestation_router(state.clone());
+    let binding_router = binding_router(state.clone());
+    let notification_router = notification_router(state.clone());
+    let profile_router = profile_router(state.clone());
+    let well_known_router = well_known_router();
 
-    Router::new()
+    let mut router = Router::new();
+    if state.config.nostr.relay.enabled {
+        router = router.nest("/relay", relay_router());
+    }
+
+    router
         .nest("/", index_router)
+        .nest("/.well-known", well_known_router)
         .nest("/api/v1/health", health_router)
         .nest("/api/v1/auth", auth_router)
         .nest("/api/v1/user", user_router)
+        .nest("/api/v1/user/bindings", binding_router)
+        .nest("/api/v1/user/notifications", notification_router)
         .nest("/api/v1/users", users_router)
+        .nest("/api/v1/profiles", profile_router)
         .nest("/api/v1/group", group_router)
         .nest("/api/v1/proposal", proposal_router)
         .nest("/api/v1/vote", vote_router)
         .nest("/api/v1/events", events_router)
+        .nest("/api/v1/lamport_id", lamport_id_router)
+        .nest("/api/v1/admin", admin_router)
+        .nest("/api/v1/attestations", attestation_router)
         .layer(
             CorsLayer::new()
                 .allow_origin(Any)
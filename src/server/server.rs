@@ -1,5 +1,6 @@
 use super::router::app_router;
 use crate::{app::SharedState, common::error::AppResult};
+use std::net::SocketAddr;
 
 pub async fn http_server_start(state: SharedState) -> AppResult<()> {
     let router = app_router(state.clone());
@@ -9,7 +10,8 @@ pub async fn http_server_start(state: SharedState) -> AppResult<()> {
     let listener = tokio::net::TcpListener::bind(&addr).await?;
     tracing::info!("server is running on {}", addr);
 
-    axum::serve(listener, router).await?;
+    //the peer address is the client address unless it is a trusted proxy, see ClientInfo
+    axum::serve(listener, router.into_make_service_with_connect_info::<SocketAddr>()).await?;
 
     Ok(())
 }
@@ -1,22 +1,22 @@
 use super::user_message::*;
-use crate::{app::SharedState, common::error::{AppResult, AppError}, server::middlewares::AuthToken};
-use axum::{debug_handler, extract::State, Json};
-use crate::server::auth::{auth_message::*};
-use reqwest::Client;
+use crate::{app::SharedState, common::error::{AppResult, AppError}, server::middlewares::{AuthClaims, AuthUser}};
+use axum::{debug_handler, extract::State, http::header, response::IntoResponse, Json};
 use crate::common::consts;
-use serde::{Deserialize, Serialize};
-use crate::database::entities::twitter_binding;
 use crate::nostr;
-
-#[debug_handler]
+use crate::helpers::{eip1271::verify_hash_signature, eip191::hash_message, nostr_challenge::verify_challenge_event};
+use crate::server::{auth::auth_service::{EmailVerification, RedisClient}, events::events_message::Event};
+use crate::database::entities::users;
+use super::user_service::is_valid_username;
+use alloy_primitives::B256;
+use nostr_sdk::{EventId, Kind};
+use axum::extract::Path;
+use rand::{distributions::Alphanumeric, Rng};
+use validator::ValidateEmail;
+
+#[debug_handler(state = SharedState)]
 pub async fn get_user_info(
-    State(state): State<SharedState>,
-    AuthToken(user): AuthToken,
+    AuthUser { user, .. }: AuthUser,
 ) -> AppResult<Json<serde_json::Value>> {
-    let client = state.jwt_handler.clone();
-    let claim = client.decode_token(user).unwrap();
-
-    let user = state.store.get_user_by_uid(claim.sub.as_ref()).await?;
     let user_rep = UserResponse::from(user);
 
     Ok(Json(serde_json::json!({
@@ -27,7 +27,7 @@ pub async fn get_user_info(
 #[debug_handler]
 pub async fn get_user_count(
     State(state): State<SharedState>,
-    AuthToken(_user): AuthToken,
+    AuthClaims(_claim): AuthClaims,
 ) -> AppResult<Json<serde_json::Value>> {
     let count = state.store.count_total_users().await?;
 
@@ -39,11 +39,8 @@ pub async fn get_user_count(
 #[debug_handler]
 pub async fn get_user_stats(
     State(state): State<SharedState>,
-    AuthToken(user): AuthToken,
+    AuthClaims(claim): AuthClaims,
 ) -> AppResult<Json<serde_json::Value>> {
-    let client = state.jwt_handler.clone();
-    let claim = client.decode_token(user).unwrap();
-
     let invite_count = state
         .store
         .count_invited_users_by_uid(claim.sub.as_ref())
@@ -71,191 +68,554 @@ pub async fn get_user_stats(
     })))
 }
 
-// post binding_account
+// humanity score of the caller and what it is made of
 #[debug_handler]
-pub async fn binding_account(
+pub async fn get_user_score(
     State(state): State<SharedState>,
-    AuthToken(user): AuthToken,
-    Json(params): Json<OAuthParams>,
+    AuthClaims(claim): AuthClaims,
 ) -> AppResult<Json<serde_json::Value>> {
-    let client = state.jwt_handler.clone();
-    let claim = client.decode_token(user).unwrap();
+    let score = state.store.get_humanity_score(claim.sub.as_str()).await?;
 
-    if let Ok(t) = state.store.get_twitter_binding_by_user_id(claim.sub.as_str()).await {
-        return Ok(Json(serde_json::json!({
-            "result": {
-                "twitter_info": BindingTwitterResponse::from(t)
-            }
-        })));
+    Ok(Json(serde_json::json!({
+        "result": score
+    })))
+}
+
+// link another wallet address to the lamport id
+#[debug_handler]
+pub async fn link_address(
+    State(state): State<SharedState>,
+    AuthClaims(claim): AuthClaims,
+    Json(LinkAddressRequest { data, sig }): Json<LinkAddressRequest>,
+) -> AppResult<Json<serde_json::Value>> {
+    if data.lamport_id != claim.sub {
+        return Err(AppError::InputValidateError("lamport id mismatch".into()));
     }
 
-    tracing::info!("[auth_token] get params: {:?}", params);
+    if cfg!(not(debug_assertions)) {
+        let verified = verify_hash_signature(state.sig_checker.as_deref(), hash_message(&data)?, &sig, &data.address).await?;
+        if !verified {
+            return Err(AppError::InvalidSignature);
+        }
+        tracing::info!("signature verified success");
 
-    params.validate_items()?;
+        RedisClient::from(state.redis.clone())
+            .consume_nonce(data.address.as_str(), data.nonce.as_str())
+            .await?;
+    }
 
-    let client = Client::new();
+    if state.store.count_linked_addresses_by_uid(claim.sub.as_str()).await? >= consts::LINKED_ADDRESS_MAX {
+        return Err(AppError::InputValidateError("too many linked addresses".into()));
+    }
 
-    let token_params = [
-        ("code", params.clone().code.unwrap()),
-        ("grant_type", "authorization_code".into()),
-        ("client_id", state.config.auth.client_id.clone()),
-        ("redirect_uri", params.clone().redirect_uri.unwrap()),
-        ("code_verifier", "challenge".into()),
-    ];
+    let linked = state
+        .store
+        .link_address(claim.sub.as_str(), data.address.as_str(), sig.as_str())
+        .await?;
 
-    tracing::info!("[auth_token] exchange code params: {:?}", token_params);
+    let queue = state.queue.clone();
 
+    let e = Event {
+        event_id: uuid::Uuid::new_v4().to_string(),
+        lamport_id: claim.sub.clone(),
+        event_type: consts::EVENT_TYPE_BINDING.to_string(),
+        content: "Linked an address".to_string(),
+        created_at: chrono::Utc::now(),
+    };
+    queue.add_queue_req_ex(consts::EVENT_TOPIC, e).await?;
+
+    let nostr_event_id = state
+        .publish_nostr(
+            nostr::LamportBinding::new_kind2322(
+                state.nclient.get_pub_key(),
+                claim.sub.as_str(),
+                linked.address.as_str(),
+                linked.sig.as_str(),
+            ),
+            Some((consts::NOSTR_OBJECT_USER, claim.sub.as_str())),
+        )
+        .await?;
 
-    let token_response= client
-        .post("https://api.x.com/2/oauth2/token")
-        .header("Content-Type", "application/x-www-form-urlencoded")
-        .form(&token_params)
-        .send()
-        .await
-        .map_err(|_e| AppError::RequestError("failed to exchange code".to_string()))?;
+    Ok(Json(serde_json::json!({
+        "result": LinkedAddressInfo::from(linked),
+        "nostr_event_id": nostr_event_id
+    })))
+}
 
-    if !token_response.status().is_success() {
-        let error_message = token_response
-            .text()
-            .await
-            .unwrap_or_else(|_| "Failed to read error response".to_string());
+// unlink a wallet address, the primary address stays
+#[debug_handler]
+pub async fn unlink_address(
+    State(state): State<SharedState>,
+    AuthClaims(claim): AuthClaims,
+    Path(address): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    let unlinked = state.store.unlink_address(claim.sub.as_str(), address.as_str()).await?;
 
-        return Err(AppError::RequestError(format!(
-                    "Failed to get token. Status: Error: {}", 
-                    error_message
-        )));
-    }
+    Ok(Json(serde_json::json!({
+        "result": {
+            "status": "success",
+            "address": unlinked.address
+        }
+    })))
+}
 
-    let token: ExchangeTokenRespose = token_response
-        .json()
-        .await
-        .map_err(|e| AppError::CustomError(e.to_string() + "Failed to parse user info"))?;
+// get_linked_addresses
+#[debug_handler]
+pub async fn get_linked_addresses(
+    State(state): State<SharedState>,
+    AuthUser { user, .. }: AuthUser,
+) -> AppResult<Json<serde_json::Value>> {
+    let linked = state.store.get_linked_addresses_by_uid(user.lamport_id.as_str()).await?;
 
-    tracing::info!("[auth_token] exchange code get: {:?}", token);
+    let mut addresses = vec![LinkedAddressInfo {
+        address: user.address,
+        primary: true,
+        created_at: user.created_at.map(|t| t.into()),
+    }];
+    addresses.extend(linked.into_iter().map(LinkedAddressInfo::from));
 
-    let access_token = token.access_token.clone();
+    Ok(Json(serde_json::json!({
+        "result": {
+            "count": addresses.len(),
+            "addresses": addresses
+        }
+    })))
+}
 
-    tracing::info!("[auth_token] Access Token: {:?}", access_token);
+//profile changes are signed by the primary or a linked address of the caller
+async fn verify_profile_signature(
+    state: &SharedState,
+    user: &users::Model,
+    address: &str,
+    nonce: &str,
+    hash: B256,
+    sig: &str,
+) -> AppResult<()> {
+    let owned = user.address == address
+        || matches!(state.store.get_linked_address(address).await?, Some(linked) if linked.lamport_id == user.lamport_id);
+    if !owned {
+        return Err(AppError::Forbidden(format!("{} is not an address of {}", address, user.lamport_id)));
+    }
 
-    let client = Client::new();
+    if cfg!(not(debug_assertions)) {
+        let verified = verify_hash_signature(state.sig_checker.as_deref(), hash, sig, address).await?;
+        if !verified {
+            return Err(AppError::InvalidSignature);
+        }
+        tracing::info!("signature verified success");
 
-    let user_info_response = client
-        .get("https://api.x.com/2/users/me")
-        .bearer_auth(&access_token)
-        .query(&[("user.fields", "profile_image_url")])
-        .send()
-        .await
-        .map_err(|_e| AppError::RequestError("failed to get user info".to_string()))?;
+        RedisClient::from(state.redis.clone())
+            .consume_nonce(address, nonce)
+            .await?;
+    }
 
-    tracing::info!("[auth_token] get user info response: {:?}", user_info_response);
+    Ok(())
+}
+
+async fn add_profile_event(state: &SharedState, lamport_id: &str, content: String) -> AppResult<()> {
+    let e = Event {
+        event_id: uuid::Uuid::new_v4().to_string(),
+        lamport_id: lamport_id.to_string(),
+        event_type: consts::EVENT_TYPE_PROFILE.to_string(),
+        content,
+        created_at: chrono::Utc::now(),
+    };
+    state.queue.add_queue_req_ex(consts::EVENT_TOPIC, e).await?;
 
-    if !user_info_response.status().is_success() {
-        return Err(AppError::RequestError(
-            "non user info in response".to_string(),
-        ));
+    Ok(())
+}
+
+// update display name, avatar, bio and email
+#[debug_handler]
+pub async fn update_profile(
+    State(state): State<SharedState>,
+    AuthUser { user, .. }: AuthUser,
+    Json(UpdateProfileRequest { data, sig }): Json<UpdateProfileRequest>,
+) -> AppResult<Json<serde_json::Value>> {
+    if data.lamport_id != user.lamport_id {
+        return Err(AppError::InputValidateError("lamport id mismatch".into()));
     }
 
-    let user_info: OauthUserInfo = user_info_response
-        .json()
-        .await
-        .map_err(|e| AppError::CustomError(e.to_string() + "Failed to parse user info"))?;
-
-    tracing::info!("[auth_token] get user info: {:?}", user_info);
-
-
-    let created_binding= match state.store.binding_twitter(
-        claim.sub.clone(),
-        user_info.data.id.clone(),
-        user_info.data.name.clone(),
-        user_info.data.username.clone(),
-        user_info.data.profile_image_url.clone(),
-        token.access_token.clone(),
-        token.refresh_token.clone(),
-        token.token_type.clone(),
-        token.scope.clone(),
-    ).await {
-        Ok(u) => u,
-        Err(AppError::UserExisted(_)) => {
-            tracing::info!("user has already existed, log in");
-            state
-                .store
-                .get_twitter_binding_by_user_id(claim.sub.as_str())
-                .await?
+    let config = &state.config.profile;
+    if let Some(name) = &data.name {
+        if name.trim().is_empty() || name.chars().count() > config.name_max_len {
+            return Err(AppError::InputValidateError(format!("name must be 1 to {} characters", config.name_max_len)));
         }
-        Err(e) => return Err(e),
-    };
+    }
+    if let Some(bio) = &data.bio {
+        if bio.chars().count() > config.bio_max_len {
+            return Err(AppError::InputValidateError(format!("bio must be at most {} characters", config.bio_max_len)));
+        }
+    }
+    if let Some(image) = &data.image {
+        if !image.is_empty() && url::Url::parse(image).is_err() {
+            return Err(AppError::InputValidateError("invalid avatar url".into()));
+        }
+    }
+    if let Some(email) = &data.email {
+        if !email.is_empty() && !email.validate_email() {
+            return Err(AppError::InputValidateError("invalid email".into()));
+        }
+    }
 
-    //award point
-    state
+    verify_profile_signature(&state, &user, data.address.as_str(), data.nonce.as_str(), hash_message(&data)?, sig.as_str()).await?;
+
+    let mut changed = vec![];
+    if data.name.as_ref().is_some_and(|v| *v != user.name) {
+        changed.push("name");
+    }
+    if data.image.as_ref().is_some_and(|v| *v != user.image) {
+        changed.push("avatar");
+    }
+    if data.bio.as_ref().is_some_and(|v| *v != user.bio) {
+        changed.push("bio");
+    }
+    if data.email.as_ref().is_some_and(|v| *v != user.email) {
+        changed.push("email");
+    }
+
+    let lamport_id = user.lamport_id.clone();
+    let updated = state
         .store
-        .award_points(claim.sub.clone(), consts::POINTS_BINDING, consts::POINTS_BINDING_VALUE, "twitter")
+        .update_profile(user, data.name, data.image, data.bio, data.email)
         .await?;
 
-    //consume energy 
-    state
+    if !changed.is_empty() {
+        add_profile_event(&state, lamport_id.as_str(), format!("Updated profile {}", changed.join(", "))).await?;
+    }
+
+    Ok(Json(serde_json::json!({
+        "result": UserResponse::from(updated)
+    })))
+}
+
+// change the username, the old one keeps redirecting to this lamport id
+#[debug_handler]
+pub async fn change_username(
+    State(state): State<SharedState>,
+    AuthUser { user, .. }: AuthUser,
+    Json(ChangeUsernameRequest { data, sig }): Json<ChangeUsernameRequest>,
+) -> AppResult<Json<serde_json::Value>> {
+    if data.lamport_id != user.lamport_id {
+        return Err(AppError::InputValidateError("lamport id mismatch".into()));
+    }
+
+    if !is_valid_username(data.user_name.as_str()) {
+        return Err(AppError::InputValidateError("username must be 3 to 30 letters, digits or underscores".into()));
+    }
+
+    if data.user_name == user.user_name {
+        return Err(AppError::InputValidateError("username is unchanged".into()));
+    }
+
+    if let Some(last) = state.store.get_last_username_change(user.lamport_id.as_str()).await? {
+        let until = last.changed_at.with_timezone(&chrono::Utc)
+            + chrono::Duration::hours(state.config.profile.username_cooldown_hours);
+        if until > chrono::Utc::now() {
+            return Err(AppError::Forbidden(format!("username can be changed again after {}", until)));
+        }
+    }
+
+    verify_profile_signature(&state, &user, data.address.as_str(), data.nonce.as_str(), hash_message(&data)?, sig.as_str()).await?;
+
+    let (updated, history) = state
         .store
-        .create_energy(claim.sub.clone(), consts::ENERGY_BINDING, consts::ENERGY_BINDING_VALUE)
+        .change_username(user, data.user_name.as_str(), sig.as_str())
         .await?;
 
+    add_profile_event(
+        &state,
+        updated.lamport_id.as_str(),
+        format!("Changed username from {} to {}", history.old_user_name, history.new_user_name),
+    )
+    .await?;
 
-    tracing::info!("[auth_token] database  user info: {:?}", created_binding);
+    Ok(Json(serde_json::json!({
+        "result": {
+            "user_info": UserResponse::from(updated),
+            "history": UsernameHistoryInfo::from(history)
+        }
+    })))
+}
 
-    //pub fn new_kind2321(pubkey: PublicKey, lamport_id: &str, twitter: &str) -> Self {
-    state.queue.add_queue_req_ex(consts::NOSTR_TOPIC, nostr::LamportBinding::new_kind2321(
-        state.nclient.get_pub_key(),
-        claim.sub.as_str(),
-        created_binding.user_name.as_str(),
-    )).await?;
+// old usernames of the caller, newest first
+#[debug_handler]
+pub async fn get_username_history(
+    State(state): State<SharedState>,
+    AuthClaims(claim): AuthClaims,
+) -> AppResult<Json<serde_json::Value>> {
+    let history = state.store.get_username_history_by_user_id(claim.sub.as_str()).await?;
+
+    Ok(Json(serde_json::json!({
+        "result": history.into_iter().map(UsernameHistoryInfo::from).collect::<Vec<_>>()
+    })))
+}
+
+// mail a verification link to the email of the caller
+#[debug_handler]
+pub async fn send_email_verification(
+    State(state): State<SharedState>,
+    AuthUser { user, .. }: AuthUser,
+) -> AppResult<Json<serde_json::Value>> {
+    if user.email.is_empty() {
+        return Err(AppError::InputValidateError("no email to verify".into()));
+    }
+    if user.email_verified_at.is_some() {
+        return Err(AppError::ConflictError("email has already been verified".into()));
+    }
+
+    let redis = RedisClient::from(state.redis.clone());
+    redis.claim_email_cooldown(user.lamport_id.as_str(), user.email.as_str()).await?;
+
+    let token: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(48)
+        .map(char::from)
+        .collect();
+
+    let config = &state.config.mail;
+    redis
+        .cache_email_verification(
+            token.as_str(),
+            &EmailVerification {
+                lamport_id: user.lamport_id.clone(),
+                email: user.email.clone(),
+            },
+            config.verify_expiration,
+        )
+        .await?;
+
+    let mut link = url::Url::parse(&config.verify_link_url)?;
+    link.query_pairs_mut().append_pair("token", &token);
+
+    state
+        .mailer
+        .send(
+            user.email.as_str(),
+            "Verify your email for Lamport ID",
+            &format!(
+                "Open this link to verify {} for Lamport ID {}:\n\n{}\n\nThe link expires in {} hours.",
+                user.email,
+                user.lamport_id,
+                link,
+                config.verify_expiration / 3600
+            ),
+        )
+        .await?;
 
     Ok(Json(serde_json::json!({
         "result": {
-            "twitter_info": BindingTwitterResponse::from(created_binding)
+            "sent_to": user.email,
+            "expires_in": config.verify_expiration
         }
     })))
 }
 
-//struct for get binding twitter and impl from twitter_binding::Model
-#[derive(Debug, Serialize, Deserialize)]
-pub struct BindingTwitterResponse {
-    pub x_id: String,
-    pub name: String,
-    pub user_name: String,
-    pub image_url: String,
+// everything stored about the caller as a json archive
+#[debug_handler]
+pub async fn export_account(
+    State(state): State<SharedState>,
+    AuthUser { user, .. }: AuthUser,
+) -> AppResult<impl IntoResponse> {
+    let filename = format!("attachment; filename=\"lamport-id-{}.json\"", user.lamport_id);
+    let archive = state.store.export_account(user).await?;
+
+    Ok(([(header::CONTENT_DISPOSITION, filename)], Json(archive)))
 }
 
-impl From<twitter_binding::Model> for BindingTwitterResponse {
-    fn from(binding: twitter_binding::Model) -> Self {
-        Self {
-            x_id: binding.x_id,
-            name: binding.name,
-            user_name: binding.user_name,
-            image_url: binding.image_url,
+// delete the caller's account, its votes and proposals stay counted under a tombstone id
+#[debug_handler]
+pub async fn delete_account(
+    State(state): State<SharedState>,
+    AuthUser { user, .. }: AuthUser,
+    Json(DeleteAccountRequest { data, sig }): Json<DeleteAccountRequest>,
+) -> AppResult<Json<serde_json::Value>> {
+    if data.lamport_id != user.lamport_id {
+        return Err(AppError::InputValidateError("lamport id mismatch".into()));
+    }
+
+    verify_profile_signature(&state, &user, data.address.as_str(), data.nonce.as_str(), hash_message(&data)?, sig.as_str()).await?;
+
+    let lamport_id = user.lamport_id.clone();
+
+    //the retraction is signed and stored before the events it names are deleted, its e tags keep their ids
+    //and its deliveries are retried like any other event, so a relay that is down still gets it later
+    let retracted = lamport_nostr_events(&state, lamport_id.as_str()).await?;
+    let mut nostr_event_id = None;
+    if !retracted.is_empty() {
+        nostr_event_id = Some(
+            state
+                .publish_nostr(
+                    nostr::LamportBinding::new_kind5(state.nclient.get_pub_key(), &retracted),
+                    Some((consts::NOSTR_OBJECT_USER, lamport_id.as_str())),
+                )
+                .await?,
+        );
+    }
+
+    if let Ok(binding) = state.store.get_social_binding(lamport_id.as_str(), consts::PROVIDER_TWITTER).await {
+        if let Err(e) = state.twitter.revoke_binding(&binding).await {
+            tracing::error!("revoke twitter token of {} err: {:?}", lamport_id, e);
         }
     }
+
+    let tombstone = format!("{}{}", consts::TOMBSTONE_PREFIX, uuid::Uuid::new_v4().simple());
+    let deleted = state.store.delete_account(user, tombstone.as_str()).await?;
+
+    let sessions = RedisClient::from(state.redis.clone())
+        .revoke_all_sessions(lamport_id.as_str())
+        .await?;
+
+    tracing::info!("account {} deleted, {} sessions revoked", lamport_id, sessions);
+
+    Ok(Json(serde_json::json!({
+        "result": {
+            "lamport_id": lamport_id,
+            "deleted_at": deleted.deleted_at.map(|t| t.with_timezone(&chrono::Utc)),
+            "sessions_revoked": sessions,
+            "nostr_events_deleted": retracted.len()
+        },
+        "nostr_event_id": nostr_event_id
+    })))
 }
 
+//every event we signed for lamport_id, the stored ones and those only relays still have, best effort
+async fn lamport_nostr_events(state: &SharedState, lamport_id: &str) -> AppResult<Vec<(EventId, Kind)>> {
+    let mut events = Vec::new();
+    let batch = consts::NOSTR_RELAY_BATCH as i64;
+    let mut offset = 0;
+    loop {
+        let stored = state.store.get_nostr_events_by_lamport_id(lamport_id, &[], offset, batch).await?;
+        for event in &stored {
+            let id = EventId::from_hex(event.event_id.as_str())
+                .map_err(|_| AppError::InvalidNostrEvent(format!("stored event id {} is invalid", event.event_id)))?;
+            events.push((id, Kind::from(event.kind as u16)));
+        }
+        if (stored.len() as i64) < batch {
+            break;
+        }
+        offset += batch;
+    }
 
-// get_user_bindings
+    match state.nclient.fetch_lamport_events(lamport_id).await {
+        Ok(published) => {
+            for event in published {
+                if !events.iter().any(|(id, _)| *id == event.id) {
+                    events.push((event.id, event.kind));
+                }
+            }
+        }
+        Err(e) => tracing::error!("fetch nostr events of {} err: {:?}", lamport_id, e),
+    }
+
+    Ok(events)
+}
+
+// challenge to sign with the nostr key being linked
 #[debug_handler]
-pub async fn get_user_bindings(
+pub async fn get_nostr_challenge(
     State(state): State<SharedState>,
-    AuthToken(user): AuthToken,
+    AuthClaims(claim): AuthClaims,
 ) -> AppResult<Json<serde_json::Value>> {
-    let client = state.jwt_handler.clone();
-    let claim = client.decode_token(user).unwrap();
+    let challenge = RedisClient::from(state.redis.clone())
+        .cache_nostr_challenge(claim.sub.as_str())
+        .await?;
 
-    match state.store.get_twitter_binding_by_user_id(claim.sub.as_str()).await {
-        Ok(binding) => Ok(Json(serde_json::json!({
-                "result": {
-                    "twitter_info": BindingTwitterResponse::from(binding)
-                }
-            }))),
-        Err(AppError::CustomError(_)) => Ok(Json(serde_json::json!({
-                "result": {
-                    "twitter_info": {}
-                }
-            }))),
-        Err(e) => Err(e),
+    Ok(Json(serde_json::json!({
+        "result": {
+            "kind": consts::NOSTR_CHALLENGE_KIND,
+            "tags": [["challenge", challenge], ["LamportID", claim.sub]],
+            "challenge": challenge,
+            "expires_in": consts::NOSTR_CHALLENGE_EXPIRATION
+        }
+    })))
+}
 
+// link the nostr key that signed the challenge
+#[debug_handler]
+pub async fn link_nostr_key(
+    State(state): State<SharedState>,
+    AuthUser { user, .. }: AuthUser,
+    Json(LinkNostrKeyRequest { event }): Json<LinkNostrKeyRequest>,
+) -> AppResult<Json<serde_json::Value>> {
+    let challenge = RedisClient::from(state.redis.clone())
+        .consume_nostr_challenge(user.lamport_id.as_str())
+        .await?;
+    let pubkey = verify_challenge_event(&event, challenge.as_str(), user.lamport_id.as_str(), nostr_sdk::Timestamp::now())?;
+
+    let previous = user.nostr_pubkey.clone();
+    let updated = state.store.set_nostr_pubkey(user, Some(pubkey.to_hex())).await?;
+    let lamport_id = updated.lamport_id.as_str();
+
+    let queue = state.queue.clone();
+    if let Some(previous) = previous.filter(|p| *p != pubkey.to_hex()) {
+        state
+            .publish_nostr(
+                nostr::LamportBinding::new_kind2326(
+                    state.nclient.get_pub_key(),
+                    lamport_id,
+                    consts::PROVIDER_NOSTR,
+                    previous.as_str(),
+                    consts::BINDING_RELEASE_REBIND,
+                ),
+                Some((consts::NOSTR_OBJECT_USER, lamport_id)),
+            )
+            .await?;
     }
+
+    let e = Event {
+        event_id: uuid::Uuid::new_v4().to_string(),
+        lamport_id: lamport_id.to_string(),
+        event_type: consts::EVENT_TYPE_BINDING.to_string(),
+        content: format!("Linked Nostr key {}", pubkey.to_hex()),
+        created_at: chrono::Utc::now(),
+    };
+    queue.add_queue_req_ex(consts::EVENT_TOPIC, e).await?;
+
+    let nostr_event_id = state
+        .publish_nostr(
+            nostr::LamportBinding::new_kind2321(
+                state.nclient.get_pub_key(),
+                lamport_id,
+                consts::PROVIDER_NOSTR,
+                pubkey.to_hex().as_str(),
+            ),
+            Some((consts::NOSTR_OBJECT_USER, lamport_id)),
+        )
+        .await?;
+
+    Ok(Json(serde_json::json!({
+        "result": UserResponse::from(updated),
+        "nostr_event_id": nostr_event_id
+    })))
+}
+
+#[debug_handler]
+pub async fn unlink_nostr_key(
+    State(state): State<SharedState>,
+    AuthUser { user, .. }: AuthUser,
+) -> AppResult<Json<serde_json::Value>> {
+    let previous = user
+        .nostr_pubkey
+        .clone()
+        .ok_or(AppError::InputValidateError("no nostr key is linked".into()))?;
+    let updated = state.store.set_nostr_pubkey(user, None).await?;
+
+    let nostr_event_id = state
+        .publish_nostr(
+            nostr::LamportBinding::new_kind2326(
+                state.nclient.get_pub_key(),
+                updated.lamport_id.as_str(),
+                consts::PROVIDER_NOSTR,
+                previous.as_str(),
+                consts::BINDING_RELEASE_UNBIND,
+            ),
+            Some((consts::NOSTR_OBJECT_USER, updated.lamport_id.as_str())),
+        )
+        .await?;
+
+    Ok(Json(serde_json::json!({
+        "result": UserResponse::from(updated),
+        "nostr_event_id": nostr_event_id
+    })))
 }
@@ -1,5 +1,5 @@
 use super::user_service;
-use crate::database::entities::users;
+use crate::database::entities::{linked_addresses, username_history, users};
 use crate::server::auth::OauthUserInfo;
 use sea_orm::*;
 use serde::{Deserialize, Serialize};
@@ -30,6 +30,7 @@ pub struct User {
     pub verified: bool,
     pub invited_by: Option<String>,
     pub invite_code: String,
+    pub register_ip: Option<String>,
 }
 
 impl From<OauthUserInfo> for User {
@@ -45,6 +46,7 @@ impl From<OauthUserInfo> for User {
             verified: false,
             invited_by: None,
             invite_code: user_service::gen_invite_code(8),
+            register_ip: None,
         }
     }
 }
@@ -54,25 +56,36 @@ impl User {
         self.invited_by = Some(invited.to_string());
         self
     }
+
+    //ip the account registered from, one of the sybil signals
+    pub fn add_register_ip(mut self, ip: &str) -> Self {
+        self.register_ip = Some(ip.to_string());
+        self
+    }
 }
 
-impl Into<users::ActiveModel> for User {
-    fn into(self) -> users::ActiveModel {
+impl From<User> for users::ActiveModel {
+    fn from(user: User) -> Self {
         users::ActiveModel {
             id: NotSet,
-            lamport_id: Set(self.lamport_id),
-            name: Set(self.name),
-            address: Set(self.address),
-            x_id: Set(self.x_id),
-            user_name: Set(self.username),
-            image: Set(self.image),
-            email: Set(self.email),
-            verified: Set(self.verified),
+            lamport_id: Set(user.lamport_id),
+            name: Set(user.name),
+            address: Set(user.address),
+            x_id: Set(user.x_id),
+            user_name: Set(user.username),
+            image: Set(user.image),
+            email: Set(user.email),
+            verified: Set(user.verified),
             verified_by: Set(None),
-            invited_by: Set(self.invited_by),
-            invite_code: Set(self.invite_code),
+            invited_by: Set(user.invited_by),
+            invite_code: Set(user.invite_code),
             created_at: Set(Some(chrono::Utc::now().into())),
             updated_at: Set(Some(chrono::Utc::now().into())),
+            register_ip: Set(user.register_ip),
+            bio: Set(String::new()),
+            email_verified_at: Set(None),
+            deleted_at: Set(None),
+            nostr_pubkey: Set(None),
         }
     }
 }
@@ -87,6 +100,10 @@ pub struct UserResponse {
     pub verified: bool,
     pub email: String,
     pub address: String,
+    pub user_name: String,
+    pub bio: String,
+    pub email_verified: bool,
+    pub nostr_pubkey: Option<String>,
 }
 
 impl From<users::Model> for UserResponse {
@@ -100,6 +117,110 @@ impl From<users::Model> for UserResponse {
             verified: user.verified,
             email: user.email,
             address: user.address,
+            user_name: user.user_name,
+            bio: user.bio,
+            email_verified: user.email_verified_at.is_some(),
+            nostr_pubkey: user.nostr_pubkey,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkAddressRequest {
+    pub data: LinkAddressData,
+    pub sig: String,
+}
+
+//signed by the address being linked, nonce from /api/v1/auth/nonce/:address
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkAddressData {
+    pub lamport_id: String,
+    pub address: String,
+    pub nonce: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct LinkedAddressInfo {
+    pub address: String,
+    pub primary: bool,
+    pub created_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl From<linked_addresses::Model> for LinkedAddressInfo {
+    fn from(linked: linked_addresses::Model) -> Self {
+        Self {
+            address: linked.address,
+            primary: false,
+            created_at: Some(linked.created_at.into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateProfileRequest {
+    pub data: ProfileData,
+    pub sig: String,
+}
+
+//signed by the primary or a linked address, fields left out stay unchanged
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileData {
+    pub lamport_id: String,
+    pub address: String,
+    pub nonce: String,
+    pub name: Option<String>,
+    pub image: Option<String>,
+    pub bio: Option<String>,
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeUsernameRequest {
+    pub data: UsernameData,
+    pub sig: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsernameData {
+    pub lamport_id: String,
+    pub address: String,
+    pub nonce: String,
+    pub user_name: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct UsernameHistoryInfo {
+    pub old_user_name: String,
+    pub new_user_name: String,
+    pub changed_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<username_history::Model> for UsernameHistoryInfo {
+    fn from(history: username_history::Model) -> Self {
+        Self {
+            old_user_name: history.old_user_name,
+            new_user_name: history.new_user_name,
+            changed_at: history.changed_at.into(),
         }
     }
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteAccountRequest {
+    pub data: DeleteAccountData,
+    pub sig: String,
+}
+
+//signed by the primary or a linked address, deletion can not be undone
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteAccountData {
+    pub lamport_id: String,
+    pub address: String,
+    pub nonce: String,
+}
+
+//a kind 22242 event signed by the nostr key, tagged with the challenge and the lamport id
+#[derive(Debug, Clone, Deserialize)]
+pub struct LinkNostrKeyRequest {
+    pub event: nostr_sdk::Event,
+}
@@ -8,7 +8,18 @@ pub fn user_router(state: SharedState) -> Router<SharedState> {
         .route("/info", get(get_user_info))
         .route("/count", get(get_user_count))
         .route("/stats", get(get_user_stats))
-        .route("/bindings", post(binding_account).get(get_user_bindings))
+        .route("/score", get(get_user_score))
+        .route("/addresses", post(link_address).get(get_linked_addresses))
+        .route("/addresses/:address/remove", post(unlink_address))
+        .route("/profile", post(update_profile))
+        .route("/username", post(change_username))
+        .route("/username/history", get(get_username_history))
+        .route("/email/verify", post(send_email_verification))
+        .route("/export", get(export_account))
+        .route("/delete", post(delete_account))
+        .route("/nostr", post(link_nostr_key))
+        .route("/nostr/challenge", get(get_nostr_challenge))
+        .route("/nostr/remove", post(unlink_nostr_key))
         .layer(middleware::from_fn_with_state(
             state,
             middlewares::auth_middleware,
@@ -23,3 +23,8 @@ pub fn gen_invite_code(length: usize) -> String {
         .map(char::from)
         .collect()
 }
+
+//3 to 30 letters, digits or underscores
+pub fn is_valid_username(username: &str) -> bool {
+    (3..=30).contains(&username.len()) && username.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
@@ -2,8 +2,8 @@ use super::users_message::*;
 use crate::{
     app::SharedState, 
     common::{error::{AppResult, AppError}, consts}, 
-    server::{middlewares::AuthToken, user::{UserResponse, User}, auth::auth_service::*, events::events_message::Event},
-    helpers::eip191::verify_signature,
+    server::{middlewares::ClientInfo, user::{UserResponse, User}, auth::{auth_service::*, session_service::start_session}, events::events_message::Event, notification::Notification},
+    helpers::{eip712::verify_signature_by_type, siwe::{verify_siwe_signature, SiweMessage}},
 };
 use axum::{
     debug_handler,
@@ -11,6 +11,8 @@ use axum::{
     Json, 
 };
 use crate::nostr;
+use alloy_primitives::Address;
+use validator::ValidateEmail;
 
 
 //check username
@@ -19,7 +21,8 @@ pub async fn check_username(
     State(state): State<SharedState>,
     Path(username): Path<String>,
 ) -> AppResult<Json<serde_json::Value>> {
-    let existing= state.store.is_user_exists_by_username(&username).await;
+    //old handles stay reserved for the lamport id they redirect to
+    let existing= state.store.is_username_taken(&username, None).await;
     match existing {
         Ok(true) => {
             Err(AppError::ConflictError("Name is already taken".to_string()))
@@ -36,14 +39,37 @@ pub async fn check_username(
 }
 
 
+//resolve a username, old handles redirect to the lamport id that held them
+#[debug_handler]
+pub async fn resolve_username(
+    State(state): State<SharedState>,
+    Path(username): Path<String>,
+) -> AppResult<Json<serde_json::Value>> {
+    let (user, redirected_from) = state.store.resolve_username(&username).await?;
+
+    Ok(Json(serde_json::json!({
+        "result": {
+            "lamport_id": user.lamport_id,
+            "user_name": user.user_name,
+            "redirected_from": redirected_from
+        }
+    })))
+}
+
 // register
 #[debug_handler]
 pub async fn register(
     State(state): State<SharedState>,
+    client: ClientInfo,
     EJson(req): EJson<RegisterRequest>,
 ) -> AppResult<Json<serde_json::Value>> {
+    //the email stays unverified until its owner opens the link from /api/v1/user/email/verify
+    if !req.data.email.is_empty() && !req.data.email.validate_email() {
+        return Err(AppError::InputValidateError("invalid email".into()));
+    }
+
     if cfg!(not(debug_assertions)) {
-        let verified= verify_signature(&req.data, &req.sig, &req.data.address)?;
+        let verified= verify_signature_by_type(req.sig_type, &req.data, &req.sig, &req.data.address, &state.config.eip712, state.sig_checker.as_deref()).await?;
         if !verified {
             return Err(AppError::InvalidSignature);
         }
@@ -86,7 +112,7 @@ pub async fn register(
             .get_user_by_address(user_info.data.address.as_ref())
             .await?
     } else {
-        let user: User = User::from(user_info.clone());
+        let user: User = User::from(user_info.clone()).add_register_ip(client.ip.as_str());
 
         //points
         let user = match req.invited_by {
@@ -100,7 +126,7 @@ pub async fn register(
                 tracing::info!("user has already existed, log in");
                 state
                     .store
-                    .get_user_by_username(user_info.data.user_name.as_ref())
+                    .get_user_by_address(user_info.data.address.as_ref())
                     .await?
             }
             Err(e) => return Err(e),
@@ -123,9 +149,14 @@ pub async fn register(
             //consume energy 
             state
                 .store
-                .create_energy(inviter.lamport_id, consts::ENERGY_INVITE, consts::ENERGY_INVITE_VALUE)
+                .create_energy(inviter.lamport_id.clone(), consts::ENERGY_INVITE, consts::ENERGY_INVITE_VALUE)
                 .await?;
 
+            state.queue.add_queue_req_ex(consts::NOTIFICATION_TOPIC, Notification::Invited {
+                lamport_id: inviter.lamport_id,
+                invitee: created_user.lamport_id.clone(),
+            }).await?;
+
         }
 
         let queue = state.queue.clone();
@@ -139,60 +170,39 @@ pub async fn register(
         };
         queue.add_queue_req_ex(consts::EVENT_TOPIC, e).await?;
 
-        queue.add_queue_req_ex(consts::NOSTR_TOPIC, nostr::LamportBinding::new_kind2322(state.nclient.get_pub_key(),created_user.lamport_id.as_str(), created_user.address.as_str(),"")).await?;
+        state
+            .publish_nostr(
+                nostr::LamportBinding::new_kind2322(state.nclient.get_pub_key(), created_user.lamport_id.as_str(), created_user.address.as_str(), ""),
+                Some((consts::NOSTR_OBJECT_USER, created_user.lamport_id.as_str())),
+            )
+            .await?;
 
         tracing::info!("[auth_token] database  user info: {:?}", created_user);
         created_user
     };
 
-    let secret = state.jwt_handler.clone();
-    let token: String =
-        secret.create_token(&created_user.lamport_id, &created_user.name, &created_user.user_name);
+    let tokens = start_session(&RedisClient::from(state.redis.clone()), &state.jwt_handler, &state.store, &created_user, &client, None).await?;
 
-    tracing::info!("[auth_token] jwt token: {:?}", token);
+    tracing::info!("[auth_token] jwt token: {:?}", tokens.access_token);
 
     Ok(Json(serde_json::json!({
         "result": {
-            "access_token": token,
+            "access_token": tokens.access_token,
+            "refresh_token": tokens.refresh_token,
             "user_info": UserResponse::from(created_user)
         }
     })))
 }
 
-// verify user
-#[debug_handler]
-pub async fn verify_user(
-    State(state): State<SharedState>,
-    AuthToken(user): AuthToken,
-    Path(address): Path<String>,
-) -> AppResult<Json<serde_json::Value>> {
-    let client = state.jwt_handler.clone();
-    let claim = client.decode_token(user).unwrap();
-    
-    //get user by uid
-    let verifier = state.store.get_user_by_uid(claim.sub.as_str()).await?;
-    if !verifier.verified {
-        return Err(AppError::InputValidateError("verifier is not verified".into()));
-    }
-
-    let _user = state.store.update_user(address.as_str(), claim.sub.as_str()).await?;
-
-    Ok(Json(serde_json::json!({
-        "result": {
-            "status": "success",
-            "address": address
-        }
-    })))
-}
-
 //login
 #[debug_handler]
 pub async fn login(
     State(state): State<SharedState>,
+    client: ClientInfo,
     EJson(req): EJson<LoginRequest>,
 ) -> AppResult<Json<serde_json::Value>> {
     if cfg!(not(debug_assertions)) {
-        let verified= verify_signature(&req.data, &req.sig, &req.data.address)?;
+        let verified= verify_signature_by_type(req.sig_type, &req.data, &req.sig, &req.data.address, &state.config.eip712, state.sig_checker.as_deref()).await?;
         if !verified {
             return Err(AppError::InvalidSignature);
         }
@@ -223,15 +233,63 @@ pub async fn login(
     }
 
     let user = state.store.get_user_by_address(&req.data.address).await?;
-    let secret = state.jwt_handler.clone();
-    let token: String =
-        secret.create_token(&user.lamport_id, &user.name, &user.user_name);
+    let tokens = start_session(&RedisClient::from(state.redis.clone()), &state.jwt_handler, &state.store, &user, &client, None).await?;
+
+    tracing::info!("[auth_token] jwt token: {:?}", tokens.access_token);
 
-    tracing::info!("[auth_token] jwt token: {:?}", token);
+    Ok(Json(serde_json::json!({
+        "result": {
+            "access_token": tokens.access_token,
+            "refresh_token": tokens.refresh_token,
+            "user_info": UserResponse::from(user)
+        }
+    })))
+}
+
+//sign in with ethereum (EIP-4361)
+#[debug_handler]
+pub async fn siwe_login(
+    State(state): State<SharedState>,
+    client: ClientInfo,
+    EJson(req): EJson<SiweLoginRequest>,
+) -> AppResult<Json<serde_json::Value>> {
+    let message: SiweMessage = req.message.parse()?;
+    message.validate(&state.config.siwe, chrono::Utc::now())?;
+
+    if !verify_siwe_signature(&req.message, &req.sig, &message.address, state.sig_checker.as_deref()).await? {
+        return Err(AppError::InvalidSignature);
+    }
+    tracing::info!("siwe signature verified success");
+
+    //the message carries the EIP-55 form, accounts registered through EIP-191 keep the address as it was sent,
+    //usually lowercase, so the lowercase form is canonical and the checksummed one is only a fallback
+    let address: Address = message.address.parse().map_err(|_| AppError::SiweError("invalid address".into()))?;
+    let canonical = format!("{:#x}", address);
+
+    //nonce comes from /api/v1/auth/nonce/:address and can only be used once
+    let redis_client = RedisClient::from(state.redis.clone());
+    redis_client
+        .consume_nonce(canonical.as_str(), message.nonce.as_str())
+        .await?;
+
+    let user = match state.store.get_user_by_address(&canonical).await {
+        Err(AppError::UserUnExisted(_)) => state.store.get_user_by_address(&message.address).await?,
+        user => user?,
+    };
+    let tokens = start_session(
+        &redis_client,
+        &state.jwt_handler,
+        &state.store,
+        &user,
+        &client,
+        Some((message.address.as_str(), message.chain_id)),
+    )
+    .await?;
 
     Ok(Json(serde_json::json!({
         "result": {
-            "access_token": token,
+            "access_token": tokens.access_token,
+            "refresh_token": tokens.refresh_token,
             "user_info": UserResponse::from(user)
         }
     })))
@@ -1,4 +1,8 @@
-use crate::server::user::{User, user_service};
+use crate::{
+    common::error::AppResult,
+    helpers::eip712::{self, parse_address, SignatureType, TypedMessage},
+    server::user::{User, user_service},
+};
 use serde::{Deserialize, Serialize};
 
 
@@ -8,6 +12,8 @@ pub struct RegisterRequest {
     pub data: UserInfo,
     pub invited_by: Option<String>,
     pub sig: String,
+    #[serde(default)]
+    pub sig_type: SignatureType,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,6 +38,7 @@ impl From<RegisterRequest> for User {
             verified: false,
             invited_by: None,
             invite_code: user_service::gen_invite_code(8),
+            register_ip: None,
         }
     }
 }
@@ -41,6 +48,8 @@ impl From<RegisterRequest> for User {
 pub struct LoginRequest {
     pub data: LoginData,
     pub sig: String,
+    #[serde(default)]
+    pub sig_type: SignatureType,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -49,3 +58,36 @@ pub struct LoginData {
     pub address: String,
     pub nonce: String,
 }
+
+//Sign-In with Ethereum, `message` is the EIP-4361 text the wallet signed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SiweLoginRequest {
+    pub message: String,
+    pub sig: String,
+}
+
+impl TypedMessage for UserInfo {
+    type Typed = eip712::Register;
+
+    fn to_typed(&self) -> AppResult<Self::Typed> {
+        Ok(eip712::Register {
+            user_name: self.user_name.clone(),
+            email: self.email.clone(),
+            image: self.image.clone(),
+            wallet: parse_address(&self.address)?,
+            nonce: self.nonce.clone(),
+        })
+    }
+}
+
+impl TypedMessage for LoginData {
+    type Typed = eip712::Login;
+
+    fn to_typed(&self) -> AppResult<Self::Typed> {
+        Ok(eip712::Login {
+            content: self.content.clone(),
+            wallet: parse_address(&self.address)?,
+            nonce: self.nonce.clone(),
+        })
+    }
+}
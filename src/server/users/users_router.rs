@@ -8,13 +8,13 @@ pub fn users_router(state: SharedState) -> Router<SharedState> {
     Router::new()
         .route("/info", get(get_user_info))
         .route("/stats", get(get_user_stats))
-        .route("/verify/:address", post(verify_user))
         .layer(middleware::from_fn_with_state(
             state,
             middlewares::auth_middleware,
         ))
         .route("/login", post(login))
+        .route("/siwe", post(siwe_login))
         .route("/", post(register))
         .route("/:username", get(check_username))
+        .route("/:username/resolve", get(resolve_username))
 }
-
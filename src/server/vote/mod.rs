@@ -1,6 +1,7 @@
 mod vote_handler;
 mod vote_router;
 mod vote_message;
+pub mod vote_service;
 
 
 //pub use vote_message::*;
@@ -1,10 +1,10 @@
-use super::vote_message::*;
+use super::{vote_message::*, vote_service};
 use crate::{
     app::SharedState, 
     common::error::{AppResult, AppError}, 
-    server::{middlewares::AuthToken, proposal::proposal_service::get_proposal_status, events::events_message::Event }, 
+    server::{middlewares::AuthClaims, events::events_message::Event }, 
     common::consts,
-    helpers::eip191::verify_signature,
+    helpers::eip712::verify_signature_by_type,
     nostr,
 };
 use axum::{debug_handler, extract::Path, extract::State, extract::Query, extract::Json as EJson, Json};
@@ -14,12 +14,9 @@ use axum::{debug_handler, extract::Path, extract::State, extract::Query, extract
 #[debug_handler]
 pub async fn create_vote(
     State(state): State<SharedState>,
-    AuthToken(user): AuthToken,
-    EJson(CreateVoteRequest{data,sig}): EJson<CreateVoteRequest>,
+    AuthClaims(claim): AuthClaims,
+    EJson(CreateVoteRequest{data,sig,sig_type}): EJson<CreateVoteRequest>,
 ) -> AppResult<Json<serde_json::Value>> {
-    let client = state.jwt_handler.clone();
-    let claim = client.decode_token(user).unwrap();  //TODO address in jwt
-
     if cfg!(not(debug_assertions)) {
         let unverified_data = UnVerifyVoteInfo {
             proposal_id: data.proposal_id.clone(),
@@ -29,52 +26,22 @@ pub async fn create_vote(
         //get user address by lamport id
         let user = state.store.get_user_by_uid(claim.sub.as_str()).await?;
 
-        let verified= verify_signature(&unverified_data, &sig, &user.address)?;
+        let verified= verify_signature_by_type(sig_type, &unverified_data, &sig, &user.address, &state.config.eip712, state.sig_checker.as_deref()).await?;
         if !verified {
             return Err(AppError::InvalidSignature);
         }
     }
 
-    let mut vote_info = data.clone();
-
-    //check energy
-    let energy = state.store.get_user_power(claim.sub.as_str()).await?;
-    if energy < (consts::ENERGY_PROPOSAL_VALUE as i64) {
-        return Err(AppError::InputValidateError("energy not enough".into()));
-    }
-    //check vote_info.proposal_id, proposal_id must be in database, porposal_id must be active
-    let proposal = state.store.get_proposal_by_proposal_id(vote_info.proposal_id.as_str()).await?;
-    if get_proposal_status(proposal.start_time.into(), proposal.end_time.into()) != consts::PROPOSAL_STATUS_ACTIVE {
-        return Err(AppError::InputValidateError("proposal not active".into()));
-    }
-
-    //checkout vote_info.choice, choice must be in proposal options
-    if !proposal.options.contains(&vote_info.choice) {
-        return Err(AppError::InputValidateError("choice not in proposal options".into()));
-    }
-
-    //checkout vote_info.voter_id, voter_id must not be voted before
-    if state.store.is_voted_by_voter_id(claim.sub.as_str(), vote_info.proposal_id.as_str()).await? {
-        return Err(AppError::InputValidateError("voter has voted before".into()));
-    }
-
-    vote_info.voter_id = Some(claim.sub.clone());
-
-    let active_vote = vote_info.into();
-
-    let created_vote = state.store.create_vote(active_vote).await?;
-
-    //award point
-    state
-        .store
-        .award_points(claim.sub.clone(), consts::POINTS_VOTE, consts::POINTS_VOTE_VALUE, "vote reward")
-        .await?;
-
-    //consume energy 
-    state
-        .store
-        .create_energy(claim.sub.clone(), consts::ENERGY_VOTE, consts::ENERGY_VOTE_VALUE)
-        .await?;
+    let proposal = state.store.get_proposal_by_proposal_id(data.proposal_id.as_str()).await?;
+    let created_vote = vote_service::cast_vote(
+        &state.store,
+        &state.config.scoring,
+        claim.sub.as_str(),
+        &proposal,
+        data.choice.as_str(),
+        data.channel.as_str(),
+    )
+    .await?;
 
     let queue = state.queue.clone();
 
@@ -104,21 +71,29 @@ pub async fn create_vote(
 
     }
 
-    queue.add_queue_req_ex(consts::NOSTR_TOPIC, nostr::LamportBinding::new_kind_vote(
-        state.nclient.get_pub_key(),
-        claim.sub.as_str(),
-        created_vote.uid.as_str(),
-        proposal.title.as_str(),
-        format!("{} vote for {}", claim.sub.as_str(), proposal.title.as_str()).as_str(),
-        proposal.start_time.to_string().as_str(),
-        proposal.end_time.to_string().as_str(),
-        proposal.options.join(",").as_str(),
-        "",
-    )).await?;
+    let nostr_event_id = state
+        .publish_nostr(
+            nostr::LamportBinding::new_kind2328(
+                state.nclient.get_pub_key(),
+                nostr::schema::VoteEvent {
+                    lamport_id: claim.sub.clone(),
+                    vote_id: created_vote.uid.clone(),
+                    proposal_id: created_vote.proposal_id.clone(),
+                    choice: created_vote.choice.clone(),
+                    title: proposal.title.clone(),
+                    options: proposal.options.clone(),
+                    start_time: proposal.start_time.to_rfc3339(),
+                    end_time: proposal.end_time.to_rfc3339(),
+                    sig: sig.clone(),
+                },
+            ),
+            Some((consts::NOSTR_OBJECT_VOTE, created_vote.uid.as_str())),
+        )
+        .await?;
 
     Ok(Json(serde_json::json!({
-        "result": VoteInfo::from(created_vote)
-        
+        "result": VoteInfo::from(created_vote),
+        "nostr_event_id": nostr_event_id
     })))
 }
 
@@ -172,10 +147,12 @@ pub async fn count_votes_by_proposal_id_and_choice(
     Query(GetChoiceCountRequest{proposal_id,choice}): Query<GetChoiceCountRequest>,
 ) -> AppResult<Json<serde_json::Value>> {
     let count = state.store.count_votes_by_proposal_id_and_choice(proposal_id.as_str(), choice.as_str()).await?;
+    let weight = state.store.sum_vote_weight_by_proposal_id_and_choice(proposal_id.as_str(), choice.as_str()).await?;
 
     Ok(Json(serde_json::json!({
         "result": {
-            "count": count
+            "count": count,
+            "weight": weight
         }
     })))
 }
@@ -184,12 +161,9 @@ pub async fn count_votes_by_proposal_id_and_choice(
 #[debug_handler]
 pub async fn get_proposal_vote_by_voter_id(
     State(state): State<SharedState>,
-    AuthToken(user): AuthToken,
+    AuthClaims(claim): AuthClaims,
     Path(proposal_id): Path<String>,
 ) -> AppResult<Json<serde_json::Value>> {
-    let client = state.jwt_handler.clone();
-    let claim = client.decode_token(user).unwrap();
-
     match state.store.get_proposal_vote_by_voter_id(claim.sub.as_str(), proposal_id.as_str()).await {
         Ok(v) => Ok(Json(serde_json::json!({
             "result": VoteInfo::from(v)
@@ -1,4 +1,8 @@
-use crate::database::entities::vote;
+use crate::{
+    common::error::AppResult,
+    database::entities::vote,
+    helpers::eip712::{self, SignatureType, TypedMessage},
+};
 use sea_orm::*;
 use uuid::Uuid;
 
@@ -6,6 +10,8 @@ use uuid::Uuid;
 pub struct CreateVoteRequest {
     pub data: VoteInfo,
     pub sig: String,
+    #[serde(default)]
+    pub sig_type: SignatureType,
 }
 
 //create VoteInfo struct
@@ -25,15 +31,27 @@ pub struct UnVerifyVoteInfo {
     pub channel: String,
 }
 
-//impl into active model for VoteInfo
-impl Into<vote::ActiveModel> for VoteInfo {
-    fn into(self) -> vote::ActiveModel {
+impl TypedMessage for UnVerifyVoteInfo {
+    type Typed = eip712::CastVote;
+
+    fn to_typed(&self) -> AppResult<Self::Typed> {
+        Ok(eip712::CastVote {
+            proposal_id: self.proposal_id.clone(),
+            choice: self.choice.clone(),
+            channel: self.channel.clone(),
+        })
+    }
+}
+
+//impl from VoteInfo for active model
+impl From<VoteInfo> for vote::ActiveModel {
+    fn from(info: VoteInfo) -> Self {
         vote::ActiveModel {
             uid: Set(Uuid::new_v4().to_string()),
-            voter_id: Set(self.voter_id.unwrap()),
-            proposal_id: Set(self.proposal_id),
-            choice: Set(self.choice),
-            channel: Set(self.channel),
+            voter_id: Set(info.voter_id.unwrap()),
+            proposal_id: Set(info.proposal_id),
+            choice: Set(info.choice),
+            channel: Set(info.channel),
             created_at: Set(chrono::Utc::now().into()),
             ..Default::default()
         }
@@ -0,0 +1,77 @@
+use super::vote_message::VoteInfo;
+use crate::{
+    common::{config::ScoringConfig, consts, error::{AppError, AppResult}},
+    database::{entities::{proposals, vote}, Storage},
+    server::proposal::proposal_service::get_proposal_status,
+};
+use sea_orm::Set;
+
+//checks and records a vote, shared by the api and votes cast directly on nostr
+pub async fn cast_vote(
+    store: &Storage,
+    scoring: &ScoringConfig,
+    voter_id: &str,
+    proposal: &proposals::Model,
+    choice: &str,
+    channel: &str,
+) -> AppResult<vote::Model> {
+    //deleted, banned and suspended voters can not vote
+    let voter = store.get_user_by_uid(voter_id).await?;
+    if voter.deleted_at.is_some() {
+        return Err(AppError::Forbidden(format!("user {} is deleted", voter_id)));
+    }
+    if let Some(sanction) = store.get_active_sanction(voter_id).await? {
+        return Err(AppError::Forbidden(format!("account is under {}: {}", sanction.kind, sanction.reason)));
+    }
+
+    //check energy
+    let energy = store.get_user_power(voter_id).await?;
+    if energy < (consts::ENERGY_PROPOSAL_VALUE as i64) {
+        return Err(AppError::InputValidateError("energy not enough".into()));
+    }
+    //proposal must be active
+    if get_proposal_status(proposal.start_time.into(), proposal.end_time.into()) != consts::PROPOSAL_STATUS_ACTIVE {
+        return Err(AppError::InputValidateError("proposal not active".into()));
+    }
+
+    //checkout choice, choice must be in proposal options
+    if !proposal.options.iter().any(|o| o == choice) {
+        return Err(AppError::InputValidateError("choice not in proposal options".into()));
+    }
+
+    //checkout voter_id, voter_id must not be voted before
+    if store.is_voted_by_voter_id(voter_id, proposal.proposal_id.as_str()).await? {
+        return Err(AppError::InputValidateError("voter has voted before".into()));
+    }
+
+    //check humanity score, it is also the vote's weight when votes are weighted
+    let weight = if scoring.min_vote_score > 0 || scoring.weighted_votes {
+        let score = store.require_humanity_score(voter_id, scoring.min_vote_score).await?;
+        if scoring.weighted_votes { score.score as i32 } else { consts::VOTE_FULL_WEIGHT }
+    } else {
+        consts::VOTE_FULL_WEIGHT
+    };
+
+    let vote_info = VoteInfo {
+        voter_id: Some(voter_id.to_string()),
+        proposal_id: proposal.proposal_id.clone(),
+        choice: choice.to_string(),
+        channel: channel.to_string(),
+    };
+    let mut active_vote: vote::ActiveModel = vote_info.into();
+    active_vote.weight = Set(weight);
+
+    let created_vote = store.create_vote(active_vote).await?;
+
+    //award point
+    store
+        .award_points(voter_id.to_string(), consts::POINTS_VOTE, consts::POINTS_VOTE_VALUE, "vote reward")
+        .await?;
+
+    //consume energy
+    store
+        .create_energy(voter_id.to_string(), consts::ENERGY_VOTE, consts::ENERGY_VOTE_VALUE)
+        .await?;
+
+    Ok(created_vote)
+}
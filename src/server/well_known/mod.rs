@@ -0,0 +1,4 @@
+mod well_known_handler;
+mod well_known_router;
+
+pub use well_known_router::well_known_router;
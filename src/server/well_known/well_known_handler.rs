@@ -0,0 +1,36 @@
+use crate::{app::SharedState, common::error::AppResult};
+use axum::{debug_handler, extract::Query, extract::State, Json};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct Nip05Query {
+    pub name: Option<String>,
+}
+
+//public keys for verifying access tokens, served in the plain JWKS format
+#[debug_handler]
+pub async fn get_jwks(State(state): State<SharedState>) -> Json<serde_json::Value> {
+    Json(state.jwt_handler.jwks())
+}
+
+//NIP-05, username@our-domain resolves to the nostr key linked to that username
+#[debug_handler]
+pub async fn get_nostr_json(
+    State(state): State<SharedState>,
+    Query(Nip05Query { name }): Query<Nip05Query>,
+) -> AppResult<Json<serde_json::Value>> {
+    let mut names = serde_json::Map::new();
+    let mut relays = serde_json::Map::new();
+
+    if let Some(name) = name {
+        if let Some(pubkey) = state.store.get_nostr_pubkey_by_username(name.as_str()).await? {
+            relays.insert(pubkey.clone(), serde_json::json!(state.config.nostr.write_relays()));
+            names.insert(name, serde_json::Value::String(pubkey));
+        }
+    }
+
+    Ok(Json(serde_json::json!({
+        "names": names,
+        "relays": relays
+    })))
+}
@@ -0,0 +1,9 @@
+use super::well_known_handler::*;
+use crate::app::SharedState;
+use axum::{routing::get, Router};
+
+pub fn well_known_router() -> Router<SharedState> {
+    Router::new()
+        .route("/jwks.json", get(get_jwks))
+        .route("/nostr.json", get(get_nostr_json))
+}